@@ -36,6 +36,10 @@ fn main() {
         ("hello", many(r#""hello""#, N)),
         ("hello-world", many(r#""hello\nworld""#, N)),
         ("arr", many("[]", N)),
+        (
+            "indented",
+            many("\n        {\n            \"a\": 1\n        }", N / 4),
+        ),
         ("tree", tree),
     ] {
         print!("{name}");
@@ -45,8 +45,7 @@ impl<Num: Deref<Target = str>, Str: Deref<Target = str>> TryFrom<value::Value<Nu
 fn process<L: LexAlloc>(cli: &Cli, lexer: &mut L) -> Result<(), Error> {
     if cli.parse {
         if cli.many {
-            let vs = core::iter::from_fn(|| Some(value::parse_unbounded(lexer.ws_token()?, lexer)));
-            for v in vs {
+            for v in value::parse_many(lexer) {
                 let v = v?;
                 if !cli.silent {
                     println!("{}", v)
@@ -67,6 +66,7 @@ fn process<L: LexAlloc>(cli: &Cli, lexer: &mut L) -> Result<(), Error> {
             if cli.silent {
                 lex(token, lexer, &|_| ())?;
             } else {
+                #[allow(clippy::unwrap_or_default)]
                 let path: Vec<_> = cli.path.as_deref().map(parse_path).unwrap_or(Vec::new());
                 use std::io::Write;
                 filter(&path, token, lexer, &|b| io::stdout().write_all(b).unwrap())?;
@@ -107,20 +107,16 @@ fn filter<L: LexAlloc>(
                 out
             })?;
         }
-        Token::LCurly => {
-            let mut idx = 0;
+        Token::LCurly if elem.strs.is_empty() => {
             lexer.seq(Token::RCurly, |token, lexer| {
-                idx += 1;
-
-                let key = lexer.str_colon(token, |lexer| lexer.str_string().map_err(Error::Str))?;
-                let token = lexer.ws_token().ok_or(Expect::Value)?;
-                if elem.strs.is_empty() || elem.strs.iter().any(|s| s == key.deref()) {
-                    filter(rest, token, lexer, print)
-                } else {
-                    hifijson::ignore::parse(token, lexer)
-                }
+                lexer.str_colon(token, |lexer| lexer.str_ignore().map_err(Error::Str))?;
+                filter(rest, lexer.ws_token().ok_or(Expect::Value)?, lexer, print)
             })?;
         }
+        Token::LCurly => {
+            let keys: Vec<&str> = elem.strs.iter().map(|s| s.as_str()).collect();
+            lexer.object_select(&keys, |_, token, lexer| filter(rest, token, lexer, print))?;
+        }
         _ => todo!(),
     }
     Ok(())
@@ -184,8 +180,7 @@ fn process_file(cli: &Cli, path: &str) -> io::Result<()> {
 }
 
 fn process_stdin(cli: &Cli) -> io::Result<()> {
-    use io::Read;
-    process(cli, &mut IterLexer::new(io::stdin().bytes())).unwrap();
+    process(cli, &mut IterLexer::from_read(io::stdin())).unwrap();
     Ok(())
 }
 
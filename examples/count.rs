@@ -39,6 +39,7 @@ fn main() {
         process(hifijson::SliceLexer::new(file.as_bytes()))
     } else {
         use std::io::Read;
+        #[allow(clippy::unbuffered_bytes)]
         process(hifijson::IterLexer::new(std::io::stdin().bytes()))
     };
     println!("{:?}", n)
@@ -0,0 +1,58 @@
+//! Arrays.
+
+use crate::token::Lex;
+use crate::{ignore, Error, Expect, SliceLexer, Token};
+
+/// Yield the raw input bytes of every element of an array.
+///
+/// The lexer has to be positioned right after the opening `[` of the array.
+/// Every element is validated by [`ignore::parse`], but not otherwise interpreted,
+/// so this never allocates.
+/// The bytes of an element do not include the whitespace surrounding it.
+/// After the last element, the lexer is positioned right after the closing `]`.
+///
+/// ~~~
+/// use hifijson::{token::Lex, SliceLexer, Token};
+/// let mut lexer = SliceLexer::new(br#"[1, {"a": [true]}, "x"]"#);
+/// assert_eq!(lexer.ws_token(), Some(Token::LSquare));
+/// let elems: Result<Vec<_>, _> = hifijson::array::raw_elements(&mut lexer).collect();
+/// assert_eq!(elems.unwrap(), [&b"1"[..], br#"{"a": [true]}"#, br#""x""#]);
+/// ~~~
+pub fn raw_elements<'l, 'a>(
+    lexer: &'l mut SliceLexer<'a>,
+) -> impl Iterator<Item = Result<&'a [u8], Error>> + 'l {
+    let mut first = true;
+    let mut done = false;
+    core::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let elem = next_element(lexer, core::mem::take(&mut first)).transpose();
+        done = !matches!(elem, Some(Ok(_)));
+        elem
+    })
+}
+
+fn next_element<'a>(lexer: &mut SliceLexer<'a>, first: bool) -> Result<Option<&'a [u8]>, Error> {
+    if !first {
+        match lexer.ws_token().ok_or(Expect::CommaOrEnd)? {
+            Token::RSquare => return Ok(None),
+            Token::Comma => (),
+            _ => return Err(Expect::CommaOrEnd)?,
+        }
+    }
+
+    lexer.eat_whitespace();
+    let start = lexer.as_slice();
+    let token = if first {
+        let token = lexer.ws_token().ok_or(Expect::ValueOrEnd)?;
+        if token == Token::RSquare {
+            return Ok(None);
+        }
+        token
+    } else {
+        lexer.ws_token().ok_or(Expect::Value)?
+    };
+    ignore::parse(token, lexer)?;
+    Ok(Some(&start[..start.len() - lexer.as_slice().len()]))
+}
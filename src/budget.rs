@@ -0,0 +1,187 @@
+//! Limiting the memory allocated for strings and numbers.
+//!
+//! When parsing untrusted input, it can be useful to cap
+//! the total number of bytes that strings and numbers may occupy.
+//! For this, you can wrap any lexer in a [`BudgetLexer`],
+//! which charges every string and number to a given budget.
+//!
+//! ~~~
+//! use hifijson::{budget::BudgetLexer, token::Lex, value, Error, SliceLexer};
+//! let json = br#"["abc", "def", "ghi"]"#;
+//! let mut lexer = BudgetLexer::new(SliceLexer::new(json), 8);
+//! let v = lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded));
+//! assert_eq!(v.unwrap_err(), Error::Budget);
+//! ~~~
+
+use crate::{num, str, Error, Position, Read, Write};
+
+/// Lexer that fails once the strings and numbers it has lexed exceed a budget.
+///
+/// All lexing is delegated to the inner lexer.
+/// Strings and numbers are lexed with a maximal length of the remaining budget,
+/// so lexing stops as soon as a string or number exceeds the budget,
+/// before the whole string or number is stored.
+/// The error yielded in that case depends on how the string or number is lexed;
+/// [`BudgetLexer::run`] turns any error after exceeding the budget into [`Error::Budget`].
+pub struct BudgetLexer<L> {
+    lexer: L,
+    /// number of bytes that may still be lexed
+    remaining: usize,
+    /// has the budget been exceeded?
+    exhausted: bool,
+}
+
+impl<L> BudgetLexer<L> {
+    /// Wrap a lexer such that strings and numbers may occupy at most `budget` bytes.
+    pub fn new(lexer: L, budget: usize) -> Self {
+        Self {
+            lexer,
+            remaining: budget,
+            exhausted: false,
+        }
+    }
+
+    /// Return the inner lexer.
+    pub fn into_inner(self) -> L {
+        self.lexer
+    }
+
+    /// Return the number of bytes that may still be lexed.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Return true if the budget has been exceeded.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Run `f`, failing with [`Error::Budget`] if the budget has been exceeded.
+    pub fn run<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        let y = f(self);
+        if self.exhausted {
+            Err(Error::Budget)
+        } else {
+            y
+        }
+    }
+
+    /// Subtract `n` bytes from the budget, returning false if it does not suffice.
+    fn charge(&mut self, n: usize) -> bool {
+        match self.remaining.checked_sub(n) {
+            Some(remaining) => self.remaining = remaining,
+            None => {
+                self.remaining = 0;
+                self.exhausted = true;
+            }
+        }
+        !self.exhausted
+    }
+}
+
+impl<L: Position> Position for BudgetLexer<L> {
+    fn position(&self) -> usize {
+        self.lexer.position()
+    }
+}
+
+impl<L: Read> Read for BudgetLexer<L> {
+    fn strip_prefix(&mut self, s: &[u8]) -> bool {
+        self.lexer.strip_prefix(s)
+    }
+
+    fn skip_until(&mut self, stop: impl FnMut(u8) -> bool) {
+        self.lexer.skip_until(stop)
+    }
+
+    fn skip_next_until(&mut self, stop: impl FnMut(u8) -> bool) {
+        self.lexer.skip_next_until(stop)
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        self.lexer.read()
+    }
+
+    fn read_next(&mut self) {
+        self.lexer.read_next()
+    }
+
+    fn peek_next(&self) -> Option<&u8> {
+        self.lexer.peek_next()
+    }
+
+    fn take_next(&mut self) -> Option<u8> {
+        self.lexer.take_next()
+    }
+}
+
+impl<L: Write> Write for BudgetLexer<L> {
+    type Bytes = L::Bytes;
+
+    fn write_until(&mut self, bytes: &mut Self::Bytes, mut stop: impl FnMut(u8) -> bool) {
+        let mut written = 0;
+        let remaining = self.remaining;
+        // stop writing as soon as the budget is exceeded
+        self.lexer.write_until(bytes, |c| {
+            stop(c) || {
+                written += 1;
+                written > remaining
+            }
+        });
+        self.charge(written);
+    }
+}
+
+impl<L: num::LexWrite> num::LexWrite for BudgetLexer<L> {
+    type Num = L::Num;
+
+    fn num_bytes_bounded(
+        &mut self,
+        bytes: &mut Self::Bytes,
+        max: usize,
+    ) -> Result<num::Parts, num::Error> {
+        let parts = self.lexer.num_bytes_bounded(bytes, max.min(self.remaining));
+        if matches!(parts, Err(num::Error::TooLong)) && self.remaining < max {
+            self.exhausted = true;
+        }
+        let parts = parts?;
+        self.charge(bytes.len());
+        Ok(parts)
+    }
+
+    fn num_string_bounded(&mut self, max: usize) -> Result<(Self::Num, num::Parts), num::Error> {
+        let n = self.lexer.num_string_bounded(max.min(self.remaining));
+        if matches!(n, Err(num::Error::TooLong)) && self.remaining < max {
+            self.exhausted = true;
+        }
+        let (num, parts) = n?;
+        self.charge(num.len());
+        Ok((num, parts))
+    }
+}
+
+impl<L: str::LexAlloc> str::LexAlloc for BudgetLexer<L> {
+    type Str = L::Str;
+
+    fn str_string_bounded(&mut self, max: usize) -> Result<Self::Str, str::Error> {
+        let s = self.lexer.str_string_bounded(max.min(self.remaining));
+        if matches!(s, Err(str::Error::TooLong)) && self.remaining < max {
+            self.exhausted = true;
+        }
+        let s = s?;
+        self.charge(s.len());
+        Ok(s)
+    }
+
+    fn str_string_lenient_bounded(&mut self, max: usize) -> Result<Self::Str, str::Error> {
+        let s = self
+            .lexer
+            .str_string_lenient_bounded(max.min(self.remaining));
+        if matches!(s, Err(str::Error::TooLong)) && self.remaining < max {
+            self.exhausted = true;
+        }
+        let s = s?;
+        self.charge(s.len());
+        Ok(s)
+    }
+}
@@ -93,6 +93,27 @@ pub(crate) fn decode_hex(val: u8) -> Option<u8> {
     }
 }
 
+/// Decode an escape sequence at the start of `s` (without leading `\`).
+///
+/// Return the decoded character and the number of bytes consumed.
+/// A high surrogate such as `\uD83D` has to be followed by
+/// an escaped low surrogate, which is consumed as well.
+///
+/// ~~~
+/// use hifijson::escape::{decode, Error};
+/// assert_eq!(decode(b"n"), Ok(('\n', 1)));
+/// assert_eq!(decode(b"u00e9 rest"), Ok(('\u{e9}', 5)));
+/// assert_eq!(decode(br"uD83D\uDE00"), Ok(('\u{1F600}', 11)));
+/// assert_eq!(decode(b"uD83D"), Err(Error::ExpectedLowSurrogate));
+/// assert_eq!(decode(b"x"), Err(Error::UnknownKind));
+/// ~~~
+pub fn decode(s: &[u8]) -> Result<(char, usize), Error> {
+    let mut lexer = crate::SliceLexer::new(s);
+    let escape = lexer.escape()?;
+    let c = lexer.escape_char(escape)?;
+    Ok((c, s.len() - lexer.as_slice().len()))
+}
+
 /// Escape sequence lexing error.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -148,17 +169,43 @@ pub trait Lex: Read {
         let typ = self.read().ok_or(Error::Eof)?;
         let escape = Escape::try_from(typ).ok_or(Error::UnknownKind)?;
         if matches!(escape, Escape::Unicode(_)) {
-            let mut hex = 0;
-            for _ in 0..4 {
-                let h = self.read().ok_or(Error::Eof)?;
-                let h = decode_hex(h).ok_or(Error::InvalidHex)?;
-                hex = (hex << 4) + (h as u16);
+            match self.hex_partial()? {
+                (hex, 4) => Ok(Escape::Unicode(hex)),
+                _ => Err(Error::Eof),
             }
-            Ok(Escape::Unicode(hex))
         } else {
             Ok(escape)
         }
     }
+
+    /// Read up to four hexadecimal digits, such as the `0009` in `\u0009`.
+    ///
+    /// Return the value of the digits and their number.
+    /// The number of digits is smaller than four only if the input ends early,
+    /// which [`Lex::escape`] reports as [`Error::Eof`].
+    /// Fail if any other character than a hexadecimal digit is encountered.
+    ///
+    /// ~~~
+    /// use hifijson::escape::{Error, Lex};
+    /// let mut lexer = hifijson::SliceLexer::new(b"00e4");
+    /// assert_eq!(lexer.hex_partial(), Ok((0xe4, 4)));
+    /// let mut lexer = hifijson::SliceLexer::new(b"0e");
+    /// assert_eq!(lexer.hex_partial(), Ok((0xe, 2)));
+    /// let mut lexer = hifijson::SliceLexer::new(b"0x");
+    /// assert_eq!(lexer.hex_partial(), Err(Error::InvalidHex));
+    /// ~~~
+    fn hex_partial(&mut self) -> Result<(u16, u8), Error> {
+        let mut hex = 0;
+        for len in 0..4 {
+            let h = match self.read() {
+                Some(h) => h,
+                None => return Ok((hex, len)),
+            };
+            let h = decode_hex(h).ok_or(Error::InvalidHex)?;
+            hex = (hex << 4) + (h as u16);
+        }
+        Ok((hex, 4))
+    }
 }
 
 impl<T> Lex for T where T: Read {}
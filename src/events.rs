@@ -0,0 +1,191 @@
+//! Pull-based parsing to events.
+//!
+//! Unlike the parsers in [`value`](crate::value),
+//! the event parser does not use recursion, but
+//! keeps track of the arrays and objects that it is in via an explicit stack.
+//! That way, it can process arbitrarily deeply nested values in constant stack space.
+//!
+//! ~~~
+//! use hifijson::events::{Event, Parser};
+//! let lexer = hifijson::SliceLexer::new(br#"[1, {"a": null}]"#);
+//! let events: Result<Vec<_>, _> = Parser::new(lexer).collect();
+//! let events = events.unwrap();
+//! assert!(matches!(
+//!     &events[..],
+//!     [
+//!         Event::StartArray,
+//!         Event::Number(_),
+//!         Event::StartObject,
+//!         Event::Key(_),
+//!         Event::Null,
+//!         Event::EndObject,
+//!         Event::EndArray,
+//!     ]
+//! ));
+//! ~~~
+
+use crate::{num, Error, Expect, LexAlloc, Token};
+use alloc::vec::Vec;
+
+/// Parse event.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Event<Num, Str> {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// string representation of a number with positional information
+    Number((Num, num::Parts)),
+    /// string
+    String(Str),
+    /// `[`
+    StartArray,
+    /// `]`
+    EndArray,
+    /// `{`
+    StartObject,
+    /// `}`
+    EndObject,
+    /// object key, including the following `:`
+    Key(Str),
+}
+
+/// Event with number and string types of the lexer `L`.
+type LexEvent<L> = Event<<L as num::LexWrite>::Num, <L as crate::str::LexAlloc>::Str>;
+
+/// What the parser expects next.
+#[derive(Copy, Clone)]
+enum State {
+    /// a value at the top level
+    Start,
+    /// a value or the end of a sequence, right after `[` or `{`
+    Open,
+    /// a comma or the end of a sequence
+    Next,
+    /// a value after an object key
+    Value,
+    /// nothing, because the top-level value is complete or an error occurred
+    Done,
+}
+
+/// Event parser for a single value.
+pub struct Parser<L> {
+    lexer: L,
+    /// closing tokens of the arrays and objects that we are in
+    stack: Vec<Token>,
+    state: State,
+}
+
+impl<L: LexAlloc> Parser<L> {
+    /// Create a new event parser for a single value.
+    pub fn new(lexer: L) -> Self {
+        Self {
+            lexer,
+            stack: Vec::new(),
+            state: State::Start,
+        }
+    }
+
+    /// Return the lexer, positioned after the last returned event.
+    pub fn into_inner(self) -> L {
+        self.lexer
+    }
+
+    /// Return the next event, or `None` if the value has been completely parsed.
+    ///
+    /// After an error has been returned, this always returns `None`.
+    pub fn next_event(&mut self) -> Option<Result<LexEvent<L>, Error>> {
+        let event = self.step();
+        if event.is_err() {
+            self.state = State::Done
+        }
+        event.transpose()
+    }
+
+    fn step(&mut self) -> Result<Option<LexEvent<L>>, Error> {
+        let lexer = &mut self.lexer;
+        let token = match self.state {
+            State::Done => return Ok(None),
+            State::Start | State::Value => lexer.ws_token().ok_or(Expect::Value)?,
+            State::Open => {
+                let token = lexer.ws_token().ok_or(Expect::ValueOrEnd)?;
+                if self.stack.last() == Some(&token) {
+                    return Ok(Some(self.close()));
+                }
+                if self.stack.last() == Some(&Token::RCurly) {
+                    return self.key(token).map(Some);
+                }
+                token
+            }
+            State::Next => match lexer.ws_token().ok_or(Expect::CommaOrEnd)? {
+                Token::Comma => {
+                    let token = lexer.ws_token().ok_or(Expect::Value)?;
+                    if self.stack.last() == Some(&Token::RCurly) {
+                        return self.key(token).map(Some);
+                    }
+                    token
+                }
+                token if self.stack.last() == Some(&token) => return Ok(Some(self.close())),
+                _ => return Err(Expect::CommaOrEnd)?,
+            },
+        };
+        self.value(token).map(Some)
+    }
+
+    /// State after a complete value.
+    fn after_value(&self) -> State {
+        if self.stack.is_empty() {
+            State::Done
+        } else {
+            State::Next
+        }
+    }
+
+    fn close(&mut self) -> LexEvent<L> {
+        let event = match self.stack.pop() {
+            Some(Token::RSquare) => Event::EndArray,
+            _ => Event::EndObject,
+        };
+        self.state = self.after_value();
+        event
+    }
+
+    fn key(&mut self, token: Token) -> Result<LexEvent<L>, Error> {
+        let key = self
+            .lexer
+            .str_colon(token, |lexer| lexer.str_string().map_err(Error::Str))?;
+        self.state = State::Value;
+        Ok(Event::Key(key))
+    }
+
+    fn value(&mut self, token: Token) -> Result<LexEvent<L>, Error> {
+        let event = match token {
+            Token::Null => Event::Null,
+            Token::True => Event::Bool(true),
+            Token::False => Event::Bool(false),
+            Token::DigitOrMinus => Event::Number(self.lexer.num_string()?),
+            Token::Quote => Event::String(self.lexer.str_string()?),
+            Token::LSquare | Token::LCurly => {
+                let (close, event) = if token == Token::LSquare {
+                    (Token::RSquare, Event::StartArray)
+                } else {
+                    (Token::RCurly, Event::StartObject)
+                };
+                self.stack.push(close);
+                self.state = State::Open;
+                return Ok(event);
+            }
+            _ => return Err(Expect::Value)?,
+        };
+        self.state = self.after_value();
+        Ok(event)
+    }
+}
+
+impl<L: LexAlloc> Iterator for Parser<L> {
+    type Item = Result<LexEvent<L>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event()
+    }
+}
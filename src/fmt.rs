@@ -0,0 +1,209 @@
+//! Canonical output.
+//!
+//! This module writes JSON in the canonical form described by the
+//! [JSON Canonicalization Scheme (JCS)](https://www.rfc-editor.org/rfc/rfc8785), meaning that:
+//!
+//! * no whitespace is output,
+//! * object keys are sorted by their UTF-16 code units,
+//! * numbers are printed like JavaScript prints them, and
+//! * strings escape only the characters that have to be escaped.
+//!
+//! This is useful to obtain a unique representation of JSON data,
+//! for example in order to sign it.
+
+use crate::value::Value;
+use crate::{num, token, LexAlloc, Token};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Deref;
+use std::io::{self, Write};
+
+/// Canonicalisation error.
+#[derive(Debug)]
+pub enum Error {
+    /// parse error
+    Parse(crate::Error),
+    /// error while writing output
+    Io(io::Error),
+    /// `1e1000` (number cannot be represented as finite double-precision float)
+    Number(String),
+}
+
+impl_from!(crate::Error, Error, Error::Parse);
+impl_from!(token::Expect, Error, |e| Error::Parse(crate::Error::Token(
+    e
+)));
+impl_from!(io::Error, Error, Error::Io);
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use Error::*;
+        match self {
+            Parse(e) => e.fmt(f),
+            Io(e) => e.fmt(f),
+            Number(n) => write!(f, "number not representable: {}", n),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Read exactly one value from the lexer and write its canonical form to `w`.
+///
+/// Arrays are written while they are read, whereas
+/// the members of every object are buffered in order to sort them.
+///
+/// ~~~
+/// let mut lexer = hifijson::SliceLexer::new(br#"{"b": [1.50, 1e3], "a": "\u00e4"}"#);
+/// let mut out = Vec::new();
+/// hifijson::fmt::canonicalize(&mut lexer, &mut out).unwrap();
+/// assert_eq!(out, r#"{"a":"ä","b":[1.5,1000]}"#.as_bytes());
+/// ~~~
+pub fn canonicalize<L: LexAlloc, W: Write>(lexer: &mut L, w: &mut W) -> Result<(), Error> {
+    lexer.exactly_one(|token, lexer| value(token, lexer, w))
+}
+
+/// Write the canonical form of an already parsed value to `w`.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let json = br#"{"b": [1.50, 1e3], "a": "\u00e4"}"#;
+/// let v = SliceLexer::new(json).exactly_one(value::parse_unbounded).unwrap();
+/// let mut out = Vec::new();
+/// hifijson::fmt::write_value(&v, &mut out).unwrap();
+/// assert_eq!(out, r#"{"a":"ä","b":[1.5,1000]}"#.as_bytes());
+/// ~~~
+pub fn write_value<Num, Str, W>(v: &Value<Num, Str>, w: &mut W) -> Result<(), Error>
+where
+    Num: Deref<Target = str>,
+    Str: Deref<Target = str>,
+    W: Write,
+{
+    match v {
+        Value::Null => w.write_all(b"null")?,
+        Value::Bool(true) => w.write_all(b"true")?,
+        Value::Bool(false) => w.write_all(b"false")?,
+        Value::Number((n, parts)) => number(n, parts, w)?,
+        Value::String(s) => string(s, w)?,
+        Value::Array(a) => {
+            w.write_all(b"[")?;
+            for (i, v) in a.iter().enumerate() {
+                if i > 0 {
+                    w.write_all(b",")?;
+                }
+                write_value(v, w)?;
+            }
+            w.write_all(b"]")?
+        }
+        Value::Object(o) => {
+            let mut members: Vec<_> = o.iter().collect();
+            members.sort_by(|(k1, _), (k2, _)| k1.encode_utf16().cmp(k2.encode_utf16()));
+
+            w.write_all(b"{")?;
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    w.write_all(b",")?;
+                }
+                string(k, w)?;
+                w.write_all(b":")?;
+                write_value(v, w)?;
+            }
+            w.write_all(b"}")?
+        }
+    }
+    Ok(())
+}
+
+/// Write the canonical form of the value starting with `token`.
+fn value<L: LexAlloc, W: Write>(token: Token, lexer: &mut L, w: &mut W) -> Result<(), Error> {
+    match token {
+        Token::Null => w.write_all(b"null")?,
+        Token::True => w.write_all(b"true")?,
+        Token::False => w.write_all(b"false")?,
+        Token::DigitOrMinus => {
+            let (n, parts) = lexer.num_string().map_err(crate::Error::Num)?;
+            number(&n, &parts, w)?
+        }
+        Token::Quote => string(&lexer.str_string().map_err(crate::Error::Str)?, w)?,
+        Token::LSquare => {
+            w.write_all(b"[")?;
+            let mut first = true;
+            lexer.seq(Token::RSquare, |token, lexer| {
+                if !core::mem::take(&mut first) {
+                    w.write_all(b",")?;
+                }
+                value(token, lexer, w)
+            })?;
+            w.write_all(b"]")?
+        }
+        Token::LCurly => {
+            let mut members: Vec<(L::Str, Vec<u8>)> = Vec::new();
+            lexer.seq(Token::RCurly, |token, lexer| {
+                let key = lexer
+                    .str_colon(token, |lexer| lexer.str_string().map_err(crate::Error::Str))?;
+                let token = lexer.ws_token().ok_or(token::Expect::Value)?;
+                let mut v = Vec::new();
+                value(token, lexer, &mut v)?;
+                members.push((key, v));
+                Ok::<_, Error>(())
+            })?;
+            members.sort_by(|(k1, _), (k2, _)| k1.encode_utf16().cmp(k2.encode_utf16()));
+
+            w.write_all(b"{")?;
+            for (i, (k, v)) in members.iter().enumerate() {
+                if i > 0 {
+                    w.write_all(b",")?;
+                }
+                string(k, w)?;
+                w.write_all(b":")?;
+                w.write_all(v)?;
+            }
+            w.write_all(b"}")?
+        }
+        _ => Err(token::Expect::Value)?,
+    }
+    Ok(())
+}
+
+/// Write a number like JavaScript's `Number.prototype.toString()`.
+fn number<W: Write>(n: &str, parts: &num::Parts, w: &mut W) -> Result<(), Error> {
+    let f = parts.to_f64(n);
+    if !f.is_finite() {
+        return Err(Error::Number(n.to_string()));
+    }
+    if f == 0.0 {
+        return Ok(w.write_all(b"0")?);
+    }
+    if f < 0.0 {
+        w.write_all(b"-")?;
+    }
+
+    // shortest representation that round-trips, such as `1.2345e-7`
+    let sci = std::format!("{:e}", f.abs());
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exp: i32 = exp.parse().unwrap();
+    // number of digits before the decimal point
+    let n = exp + 1;
+    let k = digits.len() as i32;
+
+    if k <= n && n <= 21 {
+        write!(w, "{}{}", digits, "0".repeat((n - k) as usize))?
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        write!(w, "{}.{}", int, frac)?
+    } else if -6 < n && n <= 0 {
+        write!(w, "0.{}{}", "0".repeat(-n as usize), digits)?
+    } else {
+        let (head, tail) = digits.split_at(1);
+        let sign = if n > 0 { '+' } else { '-' };
+        let dot = if tail.is_empty() { "" } else { "." };
+        write!(w, "{}{}{}e{}{}", head, dot, tail, sign, (n - 1).abs())?
+    }
+    Ok(())
+}
+
+/// Write a string, escaping only `"`, `\`, and control characters.
+fn string<S: Deref<Target = str>, W: Write>(s: &S, w: &mut W) -> io::Result<()> {
+    crate::str::encode_to(s, w)
+}
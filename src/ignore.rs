@@ -1,18 +1,207 @@
 //! Discarding values.
 
-use crate::{Error, Expect, Lex, Token};
+use crate::{token::Kind, Error, Expect, Lex, Token};
 
-/// Parse and discard a value.
-pub fn parse<L: Lex>(token: Token, lexer: &mut L) -> Result<(), Error> {
+/// Parse and discard a value, using `f` to parse recursive values inside arrays / objects.
+fn parse_with<L: Lex>(
+    token: Token,
+    lexer: &mut L,
+    mut f: impl FnMut(Token, &mut L) -> Result<(), Error>,
+) -> Result<(), Error> {
     match token {
         Token::Null | Token::True | Token::False => Ok(()),
         Token::DigitOrMinus => Ok(lexer.num_ignore().map(|_| ())?),
         Token::Quote => Ok(lexer.str_ignore()?),
-        Token::LSquare => lexer.seq(Token::RSquare, parse),
+        Token::LSquare => lexer.seq(Token::RSquare, f),
         Token::LCurly => lexer.seq(Token::RCurly, |token, lexer| {
             lexer.str_colon(token, |lexer| lexer.str_ignore().map_err(Error::Str))?;
-            parse(lexer.ws_token().ok_or(Expect::Value)?, lexer)
+            f(lexer.ws_token().ok_or(Expect::Value)?, lexer)
         }),
         _ => Err(Expect::Value)?,
     }
 }
+
+/// Parse and discard a value, not limiting the recursion depth.
+///
+/// To prevent stack overflows, consider using [`parse_bounded`].
+pub fn parse<L: Lex>(token: Token, lexer: &mut L) -> Result<(), Error> {
+    parse_with(token, lexer, parse)
+}
+
+/// Parse and discard a value, limiting the recursion to `depth`.
+///
+/// This serves to prevent stack overflows.
+/// Like [`value::parse_bounded`](crate::value::parse_bounded),
+/// this fails with [`Error::Depth`] if `depth` is 0.
+///
+/// ~~~
+/// use hifijson::{ignore, token::Lex, Error, SliceLexer};
+/// let parse = |depth, s| SliceLexer::new(s).exactly_one(|t, l| ignore::parse_bounded(depth, t, l));
+/// assert_eq!(parse(3, b"[[1]]"), Ok(()));
+/// assert_eq!(parse(2, b"[[1]]"), Err(Error::Depth));
+/// ~~~
+pub fn parse_bounded<L: Lex>(depth: usize, token: Token, lexer: &mut L) -> Result<(), Error> {
+    let d = depth.checked_sub(1).ok_or(Error::Depth)?;
+    parse_with(token, lexer, |token, lexer| parse_bounded(d, token, lexer))
+}
+
+/// Number of values encountered by [`parse_counting`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    /// number of values, including arrays and objects, but excluding object keys
+    pub values: usize,
+    /// number of arrays
+    pub arrays: usize,
+    /// number of objects
+    pub objects: usize,
+    /// number of strings, excluding object keys
+    pub strings: usize,
+    /// number of numbers
+    pub numbers: usize,
+}
+
+/// Parse and discard a value, counting the values contained in it.
+///
+/// This does not limit the recursion depth.
+///
+/// ~~~
+/// use hifijson::{ignore, token::Lex, SliceLexer};
+/// let json = br#"[null, "hello", 0, [1], {"x": 1}]"#;
+/// let counts = SliceLexer::new(json).exactly_one(ignore::parse_counting).unwrap();
+/// assert_eq!((counts.values, counts.numbers), (8, 3));
+/// ~~~
+pub fn parse_counting<L: Lex>(token: Token, lexer: &mut L) -> Result<Counts, Error> {
+    let mut counts = Counts::default();
+    foreach_kind(token, lexer, &mut |kind| {
+        counts.values += 1;
+        match kind {
+            Kind::Number => counts.numbers += 1,
+            Kind::String => counts.strings += 1,
+            Kind::Array => counts.arrays += 1,
+            Kind::Object => counts.objects += 1,
+            Kind::Null | Kind::Bool => (),
+        }
+    })?;
+    Ok(counts)
+}
+
+/// Parse and discard a value without recursion, limiting the nesting of arrays and objects.
+///
+/// A `max_depth` of 0 permits only `null`, booleans, numbers, and strings,
+/// and a depth of 1 additionally permits arrays and objects that contain no arrays or objects.
+/// If arrays and objects are nested more deeply, this fails with [`Error::Depth`].
+/// Otherwise, this accepts exactly what [`parse`] accepts.
+///
+/// Unlike [`parse`] and [`parse_bounded`], this keeps track of
+/// the open arrays and objects in a vector instead of the call stack,
+/// so the stack usage does not depend on the input.
+///
+/// ~~~
+/// use hifijson::{ignore, token::Lex, Error, SliceLexer};
+/// let validate = |s| SliceLexer::new(s).exactly_one(|t, l| ignore::validate_iter(t, l, 2));
+/// assert_eq!(validate(br#"[{"a": 1}, []]"#), Ok(()));
+/// assert_eq!(validate(b"[[[]]]"), Err(Error::Depth));
+/// ~~~
+#[cfg(feature = "alloc")]
+pub fn validate_iter<L: Lex>(token: Token, lexer: &mut L, max_depth: usize) -> Result<(), Error> {
+    // closing delimiters of the arrays / objects that we are currently in
+    let mut stack: alloc::vec::Vec<u8> = alloc::vec::Vec::new();
+    let close_token = |c| {
+        if c == b']' {
+            Token::RSquare
+        } else {
+            Token::RCurly
+        }
+    };
+    // read an object key and return the token that starts the value
+    let key = |token, lexer: &mut L| {
+        lexer.str_colon(token, |lexer| lexer.str_ignore().map_err(Error::Str))?;
+        lexer.ws_token().ok_or(Error::from(Expect::Value))
+    };
+
+    let mut token = token;
+    loop {
+        // read a value that starts with `token`
+        let close = match token {
+            Token::LSquare => Some(b']'),
+            Token::LCurly => Some(b'}'),
+            token => {
+                parse(token, lexer)?;
+                None
+            }
+        };
+        if let Some(close) = close {
+            if stack.len() >= max_depth {
+                return Err(Error::Depth);
+            }
+            let next = lexer.ws_token().ok_or(Expect::ValueOrEnd)?;
+            if next != close_token(close) {
+                stack.push(close);
+                token = if close == b'}' {
+                    key(next, lexer)?
+                } else {
+                    next
+                };
+                continue;
+            }
+        }
+
+        // the value is complete, so close arrays / objects until we find a comma
+        loop {
+            let close = match stack.last() {
+                Some(close) => *close,
+                None => return Ok(()),
+            };
+            let next = lexer.ws_token().ok_or(Expect::CommaOrEnd)?;
+            if next == close_token(close) {
+                stack.pop();
+            } else if next == Token::Comma {
+                let next = lexer.ws_token().ok_or(Expect::Value)?;
+                token = if close == b'}' {
+                    key(next, lexer)?
+                } else {
+                    next
+                };
+                break;
+            } else {
+                return Err(Expect::CommaOrEnd.into());
+            }
+        }
+    }
+}
+
+/// Parse and discard a value, counting how many values of each kind it contains.
+///
+/// The counts are indexed by [`Kind`] in the order of its variants, i.e.
+/// `null`, booleans, numbers, strings, arrays, and objects.
+/// The value itself is counted as well, but object keys are not.
+///
+/// ~~~
+/// use hifijson::{ignore, token::{Kind, Lex}, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"[1, {"a": "b"}, [null, 2]]"#);
+/// let counts = lexer.exactly_one(ignore::kind_histogram).unwrap();
+/// assert_eq!(counts, [1, 0, 2, 1, 2, 1]);
+/// assert_eq!(counts[Kind::Array as usize], 2);
+/// ~~~
+pub fn kind_histogram<L: Lex>(token: Token, lexer: &mut L) -> Result<[usize; 6], Error> {
+    let mut counts = [0; 6];
+    foreach_kind(token, lexer, &mut |kind| counts[kind as usize] += 1)?;
+    Ok(counts)
+}
+
+/// Parse and discard a value, running `f` on the kind of every value contained in it.
+fn foreach_kind<L: Lex>(
+    token: Token,
+    lexer: &mut L,
+    f: &mut impl FnMut(Kind),
+) -> Result<(), Error> {
+    f(token.kind().ok_or(Expect::Value)?);
+    match token {
+        Token::LSquare => lexer.seq(Token::RSquare, |token, lexer| foreach_kind(token, lexer, f)),
+        Token::LCurly => lexer.seq(Token::RCurly, |token, lexer| {
+            lexer.str_colon(token, |lexer| lexer.str_ignore().map_err(Error::Str))?;
+            foreach_kind(lexer.ws_token().ok_or(Expect::Value)?, lexer, f)
+        }),
+        token => parse(token, lexer),
+    }
+}
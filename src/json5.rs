@@ -0,0 +1,334 @@
+//! JSON5.
+//!
+//! [JSON5](https://spec.json5.org/) extends JSON with syntax from ECMAScript 5.1.
+//! In particular, this module accepts:
+//!
+//! * comments (`// ...` and `/* ... */`),
+//! * commas after the last item of arrays and objects,
+//! * object keys that are identifiers, such as `{a: 1}`,
+//! * strings that are enclosed in single quotes, such as `'a'`,
+//!   escaped single quotes and escaped line breaks in strings, as well as `\xHH` escapes, and
+//! * hexadecimal numbers, numbers with a leading `+` or
+//!   a leading / trailing decimal point, `Infinity`, and `NaN`.
+//!
+//! Numbers are converted to JSON numbers where possible;
+//! for example, `0x1F` and `+.5` yield `31` and `0.5`.
+//! `Infinity`, `-Infinity`, and `NaN` cannot be represented as JSON numbers,
+//! so they are kept as they are.
+//!
+//! Identifiers are restricted to ASCII characters, and
+//! the only whitespace characters accepted are ASCII whitespace characters.
+//!
+//! ~~~
+//! let json5 = br#"
+//! // configuration
+//! {
+//!   name: 'demo',
+//!   ports: [0x50, 443,],
+//!   /* scale */ ratio: .5,
+//! }
+//! "#;
+//! let v = hifijson::json5::parse(&mut hifijson::SliceLexer::new(json5)).unwrap();
+//! assert_eq!(v.to_string(), r#"{"name":"demo","ports":[80,443],"ratio":0.5}"#);
+//! ~~~
+
+use crate::escape::{self, Escape};
+use crate::num::{self, LexWrite};
+use crate::value::Value;
+use crate::{str, Error, Expect, Lex, Token};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Parse exactly one JSON5 value.
+///
+/// This does not limit the recursion depth.
+pub fn parse<L: Lex>(lexer: &mut L) -> Result<Value<String, String>, Error> {
+    let c = ws(lexer)?.ok_or(Expect::Value)?;
+    let v = value(lexer, c)?;
+    match ws(lexer)? {
+        None => Ok(v),
+        Some(c) => Err(Expect::TrailingByte(c))?,
+    }
+}
+
+/// Skip whitespace and comments, then return the next byte without consuming it.
+fn ws<L: Lex>(lexer: &mut L) -> Result<Option<u8>, Error> {
+    loop {
+        lexer.skip_next_until(|c| !matches!(c, b' ' | b'\t' | b'\r' | b'\n' | 0x0B | 0x0C));
+        if lexer.peek_next() != Some(&b'/') {
+            return Ok(lexer.peek_next().copied());
+        }
+        lexer.take_next();
+        match next(lexer) {
+            Some(b'/') => lexer.skip_until(|c| c == b'\n'),
+            Some(b'*') => {
+                let mut star = false;
+                lexer.skip_until(|c| {
+                    let end = star && c == b'/';
+                    star = c == b'*';
+                    end
+                });
+                lexer.take_next().ok_or(Expect::CommentEnd)?;
+            }
+            _ => Err(Expect::Value)?,
+        }
+    }
+}
+
+/// Return the next byte without consuming it.
+fn peek<L: Lex>(lexer: &mut L) -> Option<u8> {
+    lexer.skip_next_until(|_| true);
+    lexer.peek_next().copied()
+}
+
+/// Parse a value starting with the (not yet consumed) byte `c`.
+fn value<L: Lex>(lexer: &mut L, c: u8) -> Result<Value<String, String>, Error> {
+    match c {
+        b'n' | b't' | b'f' => match lexer.token(c) {
+            Token::Null => Ok(Value::Null),
+            Token::True => Ok(Value::Bool(true)),
+            Token::False => Ok(Value::Bool(false)),
+            _ => Err(Expect::Value)?,
+        },
+        b'"' | b'\'' => {
+            lexer.take_next();
+            Ok(Value::String(string(lexer, c)?))
+        }
+        b'0'..=b'9' | b'-' | b'+' | b'.' | b'I' | b'N' => Ok(Value::Number(number(lexer)?)),
+        b'[' => {
+            lexer.take_next();
+            let mut arr = Vec::new();
+            seq(lexer, Token::RSquare, |lexer, c| {
+                arr.push(value(lexer, c)?);
+                Ok(())
+            })?;
+            Ok(Value::Array(arr))
+        }
+        b'{' => {
+            lexer.take_next();
+            let mut obj = Vec::new();
+            seq(lexer, Token::RCurly, |lexer, c| {
+                let key = key(lexer, c)?;
+                if ws(lexer)? != Some(b':') {
+                    Err(Expect::Colon)?
+                }
+                lexer.take_next();
+                let c = ws(lexer)?.ok_or(Expect::Value)?;
+                obj.push((key, value(lexer, c)?));
+                Ok(())
+            })?;
+            Ok(Value::Object(obj))
+        }
+        _ => Err(Expect::Value)?,
+    }
+}
+
+/// Run `f` on the first byte of every item of a sequence until `end`,
+/// permitting a trailing comma.
+///
+/// The opening bracket has to be consumed already.
+fn seq<L: Lex>(
+    lexer: &mut L,
+    end: Token,
+    mut f: impl FnMut(&mut L, u8) -> Result<(), Error>,
+) -> Result<(), Error> {
+    // only commas and closing brackets are consumed as tokens;
+    // for any other byte, we yield `Token::Error` and leave the byte to `f`
+    let token = |lexer: &mut L| {
+        Ok(ws(lexer)?.map(|c| match c {
+            b',' | b']' | b'}' => lexer.token(c),
+            _ => Token::Error,
+        }))
+    };
+    lexer.seq_by(end, true, token, |token, lexer| {
+        if token != Token::Error {
+            Err(Expect::Value)?
+        }
+        let c = ws(lexer)?.ok_or(Expect::Value)?;
+        f(lexer, c)
+    })
+}
+
+fn is_ident_start(c: u8) -> bool {
+    matches!(c, b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$')
+}
+
+/// Parse an object key starting with the (not yet consumed) byte `c`.
+fn key<L: Lex>(lexer: &mut L, c: u8) -> Result<String, Error> {
+    if matches!(c, b'"' | b'\'') {
+        lexer.take_next();
+        return string(lexer, c);
+    }
+    if !is_ident_start(c) {
+        Err(Expect::String)?
+    }
+    let mut key = String::new();
+    lexer.skip_next_until(|c| {
+        let ident = is_ident_start(c) || c.is_ascii_digit();
+        if ident {
+            key.push(char::from(c))
+        }
+        !ident
+    });
+    Ok(key)
+}
+
+/// Consume and return the next byte.
+fn next<L: Lex>(lexer: &mut L) -> Option<u8> {
+    lexer.skip_next_until(|_| true);
+    lexer.take_next()
+}
+
+/// Read a hexadecimal number of `N` digits.
+fn hex<L: Lex, const N: usize>(lexer: &mut L) -> Result<u16, escape::Error> {
+    let mut hex = 0;
+    for _ in 0..N {
+        let h = next(lexer).ok_or(escape::Error::Eof)?;
+        let h = escape::decode_hex(h).ok_or(escape::Error::InvalidHex)?;
+        hex = (hex << 4) + u16::from(h);
+    }
+    Ok(hex)
+}
+
+/// Parse the rest of a string that is enclosed by `quote`.
+fn string<L: Lex>(lexer: &mut L, quote: u8) -> Result<String, Error> {
+    let mut bytes = Vec::new();
+    loop {
+        let c = match next(lexer).ok_or(str::Error::Eof)? {
+            c if c == quote => break,
+            b'\n' | b'\r' => Err(str::Error::Control)?,
+            b'\\' => escape(lexer).map_err(str::Error::Escape)?,
+            c => {
+                bytes.push(c);
+                continue;
+            }
+        };
+        if let Some(c) = c {
+            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+        }
+    }
+    String::from_utf8(bytes).map_err(|e| str::Error::Utf8(e.utf8_error()).into())
+}
+
+/// Parse the rest of an escape sequence (after `\`).
+///
+/// Return `None` for escaped line breaks, which do not yield any character.
+fn escape<L: Lex>(lexer: &mut L) -> Result<Option<char>, escape::Error> {
+    Ok(Some(match next(lexer).ok_or(escape::Error::Eof)? {
+        b'\'' => '\'',
+        b'0' if !matches!(peek(lexer), Some(b'0'..=b'9')) => '\0',
+        b'v' => '\u{0B}',
+        b'x' => char::from(hex::<_, 2>(lexer)? as u8),
+        b'\n' => return Ok(None),
+        b'\r' => {
+            if peek(lexer) == Some(b'\n') {
+                lexer.take_next();
+            }
+            return Ok(None);
+        }
+        typ => {
+            let escape = match Escape::try_from(typ) {
+                Some(Escape::Unicode(_)) => Escape::Unicode(hex::<_, 4>(lexer)?),
+                Some(escape) => escape,
+                None => return Err(escape::Error::UnknownKind),
+            };
+            lexer.escape_char(escape)?
+        }
+    }))
+}
+
+/// Parse a number starting at the current byte.
+fn number<L: Lex>(lexer: &mut L) -> Result<(String, num::Parts), Error> {
+    let mut raw = Vec::new();
+    lexer.skip_next_until(|c| {
+        let num = c.is_ascii_alphanumeric() || matches!(c, b'.' | b'+' | b'-');
+        if num {
+            raw.push(c)
+        }
+        !num
+    });
+
+    let (sign, rest) = match raw.split_first() {
+        Some((b'-', rest)) => ("-", rest),
+        Some((b'+', rest)) => ("", rest),
+        _ => ("", &raw[..]),
+    };
+    if matches!(rest.first(), Some(b'+' | b'-')) {
+        Err(num::Error::ExpectedDigit)?
+    }
+    let mut n = String::from(sign);
+    match rest {
+        b"Infinity" => {
+            n.push_str("Infinity");
+            let neg = sign == "-";
+            return Ok((
+                n,
+                num::Parts {
+                    neg,
+                    ..num::Parts::default()
+                },
+            ));
+        }
+        b"NaN" => return Ok(("NaN".to_string(), num::Parts::default())),
+        [b'0', b'x' | b'X', hex @ ..] => n.push_str(&hex_to_dec(hex)?),
+        _ => {
+            let (mantissa, exp) = match rest.iter().position(|c| matches!(c, b'e' | b'E')) {
+                Some(pos) => rest.split_at(pos),
+                None => (rest, &[][..]),
+            };
+            let (int, frac) = match mantissa.iter().position(|c| *c == b'.') {
+                Some(pos) => (&mantissa[..pos], &mantissa[pos + 1..]),
+                None => (mantissa, &[][..]),
+            };
+            if int.is_empty() && frac.is_empty() {
+                Err(num::Error::ExpectedDigit)?
+            }
+            n.push_str(if int.is_empty() { "0" } else { ascii(int) });
+            if !frac.is_empty() {
+                n.push('.');
+                n.push_str(ascii(frac))
+            }
+            n.push_str(ascii(exp));
+        }
+    }
+    // validate the number and obtain its parts
+    let mut num_lexer = crate::SliceLexer::new(n.as_bytes());
+    let parts = num_lexer.num_string()?.1;
+    if !num_lexer.as_slice().is_empty() {
+        Err(num::Error::ExpectedDigit)?
+    }
+    Ok((n, parts))
+}
+
+/// Convert bytes that are known to be ASCII to a string.
+fn ascii(s: &[u8]) -> &str {
+    core::str::from_utf8(s).unwrap_or("?")
+}
+
+/// Convert hexadecimal digits to decimal digits.
+fn hex_to_dec(hex: &[u8]) -> Result<String, num::Error> {
+    if hex.is_empty() {
+        return Err(num::Error::ExpectedDigit);
+    }
+    // decimal digits, least significant first
+    let mut digits: Vec<u8> = Vec::new();
+    for h in hex {
+        let mut carry = escape::decode_hex(*h).ok_or(num::Error::ExpectedDigit)?;
+        for d in digits.iter_mut() {
+            let v = *d * 16 + carry;
+            *d = v % 10;
+            carry = v / 10;
+        }
+        while carry > 0 {
+            digits.push(carry % 10);
+            carry /= 10;
+        }
+    }
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
+    }
+    if digits.is_empty() {
+        digits.push(0)
+    }
+    Ok(digits.iter().rev().map(|d| char::from(b'0' + d)).collect())
+}
@@ -0,0 +1,83 @@
+//! JSON with padding (JSONP).
+//!
+//! JSONP wraps a JSON value in a JavaScript function call, such as `cb({"a": 1});`.
+
+use crate::{Expect, Lex, Token};
+
+fn is_ident_start(c: u8) -> bool {
+    matches!(c, b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$')
+}
+
+fn is_ident(c: u8) -> bool {
+    is_ident_start(c) || matches!(c, b'0'..=b'9' | b'.')
+}
+
+/// Parse a value with `f` that may be wrapped in a JSONP callback.
+///
+/// If the input starts with an identifier followed by `(`,
+/// then the identifier and the parenthesis are consumed, and
+/// after the value, a closing `)` and an optional `;` are consumed.
+/// Otherwise, this behaves like parsing the value with `f` directly.
+///
+/// ~~~
+/// use hifijson::{jsonp, value, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"callback({"a": 1});"#);
+/// let v = jsonp::unwrap(&mut lexer, value::parse_unbounded).unwrap();
+/// assert_eq!(v.to_string(), r#"{"a":1}"#);
+/// assert!(lexer.as_slice().is_empty());
+/// ~~~
+pub fn unwrap<L: Lex, T, E: From<Expect>, F>(lexer: &mut L, f: F) -> Result<T, E>
+where
+    F: FnOnce(Token, &mut L) -> Result<T, E>,
+{
+    lexer.eat_whitespace();
+    match lexer.peek_next() {
+        Some(c) if is_ident_start(*c) => (),
+        _ => return f(lexer.ws_token().ok_or(Expect::Value)?, lexer),
+    }
+
+    // because `null`, `true`, and `false` are also identifiers,
+    // we keep track of whether the identifier is one of these
+    let keywords: [(&[u8], Token); 3] = [
+        (b"null", Token::Null),
+        (b"true", Token::True),
+        (b"false", Token::False),
+    ];
+    let mut matches = [true; 3];
+    let mut len = 0;
+    lexer.skip_next_until(|c| {
+        if !is_ident(c) {
+            return true;
+        }
+        for (m, (kw, _)) in matches.iter_mut().zip(keywords.iter()) {
+            *m &= kw.get(len) == Some(&c);
+        }
+        len += 1;
+        false
+    });
+    let keyword = matches
+        .iter()
+        .zip(keywords)
+        .find(|(m, (kw, _))| **m && kw.len() == len);
+
+    lexer.eat_whitespace();
+    if lexer.peek_next() != Some(&b'(') {
+        return match keyword {
+            Some((_, (_, token))) => f(token, lexer),
+            None => Err(Expect::Value)?,
+        };
+    }
+    lexer.take_next();
+
+    let v = f(lexer.ws_token().ok_or(Expect::Value)?, lexer)?;
+    lexer.eat_whitespace();
+    if lexer.peek_next() != Some(&b')') {
+        Err(Expect::RParen)?
+    }
+    lexer.take_next();
+    lexer.eat_whitespace();
+    if lexer.peek_next() == Some(&b';') {
+        lexer.take_next();
+    }
+    Ok(v)
+}
@@ -34,7 +34,8 @@
 //! and you do not feel like caring about reviews today.
 //! Then you can simply skip reading the value for reviews by using [`ignore::parse`].
 //! Going wild and stretching the syntax a bit, you can also make
-//! a parser that accepts any value (instead of only strings as mandated by JSON) as object key.
+//! a parser that accepts any value (instead of only strings as mandated by JSON) as object key,
+//! for example with [`value::parse_with_key`].
 //! Or, if you just want to have a complete JSON value, then
 //! you can use [`value::parse_unbounded`].
 //! The choice is yours.
@@ -94,6 +95,10 @@
 //! If you build hifijson without the feature flag `alloc`, you disable any allocation.
 //! If you build hifijson with the feature flag `serde`,
 //! then you can use hifijson to deserialise JSON to data types implementing `serde::Deserialize`.
+//! If you build hifijson with the feature flag `memchr`,
+//! then strings are read faster from slices by using the `memchr` crate.
+//! If you build hifijson with the feature flag `bigint`,
+//! then you can convert integers of arbitrary size to `num_bigint::BigInt`.
 //!
 //!
 //! # Examples
@@ -134,6 +139,7 @@
 //! otherwise from standard input:
 //!
 //! ~~~ no_run
+//! # #[cfg(feature = "std")] {
 //! /// Parse a single JSON value and print it.
 //! ///
 //! /// Note that the `LexAlloc` trait indicates that this lexer allocates memory.
@@ -148,9 +154,9 @@
 //!     let file = std::fs::read(filename).expect("read file");
 //!     process(hifijson::SliceLexer::new(&file))
 //! } else {
-//!     use std::io::Read;
-//!     process(hifijson::IterLexer::new(std::io::stdin().bytes()))
+//!     process(hifijson::IterLexer::from_read(std::io::stdin()))
 //! }
+//! # }
 //! ~~~
 //!
 //! We just made a pretty printer (stretching the definition of pretty pretty far).
@@ -247,6 +253,7 @@ extern crate std;
 mod read;
 mod write;
 
+pub use read::Position;
 use read::Read;
 use write::Write;
 
@@ -257,7 +264,23 @@ pub mod token;
 
 pub use token::{Expect, Token};
 
+pub mod array;
+pub mod budget;
+#[cfg(feature = "alloc")]
+pub mod events;
+#[cfg(all(feature = "std", feature = "alloc"))]
+pub mod fmt;
 pub mod ignore;
+#[cfg(feature = "alloc")]
+pub mod json5;
+pub mod jsonp;
+#[cfg(feature = "alloc")]
+pub mod object;
+#[cfg(feature = "alloc")]
+pub mod patch;
+pub mod progress;
+#[cfg(feature = "alloc")]
+pub mod schema;
 #[cfg(feature = "serde")]
 pub mod serde;
 #[cfg(feature = "alloc")]
@@ -290,12 +313,133 @@ impl<'a> SliceLexer<'a> {
         Self { slice }
     }
 
+    /// Continue lexing from the start of a new slice.
+    ///
+    /// This discards any remaining input of the old slice.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, ignore, SliceLexer};
+    /// let mut lexer = SliceLexer::new(b"[1, 2] 3");
+    /// assert!(lexer.exactly_one(ignore::parse).is_err());
+    /// lexer.reset(b"true");
+    /// assert!(lexer.exactly_one(ignore::parse).is_ok());
+    /// ~~~
+    pub fn reset(&mut self, slice: &'a [u8]) {
+        self.slice = slice
+    }
+
+    /// Parse one value using given function and return it along with the remaining input.
+    ///
+    /// ~~~
+    /// use hifijson::{value, SliceLexer};
+    /// let (v, rest) = SliceLexer::new(b" [1, 2] 3").parse_prefix(value::parse_unbounded).unwrap();
+    /// assert_eq!((v.to_string(), rest), ("[1,2]".to_string(), &b" 3"[..]));
+    /// ~~~
+    pub fn parse_prefix<T, E: From<Expect>, F>(mut self, f: F) -> Result<(T, &'a [u8]), E>
+    where
+        F: FnOnce(Token, &mut Self) -> Result<T, E>,
+    {
+        use token::Lex;
+        let v = self.parse_one(f)?;
+        Ok((v, self.slice))
+    }
+
+    /// Run `f` and return the input that it consumed along with its result.
+    ///
+    /// For example, this can be used to obtain
+    /// the original text of a value without copying it:
+    ///
+    /// ~~~
+    /// use hifijson::{ignore, token::Lex, SliceLexer};
+    /// let mut lexer = SliceLexer::new(br#" {"a": [1,  2]} 3"#);
+    /// lexer.eat_whitespace();
+    /// let (raw, ()) = lexer.capture(|lexer| lexer.parse_one(ignore::parse)).unwrap();
+    /// assert_eq!(raw, br#"{"a": [1,  2]}"#);
+    /// assert_eq!(lexer.as_slice(), b" 3");
+    /// ~~~
+    pub fn capture<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<(&'a [u8], T), E> {
+        let before = self.slice;
+        let y = f(self)?;
+        Ok((&before[..before.len() - self.slice.len()], y))
+    }
+
     /// Return remaining input as a subslice of the original data.
     ///
     /// This can be used to find the place where an error occurred.
     pub fn as_slice(&self) -> &'a [u8] {
         self.slice
     }
+
+    /// Return the number of remaining input bytes.
+    pub fn remaining(&self) -> usize {
+        self.slice.len()
+    }
+
+    /// Return true if the whole input has been consumed.
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+}
+
+/// JSON lexer that owns its input.
+///
+/// In contrast to [`SliceLexer`], this can be moved together with its input,
+/// for example to return it from a function that reads the input.
+/// Lexing is performed by [`OwnedSliceLexer::with`].
+#[cfg(feature = "alloc")]
+pub struct OwnedSliceLexer {
+    bytes: alloc::vec::Vec<u8>,
+    /// number of bytes consumed from `bytes`
+    pos: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedSliceLexer {
+    /// Create a new lexer that owns the given input.
+    pub fn new(bytes: alloc::vec::Vec<u8>) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Run `f` on a [`SliceLexer`] over the remaining input.
+    ///
+    /// Any input consumed by `f` is consumed from this lexer as well.
+    /// If `f` resets the lexer to other input, all input of this lexer is consumed.
+    /// The result of `f` may borrow from the input.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, OwnedSliceLexer};
+    /// fn lexer() -> OwnedSliceLexer {
+    ///     OwnedSliceLexer::new(b"[1, 2] true".to_vec())
+    /// }
+    /// let mut lexer = lexer();
+    /// let v = lexer.with(|lexer| lexer.parse_one(value::parse_unbounded)).unwrap();
+    /// assert_eq!(v.to_string(), "[1,2]");
+    /// assert_eq!(lexer.as_slice(), b" true");
+    /// ~~~
+    pub fn with<'a, T>(&'a mut self, f: impl FnOnce(&mut SliceLexer<'a>) -> T) -> T {
+        let bytes: &'a [u8] = &self.bytes;
+        let mut lexer = SliceLexer::new(&bytes[self.pos..]);
+        let y = f(&mut lexer);
+        let rest = lexer.as_slice();
+        // is the remaining input of `lexer` still a suffix of `bytes`?
+        let suffix = rest.as_ptr_range().end == bytes.as_ptr_range().end;
+        let pos = bytes.len().checked_sub(rest.len()).filter(|_| suffix);
+        self.pos = pos.unwrap_or(bytes.len());
+        y
+    }
+
+    /// Return the remaining input.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[self.pos..]
+    }
+
+    /// Return the whole input, including the consumed part.
+    pub fn into_inner(self) -> alloc::vec::Vec<u8> {
+        self.bytes
+    }
 }
 
 /// JSON lexer from an iterator over (fallible) bytes.
@@ -303,13 +447,16 @@ impl<'a> SliceLexer<'a> {
 /// This can be used to lex from a [`Read`](std::io::Read) as follows:
 ///
 /// ~~~
-/// use std::io::Read;
-/// let read = std::io::stdin();
-/// let lexer = hifijson::IterLexer::new(read.bytes());
+/// # #[cfg(feature = "std")]
+/// let lexer = hifijson::IterLexer::from_read(std::io::stdin());
 /// ~~~
 pub struct IterLexer<E, I> {
     bytes: I,
+    /// number of bytes read from `bytes`
+    pos: usize,
     last: Option<u8>,
+    /// has `bytes` returned `None`?
+    exhausted: bool,
     /// error occurred during reading a byte
     pub error: Option<E>,
 }
@@ -319,10 +466,138 @@ impl<E, I: Iterator<Item = Result<u8, E>>> IterLexer<E, I> {
     pub fn new(iter: I) -> Self {
         Self {
             bytes: iter,
+            pos: 0,
             last: None,
+            exhausted: false,
             error: None,
         }
     }
+
+    /// Return true if the iterator has returned `None` and
+    /// no byte read from it remains to be processed.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, IterLexer};
+    /// let mut lexer = IterLexer::new(b"1 ".iter().copied().map(Ok::<_, ()>));
+    /// assert!(!lexer.is_exhausted());
+    /// assert!(lexer.exactly_one(hifijson::ignore::parse).is_ok());
+    /// assert!(lexer.is_exhausted());
+    /// ~~~
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted && self.last.is_none()
+    }
+
+    /// Return the error that occurred while reading a byte, if any, and reset it.
+    ///
+    /// Once the iterator yields an error, lexing behaves as if the input ended.
+    /// That means that a read error usually shows up as parse error, such as
+    /// [`str::Error::Eof`] when it occurs inside a string.
+    /// To find the actual cause, check for a read error after a parse error.
+    pub fn take_error(&mut self) -> Option<E> {
+        self.error.take()
+    }
+
+    /// Parse exactly one value like [`token::Lex::exactly_one`], but
+    /// fail with [`Error::Read`] if an error occurred while reading a byte.
+    ///
+    /// The read error itself can be obtained with [`IterLexer::take_error`].
+    ///
+    /// ~~~
+    /// use hifijson::{ignore, Error, IterLexer};
+    /// let bytes = [Ok(b'"'), Ok(b'a'), Err("broken pipe"), Ok(b'"')];
+    /// let mut lexer = IterLexer::new(bytes.into_iter());
+    /// assert_eq!(lexer.exactly_one_read(ignore::parse), Err(Error::Read));
+    /// assert_eq!(lexer.take_error(), Some("broken pipe"));
+    /// ~~~
+    pub fn exactly_one_read<T, F>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(Token, &mut Self) -> Result<T, Error>,
+    {
+        use token::Lex;
+        let y = self.exactly_one(f);
+        if self.error.is_some() {
+            Err(Error::Read)
+        } else {
+            y
+        }
+    }
+}
+
+/// Iterator lexer over a trait object.
+///
+/// In contrast to [`IterLexer`] over a concrete iterator type,
+/// parsing functions are instantiated only once for all iterator types,
+/// which can reduce code size at the cost of dynamic dispatch per byte.
+pub type DynIterLexer<'a, E> = IterLexer<E, &'a mut dyn Iterator<Item = Result<u8, E>>>;
+
+impl<'a, E> DynIterLexer<'a, E> {
+    /// Create a new iterator lexer over a trait object.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, IterLexer};
+    /// let mut iter = b"[1, 2]".iter().map(|c| Ok::<_, ()>(*c));
+    /// let mut lexer = IterLexer::from_dyn(&mut iter);
+    /// let v = lexer.exactly_one(value::parse_unbounded).unwrap();
+    /// assert_eq!(v.to_string(), "[1,2]");
+    /// ~~~
+    pub fn from_dyn(iter: &'a mut dyn Iterator<Item = Result<u8, E>>) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> IterLexer<std::io::Error, std::io::Bytes<std::io::BufReader<R>>> {
+    /// Create a new iterator lexer that reads from a buffered reader.
+    ///
+    /// Reading the bytes of an unbuffered reader one by one can be very slow,
+    /// which is why this wraps the reader in a [`BufReader`](std::io::BufReader).
+    pub fn from_read(read: R) -> Self {
+        use std::io::Read;
+        Self::new(std::io::BufReader::new(read).bytes())
+    }
+}
+
+/// Character encoding of JSON input, as returned by [`detect_encoding`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, which is the only encoding that the lexers in this crate support
+    Utf8,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// UTF-32, little endian
+    Utf32Le,
+    /// UTF-32, big endian
+    Utf32Be,
+}
+
+/// Detect the encoding of JSON input from its first (up to four) bytes.
+///
+/// This considers byte order marks as well as
+/// the pattern of zero bytes that results from
+/// the first characters of a JSON text being ASCII,
+/// as described in [RFC 4627](https://www.rfc-editor.org/rfc/rfc4627#section-3).
+/// If no other encoding is detected, this returns [`Encoding::Utf8`].
+///
+/// Input in any other encoding than UTF-8 has to be transcoded before lexing.
+/// Byte order marks are not removed by this function.
+///
+/// ~~~
+/// use hifijson::{detect_encoding, Encoding};
+/// assert_eq!(detect_encoding(b"[1]"), Encoding::Utf8);
+/// assert_eq!(detect_encoding(b"[\0001\0]\0"), Encoding::Utf16Le);
+/// assert_eq!(detect_encoding(b"\0\0\0["), Encoding::Utf32Be);
+/// ~~~
+pub fn detect_encoding(prefix: &[u8]) -> Encoding {
+    match prefix {
+        [0xEF, 0xBB, 0xBF, ..] => Encoding::Utf8,
+        [0, 0, 0xFE, 0xFF, ..] | [0, 0, 0, _, ..] => Encoding::Utf32Be,
+        [0xFF, 0xFE, 0, 0, ..] | [_, 0, 0, 0, ..] => Encoding::Utf32Le,
+        [0xFE, 0xFF, ..] | [0, _, ..] => Encoding::Utf16Be,
+        [0xFF, 0xFE, ..] | [_, 0, ..] => Encoding::Utf16Le,
+        _ => Encoding::Utf8,
+    }
 }
 
 /// Parse error.
@@ -336,22 +611,78 @@ pub enum Error {
     Str(str::Error),
     /// we did not obtain a token that we expected
     Token(token::Expect),
+    /// an object contains the same key twice
+    DuplicateKey,
+    /// an object key is longer than permitted
+    KeyTooLong,
+    /// a limit on the input has been exceeded
+    Limit(LimitKind),
+    /// the budget of a [`budget::BudgetLexer`] has been exhausted
+    Budget,
+    /// reading a byte from the input has failed, see [`IterLexer::take_error`]
+    Read,
+    /// we obtained a value of a different kind than we expected
+    UnexpectedKind {
+        /// kind of value that we expected
+        expected: token::Kind,
+        /// kind of value that we found
+        found: token::Kind,
+    },
+}
+
+/// Limit on the input, such as enforced by [`value::parse_limited`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LimitKind {
+    /// maximal nesting of arrays and objects
+    Depth,
+    /// maximal number of bytes consumed
+    TotalBytes,
+    /// maximal number of values
+    Values,
+    /// maximal length of a string
+    StringLen,
+}
+
+impl Display for LimitKind {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        use LimitKind::*;
+        match self {
+            Depth => "depth".fmt(f),
+            TotalBytes => "total bytes".fmt(f),
+            Values => "value count".fmt(f),
+            StringLen => "string length".fmt(f),
+        }
+    }
 }
 
 impl_from!(num::Error, Error, Error::Num);
 impl_from!(str::Error, Error, Error::Str);
 impl_from!(token::Expect, Error, Error::Token);
 
-use core::fmt::{self, Display};
+use core::fmt::{Display, Formatter};
 
 impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         use Error::*;
         match self {
             Depth => "maximal depth exceeded".fmt(f),
-            Num(num::Error::ExpectedDigit) => "expected digit".fmt(f),
+            Num(e) => e.fmt(f),
             Str(e) => e.fmt(f),
+            Token(token::Expect::TrailingByte(c)) => {
+                let c = core::ascii::escape_default(*c);
+                write!(f, "end of file expected, found `{}`", c)
+            }
+            Token(token::Expect::EmptyInput) => "empty input".fmt(f),
+            Token(token::Expect::StringFound(t)) => write!(f, "string expected, found {}", t),
             Token(e) => write!(f, "{} expected", e),
+            DuplicateKey => "duplicate key".fmt(f),
+            KeyTooLong => "object key too long".fmt(f),
+            Limit(l) => write!(f, "{} limit exceeded", l),
+            Budget => "allocation budget exhausted".fmt(f),
+            Read => "read error".fmt(f),
+            UnexpectedKind { expected, found } => {
+                write!(f, "{} expected, found {}", expected, found)
+            }
         }
     }
 }
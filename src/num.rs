@@ -13,28 +13,503 @@ pub enum Error {
     /// For example, if the lexer encounters `42abc`,
     /// it returns only `42` and does not touch `abc`.
     ExpectedDigit,
+    /// `007` (only when lexing strictly)
+    LeadingZero,
+    /// `1x` (only when validating with [`validate`])
+    Trailing,
+    /// `12345` (only when lexing with a maximum length)
+    TooLong,
 }
 
-/// Position of `.` and `e`/`E` in the string representation of a number.
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use Error::*;
+        match self {
+            ExpectedDigit => "expected digit".fmt(f),
+            LeadingZero => "leading zero".fmt(f),
+            Trailing => "unexpected input after number".fmt(f),
+            TooLong => "number too long".fmt(f),
+        }
+    }
+}
+
+/// Sign of a number and position of `.` and `e`/`E` in its string representation.
 ///
 /// Because a number cannot start with `.` or `e`/`E`,
 /// these positions must always be greater than zero.
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Parts {
+    /// true if the number starts with `-`
+    pub neg: bool,
     /// position of the dot
     pub dot: Option<NonZeroUsize>,
     /// position of the exponent character (`e`/`E`)
     pub exp: Option<NonZeroUsize>,
 }
 
+/// Components of the string representation of a number, see [`Parts::split`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NumberComponents<'a> {
+    /// `-` if the number is negative
+    pub sign: Option<char>,
+    /// digits before the dot or the exponent
+    pub int: &'a str,
+    /// digits after the dot
+    pub frac: Option<&'a str>,
+    /// `+` or `-` if the exponent starts with it
+    pub exp_sign: Option<char>,
+    /// digits of the exponent
+    pub exp: Option<&'a str>,
+}
+
+/// Notation of the exponent of a number, see [`Parts::exp_style`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpStyle {
+    /// true if the exponent character is `E`, false if it is `e`
+    pub upper: bool,
+    /// `+` or `-` if the exponent starts with it
+    pub sign: Option<char>,
+}
+
 impl Parts {
     /// Return true if the number contains neither a dot not an exponent.
     pub fn is_int(&self) -> bool {
         self.dot.is_none() && self.exp.is_none()
     }
+
+    /// Split the number `s` having these parts into its components.
+    ///
+    /// This uses the recorded positions of the dot and the exponent,
+    /// so `s` is not scanned again.
+    ///
+    /// ~~~
+    /// use hifijson::num::{LexWrite, NumberComponents};
+    /// let mut lexer = hifijson::SliceLexer::new(b"-12.50E+3");
+    /// let (num, parts) = lexer.num_string().unwrap();
+    /// let c = NumberComponents {
+    ///     sign: Some('-'),
+    ///     int: "12",
+    ///     frac: Some("50"),
+    ///     exp_sign: Some('+'),
+    ///     exp: Some("3"),
+    /// };
+    /// assert_eq!(parts.split(num), c);
+    /// ~~~
+    pub fn split<'a>(&self, s: &'a str) -> NumberComponents<'a> {
+        let exp_pos = self.exp.map_or(s.len(), |e| e.get());
+        let (int, frac) = match self.dot {
+            Some(dot) => (&s[..dot.get()], Some(&s[dot.get() + 1..exp_pos])),
+            None => (&s[..exp_pos], None),
+        };
+        let (exp_sign, exp) = match self.exp.map(|e| &s[e.get() + 1..]) {
+            Some(exp) => match exp.strip_prefix(&['+', '-'][..]) {
+                Some(digits) => (exp.chars().next(), Some(digits)),
+                None => (None, Some(exp)),
+            },
+            None => (None, None),
+        };
+        NumberComponents {
+            sign: if self.neg { Some('-') } else { None },
+            int: &int[usize::from(self.neg)..],
+            frac,
+            exp_sign,
+            exp,
+        }
+    }
+
+    /// Return the notation of the exponent of the number `s` having these parts,
+    /// or `None` if it has no exponent.
+    ///
+    /// This looks only at the characters at the recorded position of the exponent,
+    /// so `s` is not scanned again.
+    ///
+    /// ~~~
+    /// use hifijson::num::{ExpStyle, LexWrite};
+    /// let style = |s: &str| {
+    ///     let (n, parts) = hifijson::SliceLexer::new(s.as_bytes()).num_string().unwrap();
+    ///     parts.exp_style(n)
+    /// };
+    /// assert_eq!(style("1.5"), None);
+    /// assert_eq!(style("1e5"), Some(ExpStyle { upper: false, sign: None }));
+    /// assert_eq!(style("1E-5"), Some(ExpStyle { upper: true, sign: Some('-') }));
+    /// ~~~
+    pub fn exp_style(&self, s: &str) -> Option<ExpStyle> {
+        let s = s.as_bytes();
+        let exp = self.exp?.get();
+        let sign = match s.get(exp + 1) {
+            Some(c @ (b'+' | b'-')) => Some(char::from(*c)),
+            _ => None,
+        };
+        Some(ExpStyle {
+            upper: s.get(exp) == Some(&b'E'),
+            sign,
+        })
+    }
+
+    /// Return the number `s` having these parts as arbitrary-precision integer
+    /// if it contains neither a dot nor an exponent.
+    ///
+    /// ~~~
+    /// use hifijson::num::LexWrite;
+    /// let n = "-123456789012345678901234567890";
+    /// let (n, parts) = hifijson::SliceLexer::new(n.as_bytes()).num_string().unwrap();
+    /// assert_eq!(parts.to_bigint(n).unwrap().to_string(), n);
+    /// ~~~
+    #[cfg(feature = "bigint")]
+    pub fn to_bigint(&self, s: &str) -> Option<num_bigint::BigInt> {
+        if self.is_int() {
+            s.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Return true if the number `s` having these parts is an integer that fits into `u64`.
+    ///
+    /// This only looks at the sign and the number of digits of `s`,
+    /// comparing digit by digit only if `s` has as many digits as `u64::MAX`.
+    /// Because `-0` is zero, it fits, although `"-0".parse::<u64>()` fails.
+    pub fn fits_u64(&self, s: &str) -> bool {
+        match s.strip_prefix('-') {
+            Some(abs) => self.is_int() && abs == "0",
+            None => self.is_int() && fits_max(s, "18446744073709551615"),
+        }
+    }
+
+    /// Return true if the number `s` having these parts is an integer that fits into `i64`.
+    ///
+    /// Like [`Parts::fits_u64`], this does not parse `s`.
+    pub fn fits_i64(&self, s: &str) -> bool {
+        match s.strip_prefix('-') {
+            Some(abs) => self.is_int() && fits_max(abs, "9223372036854775808"),
+            None => self.is_int() && fits_max(s, "9223372036854775807"),
+        }
+    }
+
+    /// Convert the number `s` having these parts to a float.
+    ///
+    /// This uses the recorded positions of the dot and the exponent
+    /// to convert the number without scanning it for them again.
+    /// Integers up to `u64::MAX` as well as numbers with
+    /// a significand below 2^53 and an exponent of at most 22 in magnitude
+    /// are converted directly and correctly rounded;
+    /// all other numbers fall back to [`str::parse`].
+    ///
+    /// ~~~
+    /// use hifijson::num::LexWrite;
+    /// let mut lexer = hifijson::SliceLexer::new(b"-12.5e-1");
+    /// let (num, parts) = lexer.num_string().unwrap();
+    /// assert_eq!(parts.to_f64(num), -1.25);
+    /// ~~~
+    pub fn to_f64(&self, s: &str) -> f64 {
+        self.to_f64_fast(s)
+            .unwrap_or_else(|| s.parse().unwrap_or(f64::NAN))
+    }
+
+    /// Return true if the number `s` having these parts is exactly representable as `f64`,
+    /// meaning that [`Parts::to_f64`] does not lose any precision.
+    ///
+    /// Numbers that overflow to infinity or
+    /// nonzero numbers that underflow to zero are not exactly representable.
+    pub fn fits_f64_exactly(&self, s: &str) -> bool {
+        self.is_exactly(s, self.to_f64(s))
+    }
+
+    /// Return true if `f` is the exact value of the number `s` having these parts.
+    fn is_exactly(&self, s: &str, f: f64) -> bool {
+        use core::fmt::Write;
+
+        if !f.is_finite() {
+            return false;
+        }
+        let (_neg, mut digits, exp) = self.scientific(s);
+        if f == 0.0 {
+            return digits.next().is_none();
+        }
+
+        // every finite `f64` has at most 767 significant decimal digits,
+        // so printing it with that many fractional digits yields its exact value
+        let mut cmp = ExactCmp { digits, exp: None };
+        write!(cmp, "{:.767e}", f.abs()).is_ok()
+            && cmp.exp.map(|(neg, e)| if neg { -e } else { e }) == Some(exp)
+            && cmp.digits.all(|d| *d == b'0')
+    }
+
+    /// Decompose the number `s` having these parts into
+    /// its sign, its significant digits, and
+    /// the exponent of its first significant digit in scientific notation.
+    ///
+    /// The significant digits start with a nonzero digit, but
+    /// they may end with zeros.
+    /// For zero, there are no significant digits.
+    fn scientific<'a>(&self, s: &'a str) -> (bool, impl Iterator<Item = &'a u8> + Clone, i64) {
+        let c = self.split(s);
+        let (int, frac) = (c.int.as_bytes(), c.frac.unwrap_or("").as_bytes());
+        let digits = int.iter().chain(frac);
+        let zeros = digits.clone().take_while(|d| **d == b'0').count();
+
+        let mut exp: i64 = 0;
+        for d in c.exp.unwrap_or("").bytes() {
+            exp = exp.saturating_mul(10).saturating_add(i64::from(d - b'0'));
+        }
+        if c.exp_sign == Some('-') {
+            exp = -exp
+        }
+        let exp = exp.saturating_add(int.len() as i64 - 1 - zeros as i64);
+        (self.neg, digits.skip(zeros), exp)
+    }
+
+    /// Convert a number to a float if this can be done exactly from its parts.
+    fn to_f64_fast(&self, s: &str) -> Option<f64> {
+        // 10^0 to 10^22 are exactly representable as `f64`
+        const POW10: [f64; 23] = [
+            1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15,
+            1e16, 1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+        ];
+
+        let c = self.split(s);
+        let frac = c.frac.unwrap_or("");
+
+        let mut mant: u64 = 0;
+        for d in c.int.bytes().chain(frac.bytes()) {
+            mant = mant.checked_mul(10)?.checked_add(u64::from(d - b'0'))?;
+        }
+
+        let mut exp: i32 = 0;
+        for d in c.exp.unwrap_or("").bytes() {
+            exp = exp.checked_mul(10)?.checked_add(i32::from(d - b'0'))?;
+        }
+        if c.exp_sign == Some('-') {
+            exp = -exp
+        }
+        let exp = exp.checked_sub(i32::try_from(frac.len()).ok()?)?;
+
+        let f = if exp == 0 {
+            mant as f64
+        } else if mant < 1 << 53 && exp.unsigned_abs() < 23 {
+            let pow = POW10[exp.unsigned_abs() as usize];
+            if exp < 0 {
+                mant as f64 / pow
+            } else {
+                mant as f64 * pow
+            }
+        } else {
+            return None;
+        };
+        Some(if self.neg { -f } else { f })
+    }
+}
+
+/// Convert a number to a float, and
+/// return whether the conversion was exact.
+///
+/// This is the same as calling [`Parts::to_f64`] and [`Parts::fits_f64_exactly`],
+/// but converts the number only once.
+/// If the conversion is not exact, you might want to keep the original number instead.
+///
+/// ~~~
+/// use hifijson::num::{to_f64_checked, LexWrite};
+/// let mut lexer = hifijson::SliceLexer::new(b"0.1");
+/// let (num, parts) = lexer.num_string().unwrap();
+/// assert_eq!(to_f64_checked(num, &parts), (0.1, false));
+/// ~~~
+pub fn to_f64_checked(num: &str, parts: &Parts) -> (f64, bool) {
+    let f = parts.to_f64(num);
+    (f, parts.is_exactly(num, f))
+}
+
+/// Compare two numbers by their values.
+///
+/// Unlike comparing the numbers after conversion to `f64`,
+/// this never loses precision;
+/// for example, `9007199254740993` is greater than `9007199254740992`.
+/// Numbers with the same value, such as `1`, `1.0`, and `10e-1`, are equal.
+///
+/// ~~~
+/// use core::cmp::Ordering;
+/// use hifijson::num::{cmp, LexWrite};
+/// let num = |s: &'static [u8]| hifijson::SliceLexer::new(s).num_string().unwrap();
+/// let ((l, lp), (r, rp)) = (num(b"-1.50"), num(b"-15e-1"));
+/// assert_eq!(cmp(l, &lp, r, &rp), Ordering::Equal);
+/// let ((l, lp), (r, rp)) = (num(b"0.001"), num(b"-2"));
+/// assert_eq!(cmp(l, &lp, r, &rp), Ordering::Greater);
+/// ~~~
+pub fn cmp(l: &str, l_parts: &Parts, r: &str, r_parts: &Parts) -> core::cmp::Ordering {
+    use core::cmp::Ordering;
+    let (l_neg, mut l_digits, l_exp) = l_parts.scientific(l);
+    let (r_neg, mut r_digits, r_exp) = r_parts.scientific(r);
+    // zero has no sign
+    let l_sign = l_digits.clone().next().map(|_| if l_neg { -1 } else { 1 });
+    let r_sign = r_digits.clone().next().map(|_| if r_neg { -1 } else { 1 });
+    let mut abs = || {
+        l_exp.cmp(&r_exp).then_with(|| loop {
+            match (l_digits.next(), r_digits.next()) {
+                (None, None) => return Ordering::Equal,
+                (l, r) => match l.unwrap_or(&b'0').cmp(r.unwrap_or(&b'0')) {
+                    Ordering::Equal => continue,
+                    o => return o,
+                },
+            }
+        })
+    };
+    match (l_sign.unwrap_or(0), r_sign.unwrap_or(0)) {
+        (0, 0) => Ordering::Equal,
+        (1, 1) => abs(),
+        (-1, -1) => abs().reverse(),
+        (l, r) => l.cmp(&r),
+    }
+}
+
+/// Writer that compares printed `f64` values in scientific notation with digits.
+struct ExactCmp<I> {
+    /// significant digits that we expect
+    digits: I,
+    /// sign and value of the exponent, once we have seen `e`
+    exp: Option<(bool, i64)>,
+}
+
+impl<'a, I: Iterator<Item = &'a u8>> core::fmt::Write for ExactCmp<I> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.bytes() {
+            match (&mut self.exp, c) {
+                (Some((neg, _)), b'-') => *neg = true,
+                (Some((_, e)), d) => *e = e.saturating_mul(10).saturating_add(i64::from(d - b'0')),
+                (None, b'.') => (),
+                (None, b'e') => self.exp = Some((false, 0)),
+                (None, d) if self.digits.next().unwrap_or(&b'0') == &d => (),
+                (None, _) => return Err(core::fmt::Error),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return true if the digits `s` denote a natural number smaller or equal to `max`.
+///
+/// Because JSON integers do not have leading zeros,
+/// a number with fewer digits than `max` is always smaller than `max`, and
+/// for the same number of digits,
+/// lexicographic comparison coincides with numeric comparison.
+fn fits_max(s: &str, max: &str) -> bool {
+    s.len() < max.len() || (s.len() == max.len() && s <= max)
+}
+
+/// Wrapper type to print numbers without insignificant trailing zeros.
+///
+/// This removes trailing zeros from the fractional part of a number,
+/// as well as the dot if the fractional part becomes empty.
+/// The integer part and the exponent are printed unchanged.
+///
+/// ~~~
+/// use hifijson::num::{LexWrite, StripZeros};
+/// let mut lexer = hifijson::SliceLexer::new(b"1.500e3");
+/// let (num, parts) = lexer.num_string().unwrap();
+/// assert_eq!(StripZeros::new(num, &parts).to_string(), "1.5e3");
+/// ~~~
+pub struct StripZeros<'a> {
+    num: &'a str,
+    parts: &'a Parts,
+}
+
+impl<'a> StripZeros<'a> {
+    /// Create a new number to be printed without trailing zeros.
+    ///
+    /// The parts must belong to the number, such as yielded by [`LexWrite::num_string`].
+    pub fn new(num: &'a str, parts: &'a Parts) -> Self {
+        Self { num, parts }
+    }
+}
+
+impl<'a> core::fmt::Display for StripZeros<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let exp = self.parts.exp.map_or(self.num.len(), |e| e.get());
+        match self.parts.dot {
+            Some(dot) => {
+                let frac = self.num[dot.get() + 1..exp].trim_end_matches('0');
+                let end = if frac.is_empty() {
+                    dot.get()
+                } else {
+                    dot.get() + 1 + frac.len()
+                };
+                self.num[..end].fmt(f)?;
+                self.num[exp..].fmt(f)
+            }
+            None => self.num.fmt(f),
+        }
+    }
+}
+
+/// Lossless decimal representation of a number.
+///
+/// The value of the number is
+/// `digits` (interpreted as decimal integer) times ten to the power of `exponent`,
+/// negated if `negative` is true.
+/// For example, `-1.5e-3` is represented by
+/// `digits = b"15"`, `exponent = -4`, and `negative = true`.
+///
+/// Leading zeros are removed from `digits`, so zero has no digits.
+/// Trailing zeros are kept, so `1.50` and `1.5` have different digits.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExactDecimal {
+    /// ASCII digits (`b'0'..=b'9'`) of the significand
+    pub digits: alloc::vec::Vec<u8>,
+    /// power of ten by which the significand is multiplied
+    pub exponent: i32,
+    /// true if the number starts with `-`
+    pub negative: bool,
+}
+
+/// Convert a number and its parts to an exact decimal.
+///
+/// The number must have been obtained by a number lexer,
+/// such as [`LexWrite::num_string`], which also yields the parts.
+/// Exponents beyond the range of `i32` saturate.
+///
+/// ~~~
+/// use hifijson::num::{to_exact_decimal, LexWrite};
+/// let mut lexer = hifijson::SliceLexer::new(b"1.5e-3");
+/// let (num, parts) = lexer.num_string().unwrap();
+/// let dec = to_exact_decimal(num, &parts);
+/// assert_eq!((&dec.digits[..], dec.exponent, dec.negative), (&b"15"[..], -4, false));
+/// ~~~
+#[cfg(feature = "alloc")]
+pub fn to_exact_decimal(num: &str, parts: &Parts) -> ExactDecimal {
+    let c = parts.split(num);
+    let frac = c.frac.unwrap_or("");
+
+    let mut exponent: i32 = 0;
+    let exp_neg = c.exp_sign == Some('-');
+    for d in c.exp.unwrap_or("").bytes() {
+        let d = i32::from(d - b'0');
+        exponent = exponent.saturating_mul(10);
+        exponent = if exp_neg {
+            exponent.saturating_sub(d)
+        } else {
+            exponent.saturating_add(d)
+        };
+    }
+    let frac_len = i32::try_from(frac.len()).unwrap_or(i32::MAX);
+    exponent = exponent.saturating_sub(frac_len);
+
+    let mut digits: alloc::vec::Vec<u8> = c.int.bytes().chain(frac.bytes()).collect();
+    let zeros = digits.iter().take_while(|d| **d == b'0').count();
+    digits.drain(..zeros);
+
+    ExactDecimal {
+        digits,
+        exponent,
+        negative: parts.neg,
+    }
 }
 
 /// Number lexing, ignoring the number.
+///
+/// The functions of this trait never allocate memory,
+/// not even when lexing from an iterator, and
+/// they are available without the feature flag `alloc`.
+/// For example, [`Lex::num_foreach`] yields the characters of a number
+/// together with its [`Parts`], without having to write them into a string first.
 pub trait Lex: Read {
     /// Perform `f` for every digit read.
     fn digits_foreach(&mut self, mut f: impl FnMut(u8)) {
@@ -62,92 +537,213 @@ pub trait Lex: Read {
     }
 
     /// Run function for each character of a number.
-    fn num_foreach(&mut self, mut f: impl FnMut(u8)) -> Result<Parts, Error> {
-        let mut pos = 0;
-        let mut parts = Parts::default();
+    ///
+    /// This allows for processing a number without buffering it.
+    ///
+    /// ~~~
+    /// use hifijson::{num::Lex, SliceLexer};
+    /// let mut bytes = Vec::new();
+    /// let parts = SliceLexer::new(b"-1.5e3,").num_foreach(|c| bytes.push(c)).unwrap();
+    /// assert_eq!((&bytes[..], parts.is_int()), (&b"-1.5e3"[..], false));
+    /// ~~~
+    fn num_foreach(&mut self, f: impl FnMut(u8)) -> Result<Parts, Error> {
+        foreach(self, false, f)
+    }
 
-        if let Some(b'-') = self.peek_next() {
-            f(b'-');
-            self.read_next();
-            pos += 1;
-        }
+    /// Lex a number and ignore its contents, saving only its parts.
+    fn num_ignore(&mut self) -> Result<Parts, Error> {
+        self.num_foreach(|_| ())
+    }
 
-        match self.peek_next() {
-            Some(b'0') => {
-                f(b'0');
-                self.read_next();
-                pos += 1;
-            }
-            Some(digit @ b'1'..=b'9') => {
-                f(*digit);
-                self.read_next();
-                pos += 1;
-                self.digits_foreach(|digit| {
-                    f(digit);
-                    pos += 1
-                })
-            }
-            _ => return Err(Error::ExpectedDigit),
-        }
+    /// Lex a number and ignore its contents, rejecting leading zeros.
+    ///
+    /// Unlike [`Lex::num_ignore`], which lexes `007` as `0` and leaves `07` unconsumed,
+    /// this fails with [`Error::LeadingZero`] if a digit follows a leading `0`.
+    fn num_ignore_strict(&mut self) -> Result<Parts, Error> {
+        foreach(self, true, |_| ())
+    }
+}
 
-        loop {
-            match self.peek_next() {
-                Some(b'.') if parts.is_int() => {
-                    parts.dot = Some(NonZeroUsize::new(pos).unwrap());
-                    f(b'.');
-                    self.read_next();
-                    pos += 1 + self.digits1_foreach(&mut f)?.get();
-                }
+impl<T> Lex for T where T: Read {}
 
-                Some(exp @ (b'e' | b'E')) if parts.exp.is_none() => {
-                    parts.exp = Some(NonZeroUsize::new(pos).unwrap());
-                    f(*exp);
-                    self.read_next();
+/// Check that `s` is a number according to the JSON grammar and return its parts.
+///
+/// Unlike lexing a number, this requires that the whole input is consumed and
+/// rejects leading zeros.
+///
+/// ~~~
+/// use hifijson::num::{validate, Error};
+/// assert_eq!(validate(b"-1.5e3").unwrap().dot.map(|d| d.get()), Some(2));
+/// assert_eq!(validate(b"1x"), Err(Error::Trailing));
+/// assert_eq!(validate(b"1 2"), Err(Error::Trailing));
+/// assert_eq!(validate(b"01"), Err(Error::LeadingZero));
+/// assert_eq!(validate(b"1."), Err(Error::ExpectedDigit));
+/// ~~~
+pub fn validate(s: &[u8]) -> Result<Parts, Error> {
+    let mut lexer = crate::SliceLexer::new(s);
+    let parts = lexer.num_ignore_strict()?;
+    if lexer.as_slice().is_empty() {
+        Ok(parts)
+    } else {
+        Err(Error::Trailing)
+    }
+}
 
-                    if let Some(sign @ (b'+' | b'-')) = self.peek_next() {
-                        f(*sign);
-                        self.read_next();
-                        pos += 1;
-                    }
+/// Run function for each character of a number.
+///
+/// If `strict` is true, fail if a digit follows a leading zero.
+fn foreach<L: Lex + ?Sized>(
+    lexer: &mut L,
+    strict: bool,
+    mut f: impl FnMut(u8),
+) -> Result<Parts, Error> {
+    let mut pos = 0;
+    let mut parts = Parts::default();
+
+    if let Some(b'-') = lexer.peek_next() {
+        f(b'-');
+        lexer.read_next();
+        pos += 1;
+        parts.neg = true;
+    }
 
-                    pos += 1 + self.digits1_foreach(&mut f)?.get();
-                }
-                _ => return Ok(parts),
+    match lexer.peek_next() {
+        Some(b'0') => {
+            f(b'0');
+            lexer.read_next();
+            pos += 1;
+            if strict && matches!(lexer.peek_next(), Some(b'0'..=b'9')) {
+                return Err(Error::LeadingZero);
             }
         }
+        Some(digit @ b'1'..=b'9') => {
+            f(*digit);
+            lexer.read_next();
+            pos += 1;
+            lexer.digits_foreach(|digit| {
+                f(digit);
+                pos += 1
+            })
+        }
+        _ => return Err(Error::ExpectedDigit),
     }
 
-    /// Lex a number and ignore its contents, saving only its parts.
-    fn num_ignore(&mut self) -> Result<Parts, Error> {
-        self.num_foreach(|_| ())
+    loop {
+        match lexer.peek_next() {
+            Some(b'.') if parts.is_int() => {
+                parts.dot = Some(NonZeroUsize::new(pos).unwrap());
+                f(b'.');
+                lexer.read_next();
+                pos += 1 + lexer.digits1_foreach(&mut f)?.get();
+            }
+
+            Some(exp @ (b'e' | b'E')) if parts.exp.is_none() => {
+                parts.exp = Some(NonZeroUsize::new(pos).unwrap());
+                f(*exp);
+                lexer.read_next();
+
+                if let Some(sign @ (b'+' | b'-')) = lexer.peek_next() {
+                    f(*sign);
+                    lexer.read_next();
+                    pos += 1;
+                }
+
+                pos += 1 + lexer.digits1_foreach(&mut f)?.get();
+            }
+            _ => return Ok(parts),
+        }
     }
 }
 
-impl<T> Lex for T where T: Read {}
-
 /// Number lexing, keeping the number.
 pub trait LexWrite: Lex + Write {
     /// String type to save numbers as.
     type Num: core::ops::Deref<Target = str>;
 
     /// Write a number to bytes and save its parts.
-    fn num_bytes(&mut self, bytes: &mut Self::Bytes) -> Result<Parts, Error>;
+    fn num_bytes(&mut self, bytes: &mut Self::Bytes) -> Result<Parts, Error> {
+        self.num_bytes_bounded(bytes, usize::MAX)
+    }
+
+    /// Like [`LexWrite::num_bytes`], but fail with [`Error::TooLong`]
+    /// once the number is longer than `max` bytes.
+    ///
+    /// When reading from an iterator, this stops reading as soon as
+    /// the number has become too long.
+    ///
+    /// ~~~
+    /// use hifijson::num::{Error, LexWrite};
+    /// let mut bytes = Default::default();
+    /// let mut lexer = hifijson::SliceLexer::new(b"1.5e10");
+    /// assert!(lexer.num_bytes_bounded(&mut bytes, 6).is_ok());
+    /// let mut lexer = hifijson::SliceLexer::new(b"1.5e10");
+    /// assert_eq!(lexer.num_bytes_bounded(&mut bytes, 5), Err(Error::TooLong));
+    /// ~~~
+    fn num_bytes_bounded(&mut self, bytes: &mut Self::Bytes, max: usize) -> Result<Parts, Error>;
+
     /// Read a number to a string and save its parts.
-    fn num_string(&mut self) -> Result<(Self::Num, Parts), Error>;
+    fn num_string(&mut self) -> Result<(Self::Num, Parts), Error> {
+        self.num_string_bounded(usize::MAX)
+    }
+
+    /// Like [`LexWrite::num_string`], but fail with [`Error::TooLong`]
+    /// once the number is longer than `max` bytes.
+    ///
+    /// See [`LexWrite::num_bytes_bounded`].
+    fn num_string_bounded(&mut self, max: usize) -> Result<(Self::Num, Parts), Error>;
+
+    /// Read a number to a string and also return its `f64` approximation.
+    ///
+    /// The string is borrowed from the input for [`crate::SliceLexer`] and
+    /// owned for [`crate::IterLexer`].
+    ///
+    /// ~~~
+    /// use hifijson::num::LexWrite;
+    /// let mut lexer = hifijson::SliceLexer::new(b"-1.5e3");
+    /// let (f, num, _parts) = lexer.num_f64_and_str().unwrap();
+    /// assert_eq!((f, &*num), (-1500.0, "-1.5e3"));
+    /// ~~~
+    fn num_f64_and_str(&mut self) -> Result<(f64, Self::Num, Parts), Error> {
+        let (num, parts) = self.num_string()?;
+        Ok((parts.to_f64(&num), num, parts))
+    }
 }
 
 fn digits(s: &[u8]) -> usize {
     s.iter()
-        .position(|c| !matches!(c, b'0'..=b'9'))
+        .position(|c| !c.is_ascii_digit())
         .unwrap_or(s.len())
 }
 
+impl<'a> crate::SliceLexer<'a> {
+    /// Lex a number and return the slice spanning it, including a leading `-`.
+    ///
+    /// This never copies the number.
+    ///
+    /// ~~~
+    /// let mut lexer = hifijson::SliceLexer::new(b"-1.5e3, 2");
+    /// let (num, parts) = lexer.num_slice().unwrap();
+    /// assert_eq!(num, b"-1.5e3");
+    /// assert_eq!(parts.dot.map(|d| d.get()), Some(2));
+    /// assert_eq!(lexer.as_slice(), b", 2");
+    /// ~~~
+    pub fn num_slice(&mut self) -> Result<(&'a [u8], Parts), Error> {
+        let mut num = Default::default();
+        let parts = self.num_bytes(&mut num)?;
+        Ok((num, parts))
+    }
+}
+
 impl<'a> LexWrite for crate::SliceLexer<'a> {
     type Num = &'a str;
 
-    fn num_bytes(&mut self, bytes: &mut Self::Bytes) -> Result<Parts, Error> {
-        let mut pos = usize::from(self.slice[0] == b'-');
-        let mut parts = Parts::default();
+    fn num_bytes_bounded(&mut self, bytes: &mut Self::Bytes, max: usize) -> Result<Parts, Error> {
+        let neg = self.slice.first() == Some(&b'-');
+        let mut pos = usize::from(neg);
+        let mut parts = Parts {
+            neg,
+            ..Parts::default()
+        };
 
         let digits1 = |s| NonZeroUsize::new(digits(s)).ok_or(Error::ExpectedDigit);
 
@@ -172,6 +768,7 @@ impl<'a> LexWrite for crate::SliceLexer<'a> {
                     }
                     pos += digits1(&self.slice[pos..])?.get()
                 }
+                None | Some(_) if pos > max => return Err(Error::TooLong),
                 None | Some(_) => {
                     *bytes = &self.slice[..pos];
                     self.slice = &self.slice[pos..];
@@ -181,9 +778,9 @@ impl<'a> LexWrite for crate::SliceLexer<'a> {
         }
     }
 
-    fn num_string(&mut self) -> Result<(Self::Num, Parts), Error> {
+    fn num_string_bounded(&mut self, max: usize) -> Result<(Self::Num, Parts), Error> {
         let mut num = Default::default();
-        let pos = self.num_bytes(&mut num)?;
+        let pos = self.num_bytes_bounded(&mut num, max)?;
         // SAFETY: conversion to UTF-8 always succeeds because
         // lex_number validates everything it writes to num
         Ok((core::str::from_utf8(num).unwrap(), pos))
@@ -192,9 +789,12 @@ impl<'a> LexWrite for crate::SliceLexer<'a> {
 
 #[cfg(feature = "alloc")]
 impl<E, I: Iterator<Item = Result<u8, E>>> crate::IterLexer<E, I> {
-    fn digits(&mut self, num: &mut <Self as Write>::Bytes) -> Result<(), Error> {
+    fn digits(&mut self, num: &mut <Self as Write>::Bytes, max: usize) -> Result<(), Error> {
         let mut some_digit = false;
         while let Some(digit @ (b'0'..=b'9')) = self.last {
+            if num.len() >= max {
+                return Err(Error::TooLong);
+            }
             some_digit = true;
             num.push(digit);
             self.last = self.read();
@@ -211,19 +811,20 @@ impl<E, I: Iterator<Item = Result<u8, E>>> crate::IterLexer<E, I> {
 impl<E, I: Iterator<Item = Result<u8, E>>> LexWrite for crate::IterLexer<E, I> {
     type Num = alloc::string::String;
 
-    fn num_bytes(&mut self, num: &mut Self::Bytes) -> Result<Parts, Error> {
+    fn num_bytes_bounded(&mut self, num: &mut Self::Bytes, max: usize) -> Result<Parts, Error> {
         let mut parts = Parts::default();
 
         if self.last == Some(b'-') {
             num.push(b'-');
             self.last = self.read();
+            parts.neg = true;
         }
 
         if self.last == Some(b'0') {
             num.push(b'0');
             self.last = self.read();
         } else {
-            self.digits(num)?;
+            self.digits(num, max)?;
         }
 
         loop {
@@ -233,7 +834,7 @@ impl<E, I: Iterator<Item = Result<u8, E>>> LexWrite for crate::IterLexer<E, I> {
                     num.push(b'.');
                     self.last = self.read();
 
-                    self.digits(num)?;
+                    self.digits(num, max)?;
                 }
 
                 Some(e @ (b'e' | b'E')) if parts.exp.is_none() => {
@@ -246,16 +847,17 @@ impl<E, I: Iterator<Item = Result<u8, E>>> LexWrite for crate::IterLexer<E, I> {
                         self.last = self.read();
                     }
 
-                    self.digits(num)?;
+                    self.digits(num, max)?;
                 }
+                _ if num.len() > max => return Err(Error::TooLong),
                 _ => return Ok(parts),
             }
         }
     }
 
-    fn num_string(&mut self) -> Result<(Self::Num, Parts), Error> {
+    fn num_string_bounded(&mut self, max: usize) -> Result<(Self::Num, Parts), Error> {
         let mut num = Default::default();
-        let pos = self.num_bytes(&mut num)?;
+        let pos = self.num_bytes_bounded(&mut num, max)?;
         // SAFETY: conversion to UTF-8 always succeeds because
         // lex_number validates everything it writes to num
         Ok((alloc::string::String::from_utf8(num).unwrap(), pos))
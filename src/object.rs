@@ -0,0 +1,106 @@
+//! Objects.
+
+use crate::{ignore, str, Error, Expect, LexAlloc, Token};
+
+/// Parse the value of the first occurrence of `key` in an object with `f`.
+///
+/// The object has to start with `token`.
+/// All other values in the object are discarded with [`ignore::parse`],
+/// and the whole object is consumed.
+/// If the object does not contain `key`, return `None`.
+///
+/// ~~~
+/// use hifijson::{object, token::Lex, value, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"{"a": 1, "b": [2]}"#);
+/// let b = lexer.exactly_one(|token, lexer| object::get_field(token, lexer, "b", value::parse_unbounded));
+/// assert_eq!(b.unwrap().unwrap().to_string(), "[2]");
+/// ~~~
+pub fn get_field<L: LexAlloc, T, F>(
+    token: Token,
+    lexer: &mut L,
+    key: &str,
+    f: F,
+) -> Result<Option<T>, Error>
+where
+    F: FnOnce(Token, &mut L) -> Result<T, Error>,
+{
+    get_field_bounded(token, lexer, key, usize::MAX, f)
+}
+
+/// Like [`get_field`], but fail with [`Error::KeyTooLong`]
+/// once any key in the object is longer than `max_key_len`.
+///
+/// The length of a key is measured in input bytes,
+/// like for [`str::LexAlloc::str_string_bounded`].
+///
+/// ~~~
+/// use hifijson::{ignore, object, token::Lex, Error, SliceLexer};
+/// let get = |json: &[u8]| SliceLexer::new(json).exactly_one(|token, lexer| {
+///     object::get_field_bounded(token, lexer, "b", 3, ignore::parse)
+/// });
+/// assert_eq!(get(br#"{"a": 1, "b": 2}"#), Ok(Some(())));
+/// assert_eq!(get(br#"{"abcd": 1, "b": 2}"#), Err(Error::KeyTooLong));
+/// ~~~
+pub fn get_field_bounded<L: LexAlloc, T, F>(
+    token: Token,
+    lexer: &mut L,
+    key: &str,
+    max_key_len: usize,
+    f: F,
+) -> Result<Option<T>, Error>
+where
+    F: FnOnce(Token, &mut L) -> Result<T, Error>,
+{
+    token.equals_or(Token::LCurly, Expect::Value)?;
+    let mut f = Some(f);
+    let mut out = None;
+    lexer.seq(Token::RCurly, |token, lexer| {
+        let k = lexer.str_colon(token, |lexer| key_bounded(lexer, max_key_len))?;
+        let token = lexer.ws_token().ok_or(Expect::Value)?;
+        match f.take() {
+            Some(f) if *k == *key => out = Some(f(token, lexer)?),
+            g => {
+                f = g;
+                ignore::parse(token, lexer)?
+            }
+        }
+        Ok::<_, Error>(())
+    })?;
+    Ok(out)
+}
+
+/// Read an object key, failing with [`Error::KeyTooLong`] if it is longer than `max`.
+fn key_bounded<L: LexAlloc>(lexer: &mut L, max: usize) -> Result<L::Str, Error> {
+    lexer.str_string_bounded(max).map_err(|e| match e {
+        str::Error::TooLong => Error::KeyTooLong,
+        e => Error::Str(e),
+    })
+}
+
+/// Parse the value of `key` in an object with `f`, or
+/// return `default` if the object does not contain `key` or its value is `null`.
+///
+/// ~~~
+/// use hifijson::{object, token::Lex, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"{"verbose": null}"#);
+/// let verbose = lexer.exactly_one(|token, lexer| {
+///     object::get_field_or(token, lexer, "verbose", false, |token, _| Ok(token == hifijson::Token::True))
+/// });
+/// assert_eq!(verbose, Ok(false));
+/// ~~~
+pub fn get_field_or<L: LexAlloc, T, F>(
+    token: Token,
+    lexer: &mut L,
+    key: &str,
+    default: T,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(Token, &mut L) -> Result<T, Error>,
+{
+    let v = get_field(token, lexer, key, |token, lexer| match token {
+        Token::Null => Ok(None),
+        token => f(token, lexer).map(Some),
+    })?;
+    Ok(v.flatten().unwrap_or(default))
+}
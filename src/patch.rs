@@ -0,0 +1,170 @@
+//! JSON Patch (RFC 6902) operations.
+//!
+//! Patches can be generated with [`value::diff`](crate::value::diff) and
+//! applied with [`apply`].
+
+use crate::value::Value;
+use alloc::string::String;
+use core::fmt;
+use core::ops::Deref;
+
+/// Patch operation.
+///
+/// Paths are JSON Pointers (RFC 6901), such as `/a/0`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Op<Num, Str> {
+    /// insert a value at the path
+    Add(String, Value<Num, Str>),
+    /// remove the value at the path
+    Remove(String),
+    /// replace the value at the path
+    Replace(String, Value<Num, Str>),
+}
+
+impl<Num: Deref<Target = str>, Str: Deref<Target = str>> fmt::Display for Op<Num, Str> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::str::Display;
+        let (op, path, value) = match self {
+            Self::Add(path, v) => ("add", path, Some(v)),
+            Self::Remove(path) => ("remove", path, None),
+            Self::Replace(path, v) => ("replace", path, Some(v)),
+        };
+        let path = Display::new(&**path);
+        write!(f, r#"{{"op":"{}","path":{}"#, op, path)?;
+        if let Some(v) = value {
+            write!(f, r#","value":{}"#, v)?;
+        }
+        "}".fmt(f)
+    }
+}
+
+/// Patch application error.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// the path does not point to a (new) value
+    Path(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Path(p) => write!(f, "invalid path {}", p),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Append a key to a JSON Pointer, escaping `~` and `/`.
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    path.push('/');
+    for c in key.chars() {
+        match c {
+            '~' => path.push_str("~0"),
+            '/' => path.push_str("~1"),
+            c => path.push(c),
+        }
+    }
+}
+
+/// Decode a reference token of a JSON Pointer, replacing `~1` by `/` and `~0` by `~`.
+pub(crate) fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Parse an array index of a JSON Pointer, which is `0` or a digit sequence without leading zero.
+pub(crate) fn array_index(s: &str) -> Option<usize> {
+    let digits = s.bytes().all(|c| c.is_ascii_digit());
+    if !digits || s.is_empty() || (s.len() > 1 && s.starts_with('0')) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Apply patch operations in sequence to a value.
+///
+/// When adding a key that already exists in an object, its first occurrence is replaced.
+///
+/// ~~~
+/// use hifijson::{patch, token::Lex, value, SliceLexer};
+/// let mut v = SliceLexer::new(br#"{"a": [1]}"#).exactly_one(value::parse_unbounded).unwrap();
+/// let w = SliceLexer::new(br#"{"a": [1, 2], "b": true}"#).exactly_one(value::parse_unbounded).unwrap();
+/// let ops = value::diff(&v, &w);
+/// patch::apply(&mut v, &ops).unwrap();
+/// assert_eq!(v, w);
+/// ~~~
+pub fn apply<Num: Clone, Str: Clone + Deref<Target = str> + From<String>>(
+    value: &mut Value<Num, Str>,
+    ops: &[Op<Num, Str>],
+) -> Result<(), Error> {
+    ops.iter().try_for_each(|op| apply_one(value, op))
+}
+
+fn apply_one<Num: Clone, Str: Clone + Deref<Target = str> + From<String>>(
+    value: &mut Value<Num, Str>,
+    op: &Op<Num, Str>,
+) -> Result<(), Error> {
+    let path = match op {
+        Op::Add(path, _) | Op::Remove(path) | Op::Replace(path, _) => path,
+    };
+    let err = || Error::Path(path.clone());
+
+    let (parent, last) = match path.rfind('/') {
+        Some(pos) => (&path[..pos], unescape(&path[pos + 1..])),
+        None if path.is_empty() => {
+            return match op {
+                Op::Add(_, v) | Op::Replace(_, v) => {
+                    *value = v.clone();
+                    Ok(())
+                }
+                Op::Remove(_) => Err(err()),
+            }
+        }
+        None => return Err(err()),
+    };
+
+    let mut target = value;
+    for token in parent.split('/').skip(1) {
+        let token = unescape(token);
+        target = match target {
+            Value::Array(arr) => array_index(&token).and_then(|i| arr.get_mut(i)),
+            Value::Object(obj) => obj.iter_mut().find(|(k, _)| **k == *token).map(|(_, v)| v),
+            _ => None,
+        }
+        .ok_or_else(err)?;
+    }
+
+    match (target, op) {
+        (Value::Array(arr), Op::Add(_, v)) => {
+            let idx = if last == "-" {
+                Some(arr.len())
+            } else {
+                array_index(&last)
+            };
+            let idx = idx.filter(|i| *i <= arr.len()).ok_or_else(err)?;
+            arr.insert(idx, v.clone())
+        }
+        (Value::Array(arr), Op::Remove(_)) => {
+            let idx = array_index(&last)
+                .filter(|i| *i < arr.len())
+                .ok_or_else(err)?;
+            arr.remove(idx);
+        }
+        (Value::Array(arr), Op::Replace(_, v)) => {
+            let idx = array_index(&last);
+            *idx.and_then(|i| arr.get_mut(i)).ok_or_else(err)? = v.clone()
+        }
+        (Value::Object(obj), op) => {
+            let pos = obj.iter().position(|(k, _)| **k == *last);
+            match (pos, op) {
+                (Some(pos), Op::Add(_, v) | Op::Replace(_, v)) => obj[pos].1 = v.clone(),
+                (None, Op::Add(_, v)) => obj.push((Str::from(last), v.clone())),
+                (Some(pos), Op::Remove(_)) => drop(obj.remove(pos)),
+                (None, Op::Remove(_) | Op::Replace(..)) => return Err(err()),
+            }
+        }
+        _ => return Err(err()),
+    }
+    Ok(())
+}
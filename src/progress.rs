@@ -0,0 +1,155 @@
+//! Progress reporting.
+//!
+//! When reading large inputs, it can be useful to know how far parsing has progressed,
+//! for example to show a progress bar.
+//! For this, you can wrap any lexer in a [`ProgressLexer`],
+//! which regularly reports the number of bytes consumed so far.
+//!
+//! ~~~
+//! use hifijson::{progress::ProgressLexer, token::Lex, SliceLexer};
+//! let json = br#"[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]"#;
+//! let mut reports = Vec::new();
+//! let mut lexer = ProgressLexer::new(SliceLexer::new(json), 10, |n| reports.push(n));
+//! lexer.exactly_one(hifijson::ignore::parse).unwrap();
+//! assert_eq!(reports.len(), 3);
+//! ~~~
+
+use crate::{num, str, Position, Read, Write};
+
+/// Lexer that calls a function whenever a certain number of bytes have been consumed.
+///
+/// All lexing is delegated to the inner lexer.
+/// Because the inner lexer may consume several bytes at once
+/// (for example when lexing a string),
+/// the reported number of consumed bytes may exceed a multiple of the interval.
+pub struct ProgressLexer<L, F> {
+    lexer: L,
+    f: F,
+    every: usize,
+    /// position of the inner lexer at creation
+    start: usize,
+    /// number of consumed bytes at which to report next
+    next: usize,
+}
+
+impl<L: Position, F: FnMut(usize)> ProgressLexer<L, F> {
+    /// Wrap a lexer such that `f` is called whenever
+    /// another `every` bytes have been consumed,
+    /// with the total number of bytes consumed by the wrapper so far.
+    ///
+    /// If `every` is 0, it is treated as 1.
+    pub fn new(lexer: L, every: usize, f: F) -> Self {
+        let every = every.max(1);
+        let start = lexer.position();
+        Self {
+            lexer,
+            f,
+            every,
+            start,
+            next: every,
+        }
+    }
+
+    /// Return the inner lexer.
+    pub fn into_inner(self) -> L {
+        self.lexer
+    }
+
+    /// Call the function if at least `next` bytes have been consumed.
+    fn report<T>(&mut self, x: T) -> T {
+        let consumed = self.lexer.position().wrapping_sub(self.start);
+        if consumed >= self.next {
+            (self.f)(consumed);
+            self.next = (consumed / self.every + 1) * self.every;
+        }
+        x
+    }
+}
+
+impl<L: Position, F> Position for ProgressLexer<L, F> {
+    fn position(&self) -> usize {
+        self.lexer.position()
+    }
+}
+
+impl<L: Read + Position, F: FnMut(usize)> Read for ProgressLexer<L, F> {
+    fn strip_prefix(&mut self, s: &[u8]) -> bool {
+        let x = self.lexer.strip_prefix(s);
+        self.report(x)
+    }
+
+    fn skip_until(&mut self, stop: impl FnMut(u8) -> bool) {
+        self.lexer.skip_until(stop);
+        self.report(())
+    }
+
+    fn skip_next_until(&mut self, stop: impl FnMut(u8) -> bool) {
+        self.lexer.skip_next_until(stop);
+        self.report(())
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        let x = self.lexer.read();
+        self.report(x)
+    }
+
+    fn read_next(&mut self) {
+        self.lexer.read_next();
+        self.report(())
+    }
+
+    fn peek_next(&self) -> Option<&u8> {
+        self.lexer.peek_next()
+    }
+
+    fn take_next(&mut self) -> Option<u8> {
+        let x = self.lexer.take_next();
+        self.report(x)
+    }
+}
+
+impl<L: Write + Position, F: FnMut(usize)> Write for ProgressLexer<L, F> {
+    type Bytes = L::Bytes;
+
+    fn write_until(&mut self, bytes: &mut Self::Bytes, stop: impl FnMut(u8) -> bool) {
+        self.lexer.write_until(bytes, stop);
+        self.report(())
+    }
+
+    fn write_until_str_end(&mut self, bytes: &mut Self::Bytes) {
+        self.lexer.write_until_str_end(bytes);
+        self.report(())
+    }
+}
+
+impl<L: num::LexWrite + Position, F: FnMut(usize)> num::LexWrite for ProgressLexer<L, F> {
+    type Num = L::Num;
+
+    fn num_bytes_bounded(
+        &mut self,
+        bytes: &mut Self::Bytes,
+        max: usize,
+    ) -> Result<num::Parts, num::Error> {
+        let x = self.lexer.num_bytes_bounded(bytes, max);
+        self.report(x)
+    }
+
+    fn num_string_bounded(&mut self, max: usize) -> Result<(Self::Num, num::Parts), num::Error> {
+        let x = self.lexer.num_string_bounded(max);
+        self.report(x)
+    }
+}
+
+impl<L: str::LexAlloc + Position, F: FnMut(usize)> str::LexAlloc for ProgressLexer<L, F> {
+    type Str = L::Str;
+
+    fn str_string_bounded(&mut self, max: usize) -> Result<Self::Str, str::Error> {
+        let x = self.lexer.str_string_bounded(max);
+        self.report(x)
+    }
+
+    fn str_string_lenient_bounded(&mut self, max: usize) -> Result<Self::Str, str::Error> {
+        let x = self.lexer.str_string_lenient_bounded(max);
+        self.report(x)
+    }
+}
@@ -1,7 +1,7 @@
 /// Low-level input operations.
 pub trait Read {
     /// Return `true` if the given byte sequence is a prefix of the input.
-    fn strip_prefix<const N: usize>(&mut self, s: [u8; N]) -> bool;
+    fn strip_prefix(&mut self, s: &[u8]) -> bool;
 
     /// Run a function on current input until a certain condition is fulfilled.
     fn foreach_until(&mut self, mut f: impl FnMut(u8), mut stop: impl FnMut(u8) -> bool) {
@@ -32,8 +32,8 @@ pub trait Read {
 }
 
 impl<'a> Read for crate::SliceLexer<'a> {
-    fn strip_prefix<const N: usize>(&mut self, s: [u8; N]) -> bool {
-        if let Some(rest) = self.slice.strip_prefix(&s) {
+    fn strip_prefix(&mut self, s: &[u8]) -> bool {
+        if let Some(rest) = self.slice.strip_prefix(s) {
             self.slice = rest;
             true
         } else {
@@ -70,10 +70,10 @@ impl<'a> Read for crate::SliceLexer<'a> {
 }
 
 impl<E, I: Iterator<Item = Result<u8, E>>> Read for crate::IterLexer<E, I> {
-    fn strip_prefix<const N: usize>(&mut self, s: [u8; N]) -> bool {
+    fn strip_prefix(&mut self, s: &[u8]) -> bool {
         for c1 in s {
             match self.read() {
-                Some(c2) if c1 == c2 => continue,
+                Some(c2) if *c1 == c2 => continue,
                 Some(_) | None => return false,
             }
         }
@@ -82,6 +82,7 @@ impl<E, I: Iterator<Item = Result<u8, E>>> Read for crate::IterLexer<E, I> {
 
     fn skip_until(&mut self, mut stop: impl FnMut(u8) -> bool) {
         for c in self.bytes.by_ref() {
+            self.pos += 1;
             match c {
                 Ok(c) if !stop(c) => continue,
                 Ok(c) => self.last = Some(c),
@@ -92,6 +93,7 @@ impl<E, I: Iterator<Item = Result<u8, E>>> Read for crate::IterLexer<E, I> {
             }
             return;
         }
+        self.exhausted = true;
         self.last = None
     }
 
@@ -103,7 +105,11 @@ impl<E, I: Iterator<Item = Result<u8, E>>> Read for crate::IterLexer<E, I> {
     }
 
     fn read(&mut self) -> Option<u8> {
-        match self.bytes.next()? {
+        let b = self.bytes.next();
+        self.exhausted |= b.is_none();
+        let b = b?;
+        self.pos += 1;
+        match b {
             Ok(b) => Some(b),
             Err(e) => {
                 self.error = Some(e);
@@ -124,3 +130,34 @@ impl<E, I: Iterator<Item = Result<u8, E>>> Read for crate::IterLexer<E, I> {
         self.last.as_ref()
     }
 }
+
+/// Position in the input.
+///
+/// This is implemented by [`SliceLexer`](crate::SliceLexer) and
+/// [`IterLexer`](crate::IterLexer), and it is required by functions such as
+/// [`value::parse_limited`](crate::value::parse_limited) and
+/// [`value::parse_recovering`](crate::value::parse_recovering).
+///
+/// ~~~
+/// use hifijson::{token::Lex, Position, SliceLexer};
+/// let mut lexer = SliceLexer::new(b"  [1]");
+/// let start = lexer.position();
+/// lexer.ws_token();
+/// assert_eq!(lexer.position().wrapping_sub(start), 3);
+/// ~~~
+pub trait Position {
+    /// Return a number that increases by one for every byte consumed from the input.
+    fn position(&self) -> usize;
+}
+
+impl<'a> Position for crate::SliceLexer<'a> {
+    fn position(&self) -> usize {
+        usize::MAX - self.slice.len()
+    }
+}
+
+impl<E, I> Position for crate::IterLexer<E, I> {
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
@@ -0,0 +1,173 @@
+//! Validation against a minimal schema.
+//!
+//! A schema describes the expected kind of a value and,
+//! for objects, which keys are required and which schemas their values must satisfy.
+//! This is a small subset of JSON Schema (no references, no patterns).
+//!
+//! ~~~
+//! use hifijson::schema::{check, Schema};
+//! use hifijson::token::{Kind, Lex};
+//!
+//! let schema = Schema {
+//!     kind: Some(Kind::Object),
+//!     required: vec!["name".into()],
+//!     properties: vec![("name".into(), Schema::kind(Kind::String))],
+//!     ..Schema::default()
+//! };
+//!
+//! let mut lexer = hifijson::SliceLexer::new(br#"{"name": "Duke", "age": 3}"#);
+//! assert!(lexer.exactly_one(|token, lexer| check(&schema, token, lexer)).is_ok());
+//!
+//! let mut lexer = hifijson::SliceLexer::new(br#"{"name": 1}"#);
+//! let err = lexer.exactly_one(|token, lexer| check(&schema, token, lexer));
+//! assert_eq!(err.unwrap_err().to_string(), "/name: string expected, found number");
+//! ~~~
+
+use crate::patch::push_key;
+use crate::token::{Expect, Kind};
+use crate::{ignore, LexAlloc, Token};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+/// Expected shape of a value.
+#[derive(Debug, Default)]
+pub struct Schema {
+    /// kind of the value (any kind if `None`)
+    pub kind: Option<Kind>,
+    /// keys that an object must contain
+    pub required: Vec<String>,
+    /// schemas for the values of object keys
+    pub properties: Vec<(String, Schema)>,
+    /// schema for every element of an array
+    pub items: Option<Box<Schema>>,
+}
+
+impl Schema {
+    /// Create a schema that only checks the kind of a value.
+    pub fn kind(kind: Kind) -> Self {
+        Self {
+            kind: Some(kind),
+            ..Self::default()
+        }
+    }
+}
+
+/// Validation error.
+///
+/// Paths are given as JSON Pointers (RFC 6901), such as `/a/0`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// input is not valid JSON
+    Parse(crate::Error),
+    /// value at `path` has kind `found`, but `expected` was required
+    Kind {
+        /// path to the value
+        path: String,
+        /// kind required by the schema
+        expected: Kind,
+        /// kind of the value
+        found: Kind,
+    },
+    /// object at `path` does not contain the required `key`
+    Required {
+        /// path to the object
+        path: String,
+        /// missing key
+        key: String,
+    },
+}
+
+impl<E: Into<crate::Error>> From<E> for Error {
+    fn from(e: E) -> Self {
+        Self::Parse(e.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+        match self {
+            Parse(e) => e.fmt(f),
+            Kind {
+                path,
+                expected,
+                found,
+            } => write!(f, "{}: {} expected, found {}", path, expected, found),
+            Required { path, key } => write!(f, "{}: missing required key {}", path, key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Check that the value starting with `token` satisfies the schema.
+pub fn check<L: LexAlloc>(schema: &Schema, token: Token, lexer: &mut L) -> Result<(), Error> {
+    check_at(schema, token, lexer, &mut String::new())
+}
+
+fn check_at<L: LexAlloc>(
+    schema: &Schema,
+    token: Token,
+    lexer: &mut L,
+    path: &mut String,
+) -> Result<(), Error> {
+    let found = token.kind().ok_or(Expect::Value)?;
+    if let Some(expected) = schema.kind.filter(|k| *k != found) {
+        let path = path.clone();
+        return Err(Error::Kind {
+            path,
+            expected,
+            found,
+        });
+    }
+
+    match token {
+        Token::LSquare => {
+            let mut idx = 0_usize;
+            lexer.seq(Token::RSquare, |token, lexer| {
+                let len = path.len();
+                write!(path, "/{}", idx).unwrap();
+                match &schema.items {
+                    Some(items) => check_at(items, token, lexer, path)?,
+                    None => ignore::parse(token, lexer)?,
+                }
+                path.truncate(len);
+                idx += 1;
+                Ok::<_, Error>(())
+            })
+        }
+        Token::LCurly => {
+            let mut seen = alloc::vec![false; schema.required.len()];
+            lexer.seq(Token::RCurly, |token, lexer| {
+                let key =
+                    lexer.str_colon(token, |lexer| lexer.str_string().map_err(Error::from))?;
+                let token = lexer.ws_token().ok_or(Expect::Value)?;
+
+                for (seen, required) in seen.iter_mut().zip(&schema.required) {
+                    *seen |= **required == *key;
+                }
+                match schema.properties.iter().find(|(k, _)| **k == *key) {
+                    Some((_, schema)) => {
+                        let len = path.len();
+                        push_key(path, &key);
+                        check_at(schema, token, lexer, path)?;
+                        path.truncate(len);
+                    }
+                    None => ignore::parse(token, lexer)?,
+                }
+                Ok::<_, Error>(())
+            })?;
+
+            let missing = seen.iter().zip(&schema.required).find(|(seen, _)| !**seen);
+            match missing {
+                Some((_, key)) => Err(Error::Required {
+                    path: path.clone(),
+                    key: key.clone(),
+                }),
+                None => Ok(()),
+            }
+        }
+        token => Ok(ignore::parse(token, lexer)?),
+    }
+}
@@ -8,9 +8,32 @@
 //! let value: Vec<_> = hifijson::serde::exactly_one(&mut lexer).unwrap();
 //! assert_eq!(vec![0, 1], value);
 //! ~~~
+//!
+//! Enums can be deserialised if they are
+//! externally tagged (the default, such as `"A"` or `{"B": 1}`),
+//! internally tagged (`#[serde(tag = "type")]`),
+//! adjacently tagged (`#[serde(tag = "type", content = "data")]`), or
+//! untagged (`#[serde(untagged)]`).
+//! For adjacently tagged enums, the tag and the content may come in any order:
+//!
+//! ~~~
+//! #[derive(Debug, PartialEq, serde::Deserialize)]
+//! #[serde(tag = "type", content = "data")]
+//! enum Message {
+//!     Ping,
+//!     Text(String),
+//! }
+//!
+//! let input = br#"{"data": "hi", "type": "Text"}"#;
+//! let mut lexer = hifijson::SliceLexer::new(input);
+//! let msg: Message = hifijson::serde::exactly_one(&mut lexer).unwrap();
+//! assert_eq!(msg, Message::Text("hi".into()));
+//! ~~~
 
 use crate::{Expect, Lex, LexAlloc, Token};
 
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
 use alloc::string::{String, ToString};
 use core::fmt;
 use serde::de::{self, DeserializeSeed, Visitor};
@@ -25,6 +48,8 @@ pub enum Error {
     Custom(String),
     /// `2e1000` (we were not able to fit a number into its type)
     Number(String),
+    /// `{"a": 1, "a": 2}` (only when denying duplicate keys)
+    DuplicateKey(String),
 }
 
 impl fmt::Display for Error {
@@ -34,6 +59,7 @@ impl fmt::Display for Error {
             Parse(e) => e.fmt(f),
             Custom(e) => e.fmt(f),
             Number(n) => write!(f, "number overflow: {}", n),
+            DuplicateKey(k) => write!(f, "duplicate key: {}", k),
         }
     }
 }
@@ -51,9 +77,35 @@ impl de::Error for Error {
     }
 }
 
+/// Options that influence deserialisation.
+#[derive(Copy, Clone, Debug)]
+struct Options {
+    /// maximal number of nested arrays / objects that may still be entered
+    depth: usize,
+    deny_duplicate_keys: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            depth: usize::MAX,
+            deny_duplicate_keys: false,
+        }
+    }
+}
+
+impl Options {
+    /// Return the options for the contents of an array / object.
+    fn nested(self) -> Result<Self> {
+        let depth = self.depth.checked_sub(1).ok_or(crate::Error::Depth)?;
+        Ok(Self { depth, ..self })
+    }
+}
+
 struct TokenLexer<L> {
     token: Token,
     lexer: L,
+    opts: Options,
 }
 
 fn parse_number<T: core::str::FromStr>(n: &str) -> Result<T> {
@@ -69,7 +121,10 @@ macro_rules! deserialize_number {
     };
 }
 
-impl<'de, 'a, L: LexAlloc + 'de> de::Deserializer<'de> for TokenLexer<&'a mut L> {
+impl<'de, L: LexAlloc + 'de> de::Deserializer<'de> for TokenLexer<&mut L>
+where
+    L::Str: Into<Cow<'de, str>>,
+{
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -81,29 +136,132 @@ impl<'de, 'a, L: LexAlloc + 'de> de::Deserializer<'de> for TokenLexer<&'a mut L>
             Token::Null => visitor.visit_unit(),
             Token::True => visitor.visit_bool(true),
             Token::False => visitor.visit_bool(false),
-            Token::Quote => visitor.visit_str(&self.lexer.str_string().map_err(Str)?),
+            Token::Quote => match self.lexer.str_string().map_err(Str)?.into() {
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
             Token::DigitOrMinus => {
                 let (n, parts) = self.lexer.num_string().map_err(Num)?;
-                if parts.is_int() {
-                    if n.starts_with('-') {
-                        visitor.visit_i64(parse_number(&n)?)
-                    } else {
-                        visitor.visit_u64(parse_number(&n)?)
-                    }
-                } else {
+                if !parts.is_int() {
                     visitor.visit_f64(parse_number(&n)?)
+                } else if parts.neg && parts.fits_i64(&n) {
+                    visitor.visit_i64(parse_number(&n)?)
+                } else if parts.fits_u64(&n) {
+                    visitor.visit_u64(parse_number(&n)?)
+                } else {
+                    Err(Error::Number(n.to_string()))
                 }
             }
-            Token::LSquare => visitor.visit_seq(CommaSeparated::new(self.lexer)),
-            Token::LCurly => visitor.visit_map(CommaSeparated::new(self.lexer)),
+            Token::LSquare => {
+                let mut seq = CommaSeparated::new(self.lexer, self.opts.nested()?);
+                let v = visitor.visit_seq(&mut seq)?;
+                seq.end(Token::RSquare)?;
+                Ok(v)
+            }
+            Token::LCurly => {
+                let mut map = CommaSeparated::new(self.lexer, self.opts.nested()?);
+                let v = visitor.visit_map(&mut map)?;
+                map.end(Token::RCurly)?;
+                Ok(v)
+            }
             _ => Err(Expect::Value)?,
         }
     }
 
+    /// Deserialise a string to the bytes of its UTF-8 encoding.
+    ///
+    /// Escape sequences in the string are decoded.
+    /// Any other value is deserialised like by `deserialize_any`.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.token != Token::Quote {
+            return self.deserialize_any(visitor);
+        }
+        match self.lexer.str_string().map_err(crate::Error::Str)?.into() {
+            Cow::Borrowed(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            Cow::Owned(s) => visitor.visit_byte_buf(s.into_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// Deserialise an externally tagged enum.
+    ///
+    /// Unit variants are read from strings, such as `"A"`, and
+    /// other variants are read from objects with a single key, such as `{"B": 1}`.
+    /// Any other value is deserialised like by `deserialize_any`.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        use de::value::{BorrowedStrDeserializer, StringDeserializer};
+        match self.token {
+            Token::Quote => match self.lexer.str_string().map_err(crate::Error::Str)?.into() {
+                Cow::Borrowed(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+                Cow::Owned(s) => visitor.visit_enum(StringDeserializer::new(s)),
+            },
+            Token::LCurly => {
+                let opts = self.opts.nested()?;
+                let lexer = &mut *self.lexer;
+                let v = visitor.visit_enum(Enum { lexer, opts })?;
+                let end = self.lexer.ws_token().filter(|t| *t == Token::RCurly);
+                end.ok_or(Expect::CommaOrEnd)?;
+                Ok(v)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Deserialise `null` to `None` and any other value `v` to `Some(v)`.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.token == Token::Null {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    /// Deserialise a unit struct from `null`.
+    ///
+    /// Any other value is deserialised like by `deserialize_any`.
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.token == Token::Null {
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    /// Deserialise a newtype struct from the value that it wraps.
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
     serde::forward_to_deserialize_any! {
         bool char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        unit seq tuple
+        tuple_struct map struct identifier ignored_any
     }
 
     deserialize_number!(deserialize_u8, visit_u8);
@@ -125,11 +283,22 @@ impl<'de, 'a, L: LexAlloc + 'de> de::Deserializer<'de> for TokenLexer<&'a mut L>
 struct CommaSeparated<'a, L> {
     lexer: &'a mut L,
     first: bool,
+    /// have we read the end of the sequence?
+    done: bool,
+    opts: Options,
+    /// keys encountered so far (only when denying duplicate keys)
+    keys: BTreeSet<String>,
 }
 
 impl<'a, L> CommaSeparated<'a, L> {
-    fn new(lexer: &'a mut L) -> Self {
-        CommaSeparated { lexer, first: true }
+    fn new(lexer: &'a mut L, opts: Options) -> Self {
+        CommaSeparated {
+            lexer,
+            first: true,
+            done: false,
+            opts,
+            keys: BTreeSet::new(),
+        }
     }
 }
 
@@ -145,9 +314,23 @@ impl<'a, L: Lex> CommaSeparated<'a, L> {
         }
         Ok(())
     }
+
+    /// Ensure that the sequence has been read up to `end`.
+    ///
+    /// This is necessary when the visitor does not read all elements,
+    /// such as when deserialising tuples.
+    fn end(&mut self, end: Token) -> Result<()> {
+        if !self.done && self.lexer.ws_token() != Some(end) {
+            Err(Expect::CommaOrEnd)?
+        }
+        Ok(())
+    }
 }
 
-impl<'de, 'a, L: LexAlloc + 'de> de::SeqAccess<'de> for CommaSeparated<'a, L> {
+impl<'de, 'a, L: LexAlloc + 'de> de::SeqAccess<'de> for CommaSeparated<'a, L>
+where
+    L::Str: Into<Cow<'de, str>>,
+{
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -157,16 +340,21 @@ impl<'de, 'a, L: LexAlloc + 'de> de::SeqAccess<'de> for CommaSeparated<'a, L> {
         let token = self.lexer.ws_token();
         let mut token = token.ok_or(Expect::ValueOrEnd)?;
         if token == Token::RSquare {
+            self.done = true;
             return Ok(None);
         };
         self.comma(&mut token)?;
 
-        let lexer = &mut *self.lexer;
-        seed.deserialize(TokenLexer { token, lexer }).map(Some)
+        let (lexer, opts) = (&mut *self.lexer, self.opts);
+        seed.deserialize(TokenLexer { token, lexer, opts })
+            .map(Some)
     }
 }
 
-impl<'de, 'a, L: LexAlloc + 'de> de::MapAccess<'de> for CommaSeparated<'a, L> {
+impl<'de, 'a, L: LexAlloc + 'de> de::MapAccess<'de> for CommaSeparated<'a, L>
+where
+    L::Str: Into<Cow<'de, str>>,
+{
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
@@ -176,6 +364,7 @@ impl<'de, 'a, L: LexAlloc + 'de> de::MapAccess<'de> for CommaSeparated<'a, L> {
         let token = self.lexer.ws_token();
         let mut token = token.ok_or(Expect::ValueOrEnd)?;
         if token == Token::RCurly {
+            self.done = true;
             return Ok(None);
         };
         self.comma(&mut token)?;
@@ -184,8 +373,11 @@ impl<'de, 'a, L: LexAlloc + 'de> de::MapAccess<'de> for CommaSeparated<'a, L> {
             Err(Expect::String)?
         }
 
-        let lexer = &mut *self.lexer;
-        seed.deserialize(TokenLexer { token, lexer }).map(Some)
+        let key = self.lexer.str_string().map_err(crate::Error::Str)?.into();
+        if self.opts.deny_duplicate_keys && !self.keys.insert(key.to_string()) {
+            return Err(Error::DuplicateKey(key.into_owned()));
+        }
+        seed.deserialize(MapKey { key }).map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
@@ -197,11 +389,263 @@ impl<'de, 'a, L: LexAlloc + 'de> de::MapAccess<'de> for CommaSeparated<'a, L> {
         colon.ok_or(Expect::Colon)?;
 
         let token = lexer.ws_token().ok_or(Expect::Value)?;
-        seed.deserialize(TokenLexer { token, lexer })
+        let opts = self.opts;
+        seed.deserialize(TokenLexer { token, lexer, opts })
+    }
+}
+
+/// Object key, such as `"a"` in `{"a": 1}`.
+///
+/// Keys are always strings, but types such as `HashMap<u32, T>` expect numeric keys.
+/// For this, a key is deserialised to a number by parsing the contents of the string.
+struct MapKey<'de> {
+    key: Cow<'de, str>,
+}
+
+macro_rules! deserialize_key_number {
+    ($deserialize:ident, $visit:ident) => {
+        fn $deserialize<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            visitor.$visit(parse_number(&self.key)?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKey<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.key {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
+    }
+
+    /// Deserialise a unit variant of an enum, such as `"A"`.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        use de::value::{BorrowedStrDeserializer, StringDeserializer};
+        match self.key {
+            Cow::Borrowed(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            Cow::Owned(s) => visitor.visit_enum(StringDeserializer::new(s)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf option
+        unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+
+    deserialize_key_number!(deserialize_u8, visit_u8);
+    deserialize_key_number!(deserialize_u16, visit_u16);
+    deserialize_key_number!(deserialize_u32, visit_u32);
+    deserialize_key_number!(deserialize_u64, visit_u64);
+    deserialize_key_number!(deserialize_u128, visit_u128);
+
+    deserialize_key_number!(deserialize_i8, visit_i8);
+    deserialize_key_number!(deserialize_i16, visit_i16);
+    deserialize_key_number!(deserialize_i32, visit_i32);
+    deserialize_key_number!(deserialize_i64, visit_i64);
+    deserialize_key_number!(deserialize_i128, visit_i128);
+
+    deserialize_key_number!(deserialize_f32, visit_f32);
+    deserialize_key_number!(deserialize_f64, visit_f64);
+}
+
+/// Variant of an externally tagged enum, such as `"B": 1` in `{"B": 1}`.
+struct Enum<'a, L> {
+    lexer: &'a mut L,
+    opts: Options,
+}
+
+impl<'a, L: LexAlloc> Enum<'a, L> {
+    /// Return a deserializer for the value that follows.
+    fn value(self) -> Result<TokenLexer<&'a mut L>> {
+        let token = self.lexer.ws_token().ok_or(Expect::Value)?;
+        let (lexer, opts) = (self.lexer, self.opts);
+        Ok(TokenLexer { token, lexer, opts })
+    }
+}
+
+impl<'de, 'a, L: LexAlloc + 'de> de::EnumAccess<'de> for Enum<'a, L>
+where
+    L::Str: Into<Cow<'de, str>>,
+{
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let token = self.lexer.ws_token().ok_or(Expect::String)?;
+        token.equals_or(Token::Quote, Expect::String)?;
+        let (lexer, opts) = (&mut *self.lexer, self.opts);
+        let variant = seed.deserialize(TokenLexer { token, lexer, opts })?;
+
+        let colon = self.lexer.ws_token().filter(|t| *t == Token::Colon);
+        colon.ok_or(Expect::Colon)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, L: LexAlloc + 'de> de::VariantAccess<'de> for Enum<'a, L>
+where
+    L::Str: Into<Cow<'de, str>>,
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        <()>::deserialize(self.value()?)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
     }
 }
 
 /// Deserialise a single value.
-pub fn exactly_one<'a, T: Deserialize<'a>, L: LexAlloc + 'a>(lexer: &mut L) -> Result<T> {
-    lexer.exactly_one(|token, lexer| T::deserialize(TokenLexer { token, lexer }))
+///
+/// Strings without escape sequences are borrowed from the input if
+/// the lexer borrows them, as [`SliceLexer`](crate::SliceLexer) does.
+/// That allows deserialising to types such as `&str`.
+pub fn exactly_one<'a, T: Deserialize<'a>, L: LexAlloc + 'a>(lexer: &mut L) -> Result<T>
+where
+    L::Str: Into<Cow<'a, str>>,
+{
+    Deserializer::builder().build(lexer).exactly_one()
+}
+
+/// Builder for a [`Deserializer`] with custom options.
+///
+/// ~~~
+/// use hifijson::serde::{Deserializer, Error};
+/// let mut lexer = hifijson::SliceLexer::new(br#"{"a": [[1]], "b": 2}"#);
+/// let de = Deserializer::builder().max_depth(2).build(&mut lexer);
+/// let err = de.exactly_one::<serde::de::IgnoredAny>().unwrap_err();
+/// assert!(matches!(err, Error::Parse(hifijson::Error::Depth)));
+/// ~~~
+#[derive(Clone, Debug, Default)]
+pub struct Builder(Options);
+
+impl Builder {
+    /// Fail if arrays and objects are nested more than `depth` times.
+    ///
+    /// For example, a depth of 0 permits only `null`, booleans, numbers, and strings,
+    /// and a depth of 1 additionally permits arrays and objects that contain no arrays or objects.
+    /// By default, depth is unbounded.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.0.depth = depth;
+        self
+    }
+
+    /// Fail with [`Error::DuplicateKey`] if an object contains the same key twice.
+    ///
+    /// By default, duplicate keys are passed on to the deserialised type.
+    pub fn deny_duplicate_keys(mut self, deny: bool) -> Self {
+        self.0.deny_duplicate_keys = deny;
+        self
+    }
+
+    /// Create a deserializer with the configured options that reads from `lexer`.
+    pub fn build<L>(self, lexer: &mut L) -> Deserializer<'_, L> {
+        Deserializer {
+            lexer,
+            opts: self.0,
+        }
+    }
+}
+
+/// Deserializer with custom options, created by a [`Builder`].
+pub struct Deserializer<'a, L> {
+    lexer: &'a mut L,
+    opts: Options,
+}
+
+impl Deserializer<'_, ()> {
+    /// Start building a deserializer with default options.
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+}
+
+impl<'a, L: LexAlloc> Deserializer<'a, L> {
+    /// Deserialise a single value and fail if any further input follows.
+    ///
+    /// See [`exactly_one`](fn@exactly_one) for when strings are borrowed.
+    pub fn exactly_one<'de, T: Deserialize<'de>>(self) -> Result<T>
+    where
+        L: 'de,
+        L::Str: Into<Cow<'de, str>>,
+    {
+        let opts = self.opts;
+        let f = |token, lexer: &mut L| T::deserialize(TokenLexer { token, lexer, opts });
+        self.lexer.exactly_one(f)
+    }
+
+    /// Deserialise a single value, leaving any further input untouched.
+    pub fn one<'de, T: Deserialize<'de>>(&mut self) -> Result<T>
+    where
+        L: 'de,
+        L::Str: Into<Cow<'de, str>>,
+    {
+        let token = self.lexer.ws_token().ok_or(Expect::Value)?;
+        let (lexer, opts) = (&mut *self.lexer, self.opts);
+        T::deserialize(TokenLexer { token, lexer, opts })
+    }
+
+    /// Deserialise a sequence of values until the end of the input.
+    ///
+    /// The iterator yields no more values after the first error.
+    pub fn iter<'de, T: Deserialize<'de>>(self) -> impl Iterator<Item = Result<T>> + 'a
+    where
+        L: 'de,
+        L::Str: Into<Cow<'de, str>>,
+    {
+        let mut failed = false;
+        core::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let token = self.lexer.ws_token()?;
+            let (lexer, opts) = (&mut *self.lexer, self.opts);
+            let v = T::deserialize(TokenLexer { token, lexer, opts });
+            failed = v.is_err();
+            Some(v)
+        })
+    }
 }
@@ -31,23 +31,127 @@ use crate::{Read, Write};
 use core::fmt;
 use core::ops::Deref;
 
+/// Write a string as JSON string (including surrounding quotes) to `out`.
+///
+/// This escapes `"` and `\` as well as control characters.
+/// The control characters `\n`, `\r`, `\t`, `\b`, and `\f` are escaped by their short forms,
+/// all other control characters (up to `0x1F`) are escaped as `\u00XX`.
+///
+/// ~~~
+/// let mut out = String::new();
+/// hifijson::str::encode("a\"\n\u{1f}", &mut out).unwrap();
+/// assert_eq!(out, r#""a\"\n\u001f""#);
+/// ~~~
+pub fn encode(s: &str, out: &mut impl fmt::Write) -> fmt::Result {
+    encode_with(s, |piece| out.write_str(piece))
+}
+
+/// Write a string as JSON string (including surrounding quotes) to `out`.
+///
+/// This escapes the same characters as [`encode`].
+#[cfg(feature = "std")]
+pub fn encode_to(s: &str, out: &mut impl std::io::Write) -> std::io::Result<()> {
+    encode_with(s, |piece| out.write_all(piece.as_bytes()))
+}
+
+/// Return true if `c` ends a run of literal characters in a string,
+/// namely if `c` is `"`, `\\`, or a control character.
+///
+/// This is the predicate that is used to scan strings.
+/// The `write_until` function of lexers that implement [`LexWrite`]
+/// accepts arbitrary predicates, so this can be combined with other conditions,
+/// for example to stop reading at a byte that delimits strings in a protocol:
+///
+/// ~~~
+/// use hifijson::{str, LexWrite, SliceLexer};
+/// fn until_sentinel<L: LexWrite>(lexer: &mut L) -> L::Bytes {
+///     let mut bytes = Default::default();
+///     lexer.write_until(&mut bytes, |c| str::string_end(c) || c == b'|');
+///     bytes
+/// }
+/// let mut lexer = SliceLexer::new(b"abc|def\"");
+/// assert_eq!(until_sentinel(&mut lexer), b"abc");
+/// assert_eq!(lexer.as_slice(), b"|def\"");
+/// ~~~
+pub fn string_end(c: u8) -> bool {
+    matches!(c, b'"' | b'\\' | 0..=0x1F)
+}
+
+/// Write a string as JSON string by calling `write` on consecutive pieces.
+fn encode_with<E>(s: &str, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    write("\"")?;
+    let mut rest = s;
+    while let Some(pos) = rest.bytes().position(string_end) {
+        write(&rest[..pos])?;
+        let c = rest.as_bytes()[pos];
+        let unicode;
+        write(match c {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0C => "\\f",
+            _ => {
+                unicode = [
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX[usize::from(c >> 4)],
+                    HEX[usize::from(c & 0xF)],
+                ];
+                core::str::from_utf8(&unicode).unwrap()
+            }
+        })?;
+        rest = &rest[pos + 1..];
+    }
+    write(rest)?;
+    write("\"")
+}
+
 /// Wrapper type to facilitate printing strings as JSON.
-pub struct Display<Str>(Str);
+pub struct Display<Str> {
+    s: Str,
+    /// escape all non-ASCII characters?
+    ascii: bool,
+}
 
 impl<Str> Display<Str> {
     /// Create a new string to be printed as JSON string.
     pub fn new(s: Str) -> Self {
-        Self(s)
+        Self { s, ascii: false }
+    }
+
+    /// Create a new string to be printed as JSON string that contains only ASCII characters.
+    ///
+    /// Every non-ASCII character is escaped as `\uXXXX`,
+    /// using a surrogate pair for characters outside the Basic Multilingual Plane.
+    ///
+    /// ~~~
+    /// use hifijson::str::Display;
+    /// assert_eq!(Display::ascii("é😀").to_string(), r#""\u00e9\ud83d\ude00""#);
+    /// assert_eq!(Display::new("é😀").to_string(), r#""é😀""#);
+    /// ~~~
+    pub fn ascii(s: Str) -> Self {
+        Self { s, ascii: true }
     }
 }
 
 impl<Str: Deref<Target = str>> fmt::Display for Display<Str> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         '"'.fmt(f)?;
-        for c in self.0.chars() {
+        for c in self.s.chars() {
             match c {
                 '\\' | '"' | '\n' | '\r' | '\t' => c.escape_default().try_for_each(|c| c.fmt(f)),
-                c if (c as u32) < 20 => write!(f, "\\u{:04x}", c as u16),
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u16),
+                c if self.ascii && !c.is_ascii() => {
+                    let mut buf = [0; 2];
+                    let units = c.encode_utf16(&mut buf);
+                    units.iter().try_for_each(|u| write!(f, "\\u{:04x}", u))
+                }
                 c => c.fmt(f),
             }?
         }
@@ -66,6 +170,8 @@ pub enum Error {
     Eof,
     /// string is not in UTF-8
     Utf8(core::str::Utf8Error),
+    /// string is longer than permitted
+    TooLong,
 }
 
 impl Error {
@@ -94,19 +200,96 @@ impl core::fmt::Display for Error {
             Escape(e) => e.fmt(f),
             Eof => "unterminated string".fmt(f),
             Utf8(e) => e.fmt(f),
+            TooLong => "string too long".fmt(f),
         }
     }
 }
 
 /// String lexing state machine.
-#[derive(Default)]
 struct State {
+    /// character that terminates the string
+    quote: u8,
     /// Are we in an escape sequence, and if so,
     /// are we in a unicode escape sequence, and if so,
     /// at which position in the hex code are we?
     escape: Option<Option<u8>>,
     /// Did we encounter an error so far?
     error: Option<Error>,
+    /// Do we accept unescaped control characters?
+    lenient: bool,
+    /// Do we check that surrogates in Unicode escape sequences are paired?
+    checked: bool,
+    /// value of the current Unicode escape sequence read so far
+    hex: u16,
+    /// Did the last escape sequence yield a high surrogate (only when `checked`)?
+    high: bool,
+    /// If present, validate UTF-8 while reading.
+    utf8: Option<Utf8>,
+}
+
+/// Incremental UTF-8 validator.
+#[derive(Default)]
+struct Utf8 {
+    /// bytes of the current character read so far
+    buf: [u8; 4],
+    /// number of bytes in `buf`
+    len: usize,
+    /// number of bytes that are still missing for the current character
+    need: usize,
+    /// minimal and maximal value of the next byte (only when `need > 0`)
+    range: (u8, u8),
+}
+
+impl Utf8 {
+    /// Process the next byte, failing if it cannot occur in valid UTF-8 at this position.
+    fn process(&mut self, c: u8) -> Result<(), core::str::Utf8Error> {
+        // obtain the error that is returned for the invalid UTF-8 sequence `bytes`
+        let invalid = |bytes: &[u8]| core::str::from_utf8(bytes).unwrap_err();
+        if self.need == 0 {
+            // see the table "Well-Formed UTF-8 Byte Sequences" of the Unicode standard
+            let (need, range) = match c {
+                0x00..=0x7F => return Ok(()),
+                0xC2..=0xDF => (1, (0x80, 0xBF)),
+                0xE0 => (2, (0xA0, 0xBF)),
+                0xE1..=0xEC | 0xEE..=0xEF => (2, (0x80, 0xBF)),
+                0xED => (2, (0x80, 0x9F)),
+                0xF0 => (3, (0x90, 0xBF)),
+                0xF1..=0xF3 => (3, (0x80, 0xBF)),
+                0xF4 => (3, (0x80, 0x8F)),
+                _ => return Err(invalid(&[c])),
+            };
+            *self = Self {
+                buf: [c, 0, 0, 0],
+                len: 1,
+                need,
+                range,
+            };
+        } else {
+            self.buf[self.len] = c;
+            self.len += 1;
+            if c < self.range.0 || c > self.range.1 {
+                return Err(invalid(&self.buf[..self.len]));
+            }
+            self.need -= 1;
+            self.range = (0x80, 0xBF);
+        }
+        Ok(())
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            quote: b'"',
+            escape: None,
+            error: None,
+            lenient: false,
+            checked: false,
+            hex: 0,
+            high: false,
+            utf8: None,
+        }
+    }
 }
 
 impl State {
@@ -117,40 +300,84 @@ impl State {
         if let Some(unicode) = &mut self.escape {
             // are we in a Unicode escape sequence (started by "\u")?
             if let Some(hex_pos) = unicode {
-                if escape::decode_hex(c).is_none() {
-                    self.error = Some(escape::Error::InvalidHex.into());
-                } else if *hex_pos < 3 {
-                    *hex_pos += 1
-                } else {
-                    self.escape = None;
+                match escape::decode_hex(c) {
+                    None => self.error = Some(escape::Error::InvalidHex.into()),
+                    Some(h) => {
+                        self.hex = (self.hex << 4) | u16::from(h);
+                        if *hex_pos < 3 {
+                            *hex_pos += 1
+                        } else {
+                            self.escape = None;
+                            if self.checked {
+                                self.surrogate()
+                            }
+                        }
+                    }
                 }
             } else {
                 // we are in a non-Unicode escape sequence,
                 // let us see which kind of sequence ...
                 match Escape::try_from(c) {
-                    Some(Escape::Unicode(_)) => *unicode = Some(0),
+                    Some(Escape::Unicode(_)) => {
+                        *unicode = Some(0);
+                        self.hex = 0
+                    }
+                    Some(_) if self.high => {
+                        self.error = Some(escape::Error::ExpectedLowSurrogate.into())
+                    }
                     Some(_) => self.escape = None,
                     None => self.error = Some(escape::Error::UnknownKind.into()),
                 }
             }
+        } else if let Some(Err(e)) = self.utf8.as_mut().map(|u| u.process(c)) {
+            self.error = Some(Error::Utf8(e))
+        } else if self.high && c != b'\\' {
+            // a high surrogate has to be followed by an escape sequence
+            self.error = Some(escape::Error::ExpectedLowSurrogate.into())
         } else {
             // we are not in any escape sequence
             match c {
-                b'"' => return true,
+                c if c == self.quote => return true,
                 b'\\' => self.escape = Some(None),
-                0..=0x1F => self.error = Some(Error::Control),
+                0..=0x1F if !self.lenient => self.error = Some(Error::Control),
                 _ => return false,
             };
         }
         self.error.is_some()
     }
 
+    /// Check whether the Unicode escape sequence that was just read
+    /// is a high surrogate followed by a low surrogate or not a surrogate at all.
+    fn surrogate(&mut self) {
+        match (self.high, self.hex) {
+            (true, 0xDC00..=0xDFFF) => self.high = false,
+            (true, _) => self.error = Some(escape::Error::ExpectedLowSurrogate.into()),
+            (false, 0xD800..=0xDBFF) => self.high = true,
+            (false, low @ 0xDC00..=0xDFFF) => {
+                self.error = Some(escape::Error::InvalidChar(low.into()).into())
+            }
+            (false, _) => (),
+        }
+    }
+
+    /// Like `process`, but fail once more than `max` bytes have been processed,
+    /// where `len` holds the number of bytes processed so far.
+    fn process_bounded(&mut self, c: u8, len: &mut usize, max: usize) -> bool {
+        self.process(c) || {
+            *len += 1;
+            if *len > max {
+                self.error = Some(Error::TooLong)
+            }
+            self.error.is_some()
+        }
+    }
+
     /// Ensure that once `process` has returned `true`, the string has actually terminated.
     fn finish(self, mut next: impl FnMut() -> Option<u8>) -> Result<(), Error> {
         match self.error {
             Some(e) => Err(e),
             None if self.escape.is_some() => Err(escape::Error::Eof)?,
-            None if next() != Some(b'"') => Err(Error::Eof),
+            None if next() != Some(self.quote) => Err(Error::Eof),
             None => Ok(()),
         }
     }
@@ -163,12 +390,127 @@ pub trait Lex: escape::Lex {
         self.str_foreach(|_| ())
     }
 
+    /// Read a string without saving it,
+    /// failing with [`Error::TooLong`] once more than `max` bytes have been read.
+    ///
+    /// The length of a string is measured in input bytes,
+    /// counting escape sequences such as `\u00e4` by their length in the input.
+    fn str_ignore_bounded(&mut self, max: usize) -> Result<(), Error> {
+        let mut state = State::default();
+        let mut len = 0;
+        self.skip_until(|c| state.process_bounded(c, &mut len, max));
+        state.finish(|| self.take_next())
+    }
+
     /// Run a function for every character of the string.
     fn str_foreach(&mut self, f: impl FnMut(u8)) -> Result<(), Error> {
-        let mut state = State::default();
+        self.str_foreach_quote(b'"', f)
+    }
+
+    /// Like [`Lex::str_foreach`], but for a string that is terminated by `quote`.
+    ///
+    /// For example, this can be used to read strings enclosed in single quotes.
+    /// Escape sequences are treated like in strings enclosed in double quotes,
+    /// so double quotes need not be escaped, whereas
+    /// `quote` has to be escaped with `\u`.
+    ///
+    /// ~~~
+    /// use hifijson::str::Lex;
+    /// let mut lexer = hifijson::SliceLexer::new(br#"a"\u0027b', 1"#);
+    /// let mut bytes = Vec::new();
+    /// lexer.str_foreach_quote(b'\'', |c| bytes.push(c)).unwrap();
+    /// assert_eq!(bytes, br#"a"\u0027b"#);
+    /// assert_eq!(lexer.as_slice(), b", 1");
+    /// ~~~
+    fn str_foreach_quote(&mut self, quote: u8, f: impl FnMut(u8)) -> Result<(), Error> {
+        let mut state = State {
+            quote,
+            ..State::default()
+        };
         self.foreach_until(f, |c| state.process(c));
         state.finish(|| self.take_next())
     }
+
+    /// Like [`Lex::str_foreach`], but accept unescaped control characters
+    /// (`0x00..=0x1F`) in the string and pass them verbatim to `f`.
+    ///
+    /// This is useful to read output from producers that fail to escape
+    /// characters such as tabs or newlines.
+    /// Unterminated strings and invalid escape sequences are still rejected.
+    fn str_foreach_lenient(&mut self, f: impl FnMut(u8)) -> Result<(), Error> {
+        let mut state = State {
+            lenient: true,
+            ..State::default()
+        };
+        self.foreach_until(f, |c| state.process(c));
+        state.finish(|| self.take_next())
+    }
+
+    /// Run a function on consecutive chunks of the string contents, interpreting escape sequences.
+    ///
+    /// Every chunk is either a run of literal bytes or
+    /// the UTF-8 encoding of a character obtained from an escape sequence.
+    /// Long runs of literal bytes are split into several chunks,
+    /// so that the whole string is never held in memory.
+    /// Note that the bytes of a literal chunk are not validated to be UTF-8.
+    fn str_chunks<E: From<Error>>(
+        &mut self,
+        mut f: impl FnMut(&[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut buf = [0; 1024];
+        let mut len = 0;
+        let mut err = None;
+        loop {
+            self.skip_until(|c| {
+                string_end(c) || {
+                    buf[len] = c;
+                    len += 1;
+                    len == buf.len() && {
+                        len = 0;
+                        err = f(&buf).err();
+                        err.is_some()
+                    }
+                }
+            });
+            if let Some(e) = err {
+                return Err(e);
+            }
+            if len > 0 {
+                f(&buf[..len])?;
+                len = 0;
+            }
+            match self.take_next().ok_or(Error::Eof)? {
+                b'\\' => (),
+                b'"' => return Ok(()),
+                0..=0x1F => return Err(Error::Control)?,
+                _ => unreachable!(),
+            }
+            let escape = self.escape().map_err(Error::Escape)?;
+            let c = self.escape_char(escape).map_err(Error::Escape)?;
+            f(c.encode_utf8(&mut [0; 4]).as_bytes())?;
+        }
+    }
+
+    /// Read a string and return whether it equals `expected`, interpreting escape sequences.
+    ///
+    /// This does not allocate memory.
+    /// The whole string is consumed, even if it differs from `expected` early.
+    ///
+    /// ~~~
+    /// use hifijson::str::Lex;
+    /// let mut lexer = hifijson::SliceLexer::new(br#"foo" "bar" "#);
+    /// assert_eq!(lexer.str_eq("foo"), Ok(true));
+    /// assert_eq!(lexer.as_slice(), br#" "bar" "#);
+    /// ~~~
+    fn str_eq(&mut self, expected: &str) -> Result<bool, Error> {
+        // remaining part of `expected`, or `None` if the string differs
+        let mut rest = Some(expected.as_bytes());
+        self.str_chunks(|chunk| {
+            rest = rest.and_then(|rest| rest.strip_prefix(chunk));
+            Ok::<_, Error>(())
+        })?;
+        Ok(rest.map_or(false, |rest| rest.is_empty()))
+    }
 }
 
 impl<T> Lex for T where T: escape::Lex {}
@@ -182,43 +524,168 @@ pub trait LexWrite: escape::Lex + Read + Write {
         state.finish(|| self.take_next())
     }
 
+    /// Like [`LexWrite::str_bytes`], but fail if the string contains
+    /// a high surrogate that is not followed by a low surrogate or
+    /// a low surrogate that is not preceded by a high surrogate,
+    /// such as `\uD801` or `\uDC37`.
+    ///
+    /// Like [`LexWrite::str_bytes`], this copies escape sequences one-to-one
+    /// and does not decode them to UTF-8.
+    ///
+    /// ~~~
+    /// use hifijson::{escape, str::{Error, LexWrite}, SliceLexer};
+    /// let mut bytes = Default::default();
+    /// let mut lexer = SliceLexer::new(br#"\uD801\uDC37""#);
+    /// assert_eq!(lexer.str_bytes_checked(&mut bytes), Ok(()));
+    /// assert_eq!(bytes, br#"\uD801\uDC37"#);
+    ///
+    /// let mut lexer = SliceLexer::new(br#"\uD801""#);
+    /// let err = Error::Escape(escape::Error::ExpectedLowSurrogate);
+    /// assert_eq!(lexer.str_bytes_checked(&mut bytes), Err(err));
+    /// ~~~
+    fn str_bytes_checked(&mut self, bytes: &mut Self::Bytes) -> Result<(), Error> {
+        let mut state = State {
+            checked: true,
+            ..State::default()
+        };
+        self.write_until(bytes, |c| state.process(c));
+        state.finish(|| self.take_next())
+    }
+
+    /// Like [`LexWrite::str_bytes`], but fail as soon as
+    /// a byte is read that makes the string invalid UTF-8.
+    ///
+    /// In contrast, [`LexWrite::str_bytes`] does not validate UTF-8 at all, and
+    /// functions such as [`LexAlloc::str_string`] validate UTF-8 only after
+    /// having read a whole string (or a part of it between escape sequences).
+    /// When failing, `bytes` contains the string up to (excluding) the byte that
+    /// made it invalid, and the [`Utf8Error`](core::str::Utf8Error) refers to
+    /// the invalid character only.
+    ///
+    /// ~~~
+    /// use hifijson::str::{Error, LexWrite};
+    /// let mut bytes = Default::default();
+    /// let mut lexer = hifijson::SliceLexer::new(b"a\xc3\xa4\"");
+    /// assert_eq!(lexer.str_bytes_utf8(&mut bytes), Ok(()));
+    /// assert_eq!(bytes, "aä".as_bytes());
+    ///
+    /// let mut lexer = hifijson::SliceLexer::new(b"ab\xc3bc\"");
+    /// assert!(matches!(lexer.str_bytes_utf8(&mut bytes), Err(Error::Utf8(_))));
+    /// assert_eq!(bytes, b"ab\xc3");
+    /// ~~~
+    fn str_bytes_utf8(&mut self, bytes: &mut Self::Bytes) -> Result<(), Error> {
+        let mut state = State {
+            utf8: Some(Utf8::default()),
+            ..State::default()
+        };
+        self.write_until(bytes, |c| state.process(c));
+        state.finish(|| self.take_next())
+    }
+
     /// Lex a string by executing `on_string` on every string and `on_bytes` on every escape sequence.
     fn str_fold<E: From<Error>, T>(
         &mut self,
-        mut out: T,
+        out: T,
         on_string: impl Fn(&mut Self::Bytes, &mut T) -> Result<(), E>,
         on_escape: impl Fn(&mut Self, Escape, &mut T) -> Result<(), E>,
     ) -> Result<T, E> {
-        fn string_end(c: u8) -> bool {
-            matches!(c, b'\\' | b'"' | 0..=0x1F)
+        self.str_fold_bounded(usize::MAX, out, on_string, on_escape)
+    }
+
+    /// Like [`LexWrite::str_fold`], but fail with [`Error::TooLong`]
+    /// once more than `max` bytes of the string have been read.
+    ///
+    /// The length of a string is measured in input bytes,
+    /// counting escape sequences such as `\u00e4` by their length in the input.
+    fn str_fold_bounded<E: From<Error>, T>(
+        &mut self,
+        max: usize,
+        out: T,
+        on_string: impl Fn(&mut Self::Bytes, &mut T) -> Result<(), E>,
+        on_escape: impl Fn(&mut Self, Escape, &mut T) -> Result<(), E>,
+    ) -> Result<T, E> {
+        fold(self, max, false, out, on_string, on_escape)
+    }
+}
+
+/// Implementation of [`LexWrite::str_fold_bounded`],
+/// accepting unescaped control characters if `lenient` is true.
+fn fold<L: LexWrite + ?Sized, E: From<Error>, T>(
+    lexer: &mut L,
+    max: usize,
+    lenient: bool,
+    mut out: T,
+    on_string: impl Fn(&mut L::Bytes, &mut T) -> Result<(), E>,
+    on_escape: impl Fn(&mut L, Escape, &mut T) -> Result<(), E>,
+) -> Result<T, E> {
+    let end = |c: u8| {
+        if lenient {
+            matches!(c, b'\\' | b'"')
+        } else {
+            string_end(c)
         }
+    };
 
-        let mut bytes = Self::Bytes::default();
-        self.write_until(&mut bytes, string_end);
+    let mut len = 0;
+    let mut bytes = L::Bytes::default();
+    loop {
+        if max == usize::MAX && !lenient {
+            lexer.write_until_str_end(&mut bytes)
+        } else {
+            lexer.write_until(&mut bytes, |c| {
+                end(c) || {
+                    len += 1;
+                    len > max
+                }
+            })
+        }
+        if len > max {
+            return Err(Error::TooLong.into());
+        }
         on_string(&mut bytes, &mut out)?;
-        match self.take_next().ok_or(Error::Eof)? {
+        match lexer.take_next().ok_or(Error::Eof)? {
             b'\\' => (),
             b'"' => return Ok(out),
             0..=0x1F => return Err(Error::Control)?,
             _ => unreachable!(),
         }
-        loop {
-            let escape = self.escape().map_err(Error::Escape)?;
-            on_escape(self, escape, &mut out)?;
-            self.write_until(&mut bytes, string_end);
-            on_string(&mut bytes, &mut out)?;
-            match self.take_next().ok_or(Error::Eof)? {
-                b'\\' => continue,
-                b'"' => return Ok(out),
-                0..=0x1F => return Err(Error::Control)?,
-                _ => unreachable!(),
-            }
+
+        let escape = lexer.escape().map_err(Error::Escape)?;
+        len += match escape {
+            // a high surrogate is followed by an escaped low surrogate
+            Escape::Unicode(0xD800..=0xDBFF) => 12,
+            Escape::Unicode(_) => 6,
+            _ => 2,
+        };
+        if len > max {
+            return Err(Error::TooLong.into());
         }
+        on_escape(lexer, escape, &mut out)?;
     }
 }
 
 impl<T> LexWrite for T where T: Read + Write {}
 
+impl<'a> crate::SliceLexer<'a> {
+    /// Lex a string and return the slice spanning its contents, excluding the quotes.
+    ///
+    /// This never copies the string and keeps escape sequences as they are.
+    /// Like [`LexWrite::str_bytes`], this fails if the string is not terminated or
+    /// contains control characters or malformed escape sequences.
+    /// It does not check whether the contents are valid UTF-8.
+    ///
+    /// ~~~
+    /// let mut lexer = hifijson::SliceLexer::new(br#"\u0041\n", 1"#);
+    /// assert_eq!(lexer.str_raw(), Ok(&br#"\u0041\n"#[..]));
+    /// assert_eq!(lexer.as_slice(), b", 1");
+    /// ~~~
+    pub fn str_raw(&mut self) -> Result<&'a [u8], Error> {
+        let mut bytes = Default::default();
+        self.str_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
 /// String lexing that always allocates when lexing from iterators and
 /// allocates when lexing from slices that contain escape sequences.
 pub trait LexAlloc: LexWrite {
@@ -226,29 +693,74 @@ pub trait LexAlloc: LexWrite {
     type Str: Deref<Target = str>;
 
     /// Lex a JSON string to a Rust string.
-    fn str_string(&mut self) -> Result<Self::Str, Error>;
+    fn str_string(&mut self) -> Result<Self::Str, Error> {
+        self.str_string_bounded(usize::MAX)
+    }
+
+    /// Lex a JSON string to a Rust string,
+    /// failing with [`Error::TooLong`] once more than `max` bytes have been read.
+    ///
+    /// See [`LexWrite::str_fold_bounded`] for how the length is measured.
+    fn str_string_bounded(&mut self, max: usize) -> Result<Self::Str, Error>;
+
+    /// Lex a JSON string to a Rust string,
+    /// accepting unescaped control characters (`0x00..=0x1F`) and keeping them verbatim.
+    ///
+    /// Unterminated strings and invalid escape sequences are still rejected.
+    /// See [`Lex::str_foreach_lenient`].
+    ///
+    /// ~~~
+    /// use hifijson::str::LexAlloc;
+    /// let mut lexer = hifijson::SliceLexer::new(b"a\tb\"");
+    /// assert_eq!(lexer.str_string_lenient().unwrap(), "a\tb");
+    /// ~~~
+    fn str_string_lenient(&mut self) -> Result<Self::Str, Error> {
+        self.str_string_lenient_bounded(usize::MAX)
+    }
+
+    /// Like [`LexAlloc::str_string_lenient`], but
+    /// fail with [`Error::TooLong`] once more than `max` bytes have been read.
+    fn str_string_lenient_bounded(&mut self, max: usize) -> Result<Self::Str, Error>;
 }
 
 #[cfg(feature = "alloc")]
 impl<'a> LexAlloc for crate::SliceLexer<'a> {
     type Str = alloc::borrow::Cow<'a, str>;
 
-    fn str_string(&mut self) -> Result<Self::Str, Error> {
+    fn str_string_bounded(&mut self, max: usize) -> Result<Self::Str, Error> {
+        self.str_cow(max, false)
+    }
+
+    fn str_string_lenient_bounded(&mut self, max: usize) -> Result<Self::Str, Error> {
+        self.str_cow(max, true)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> crate::SliceLexer<'a> {
+    fn str_cow(&mut self, max: usize, lenient: bool) -> Result<alloc::borrow::Cow<'a, str>, Error> {
         use alloc::borrow::Cow;
 
-        let on_string = |bytes: &mut Self::Bytes, out: &mut Self::Str| {
+        let on_string = |bytes: &mut &'a [u8], out: &mut Cow<'a, str>| {
             match core::str::from_utf8(bytes).map_err(Error::Utf8)? {
-                s if s.is_empty() => (),
+                "" => (),
                 s if out.is_empty() => *out = Cow::Borrowed(s),
                 s => out.to_mut().push_str(s),
             };
             Ok::<_, Error>(())
         };
         use crate::escape::Lex;
-        self.str_fold(Cow::Borrowed(""), on_string, |lexer, escape, out| {
-            out.to_mut().push(lexer.escape_char(escape)?);
-            Ok(())
-        })
+        fold(
+            self,
+            max,
+            lenient,
+            Cow::Borrowed(""),
+            on_string,
+            |lexer, escape, out| {
+                out.to_mut().push(lexer.escape_char(escape)?);
+                Ok(())
+            },
+        )
     }
 }
 
@@ -256,10 +768,21 @@ impl<'a> LexAlloc for crate::SliceLexer<'a> {
 impl<E, I: Iterator<Item = Result<u8, E>>> LexAlloc for crate::IterLexer<E, I> {
     type Str = alloc::string::String;
 
-    fn str_string(&mut self) -> Result<Self::Str, Error> {
+    fn str_string_bounded(&mut self, max: usize) -> Result<Self::Str, Error> {
+        self.str_owned(max, false)
+    }
+
+    fn str_string_lenient_bounded(&mut self, max: usize) -> Result<Self::Str, Error> {
+        self.str_owned(max, true)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E, I: Iterator<Item = Result<u8, E>>> crate::IterLexer<E, I> {
+    fn str_owned(&mut self, max: usize, lenient: bool) -> Result<alloc::string::String, Error> {
         use alloc::string::String;
 
-        let on_string = |bytes: &mut Self::Bytes, out: &mut Self::Str| {
+        let on_string = |bytes: &mut alloc::vec::Vec<u8>, out: &mut String| {
             if bytes.is_empty() {
                 return Ok(());
             }
@@ -273,9 +796,82 @@ impl<E, I: Iterator<Item = Result<u8, E>>> LexAlloc for crate::IterLexer<E, I> {
             Ok::<_, Error>(())
         };
         use crate::escape::Lex;
-        self.str_fold(Self::Str::new(), on_string, |lexer, escape, out| {
-            out.push(lexer.escape_char(escape)?);
-            Ok(())
-        })
+        fold(
+            self,
+            max,
+            lenient,
+            String::new(),
+            on_string,
+            |lexer, escape, out| {
+                out.push(lexer.escape_char(escape)?);
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E, I: Iterator<Item = Result<u8, E>>> crate::IterLexer<E, I> {
+    /// Lex a JSON string into `scratch` and return it.
+    ///
+    /// The buffer is cleared before lexing, but its memory is kept.
+    /// Reusing the same buffer for many strings therefore saves allocations
+    /// compared to [`LexAlloc::str_string`].
+    /// On error, the buffer is left empty.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, IterLexer};
+    /// let mut lexer = IterLexer::new(br#"["a", "b\nc"]"#.iter().copied().map(Ok::<_, ()>));
+    /// let mut scratch = String::new();
+    /// let mut strings = Vec::new();
+    /// lexer.exactly_one(|token, lexer| {
+    ///     assert_eq!(token, hifijson::Token::LSquare);
+    ///     lexer.seq(hifijson::Token::RSquare, |_, lexer| {
+    ///         strings.push(lexer.str_string_reuse(&mut scratch)?.len());
+    ///         Ok::<_, hifijson::Error>(())
+    ///     })
+    /// }).unwrap();
+    /// assert_eq!(strings, [1, 3]);
+    /// ~~~
+    pub fn str_string_reuse<'s>(
+        &mut self,
+        scratch: &'s mut alloc::string::String,
+    ) -> Result<&'s str, Error> {
+        use alloc::string::String;
+        scratch.clear();
+        let mut bytes = core::mem::take(scratch).into_bytes();
+        let result = match self.str_decode(&mut bytes) {
+            Ok(()) => {
+                String::from_utf8(bytes).map_err(|e| (Error::Utf8(e.utf8_error()), e.into_bytes()))
+            }
+            Err(e) => Err((e, bytes)),
+        };
+        match result {
+            Ok(s) => {
+                *scratch = s;
+                Ok(scratch)
+            }
+            Err((e, mut bytes)) => {
+                bytes.clear();
+                *scratch = String::from_utf8(bytes).unwrap_or_default();
+                Err(e)
+            }
+        }
+    }
+
+    /// Lex a JSON string, appending it to `bytes` with escape sequences decoded.
+    fn str_decode(&mut self, bytes: &mut alloc::vec::Vec<u8>) -> Result<(), Error> {
+        use crate::escape::Lex;
+        loop {
+            self.write_until(bytes, string_end);
+            match self.take_next().ok_or(Error::Eof)? {
+                b'\\' => (),
+                b'"' => return Ok(()),
+                _ => return Err(Error::Control),
+            }
+            let escape = self.escape().map_err(Error::Escape)?;
+            let c = self.escape_char(escape).map_err(Error::Escape)?;
+            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
     }
 }
@@ -5,28 +5,50 @@
 pub enum Expect {
     /// `   ` or `]` or `,`
     Value,
+    /// `   ` (when parsing exactly one value via [`Lex::exactly_one`]),
+    /// meaning that the input is empty or contains only whitespace
+    EmptyInput,
     /// `[` or `{`
     ValueOrEnd,
     /// `[1` or `[1 2`
     CommaOrEnd,
     /// `{0: 1}`
     String,
+    /// `{0: 1}` (when parsing values with functions such as
+    /// [`value::parse_unbounded`](crate::value::parse_unbounded)),
+    /// containing the token found instead of a string
+    StringFound(Token),
     /// `{"a" 1}`
     Colon,
-    /// `true false` (when parsing exactly one value)
+    /// `true false`
+    ///
+    /// This is not produced by this crate anymore;
+    /// [`Lex::exactly_one`] yields [`Expect::TrailingByte`] instead.
     Eof,
+    /// `true false` (when parsing exactly one value via [`Lex::exactly_one`]),
+    /// containing the first byte after the value
+    TrailingByte(u8),
+    /// `true false` (when parsing newline-delimited values)
+    Newline,
+    /// `cb(true` (when parsing JSONP)
+    RParen,
+    /// `/* comment` (when parsing JSON5)
+    CommentEnd,
 }
 
 impl core::fmt::Display for Expect {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         use Expect::*;
         match self {
-            Value => "value".fmt(f),
+            Value | EmptyInput => "value".fmt(f),
             ValueOrEnd => "value or end of sequence".fmt(f),
             CommaOrEnd => "comma or end of sequence".fmt(f),
-            String => "string".fmt(f),
+            String | StringFound(_) => "string".fmt(f),
             Colon => "colon".fmt(f),
-            Eof => "end of file".fmt(f),
+            Eof | TrailingByte(_) => "end of file".fmt(f),
+            Newline => "newline".fmt(f),
+            RParen => "closing parenthesis".fmt(f),
+            CommentEnd => "end of comment".fmt(f),
         }
     }
 }
@@ -80,7 +102,51 @@ impl core::fmt::Display for Token {
     }
 }
 
+/// Kind of a JSON value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool,
+    /// number
+    Number,
+    /// string
+    String,
+    /// array
+    Array,
+    /// object
+    Object,
+}
+
+impl core::fmt::Display for Kind {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use Kind::*;
+        match self {
+            Null => "null".fmt(f),
+            Bool => "boolean".fmt(f),
+            Number => "number".fmt(f),
+            String => "string".fmt(f),
+            Array => "array".fmt(f),
+            Object => "object".fmt(f),
+        }
+    }
+}
+
 impl Token {
+    /// Return the kind of value that starts with this token, if any.
+    pub fn kind(&self) -> Option<Kind> {
+        Some(match self {
+            Token::Null => Kind::Null,
+            Token::True | Token::False => Kind::Bool,
+            Token::DigitOrMinus => Kind::Number,
+            Token::Quote => Kind::String,
+            Token::LSquare => Kind::Array,
+            Token::LCurly => Kind::Object,
+            _ => return None,
+        })
+    }
+
     /// Return `Ok(())` if `self` equals `token`, else return `Err(err)`.
     pub fn equals_or<E>(&self, token: Token, err: E) -> Result<(), E> {
         if *self == token {
@@ -94,8 +160,39 @@ impl Token {
 /// Lexing that does not require allocation.
 pub trait Lex: crate::Read {
     /// Skip input until the earliest non-whitespace character.
+    ///
+    /// When lexing from a slice, this finds the earliest non-whitespace character
+    /// in a single scan and then advances the slice once.
     fn eat_whitespace(&mut self) {
-        self.skip_next_until(|c| !matches!(c, b' ' | b'\t' | b'\r' | b'\n'))
+        self.eat_whitespace_with(|c| matches!(c, b' ' | b'\t' | b'\r' | b'\n'))
+    }
+
+    /// Skip input until the earliest character for which `is_space` yields false.
+    ///
+    /// This allows for treating other characters than
+    /// space, tab, carriage return, and line feed as whitespace.
+    ///
+    /// ~~~
+    /// use hifijson::token::Lex;
+    /// let mut lexer = hifijson::SliceLexer::new(b" \x0c\t1");
+    /// lexer.eat_whitespace_with(|c| matches!(c, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c'));
+    /// assert_eq!(lexer.as_slice(), b"1");
+    /// ~~~
+    fn eat_whitespace_with(&mut self, is_space: impl Fn(u8) -> bool) {
+        self.skip_next_until(|c| !is_space(c))
+    }
+
+    /// Skip whitespace up to the end of the current line.
+    ///
+    /// Fail if anything else than whitespace precedes the next newline.
+    /// The newline itself is not consumed, and
+    /// the end of the input counts as end of line.
+    fn line_end(&mut self) -> Result<(), Expect> {
+        self.skip_next_until(|c| !matches!(c, b' ' | b'\t' | b'\r'));
+        match self.peek_next() {
+            None | Some(b'\n') => Ok(()),
+            Some(_) => Err(Expect::Newline),
+        }
     }
 
     /// Skip potential whitespace and return the following token if there is some.
@@ -104,17 +201,87 @@ pub trait Lex: crate::Read {
         Some(self.token(*self.peek_next()?))
     }
 
+    /// Skip characters for which `is_space` yields true and
+    /// return the following token if there is some.
+    ///
+    /// See [`Lex::eat_whitespace_with`].
+    fn ws_token_with(&mut self, is_space: impl Fn(u8) -> bool) -> Option<Token> {
+        self.eat_whitespace_with(is_space);
+        Some(self.token(*self.peek_next()?))
+    }
+
+    /// Skip potential whitespace and return the kind of the following value if there is some.
+    ///
+    /// In contrast to [`Lex::ws_token`], this does not consume any token,
+    /// and it returns `None` if the following byte cannot start a value.
+    /// Only the first byte of a value is inspected; for example,
+    /// `t` is classified as [`Kind::Bool`] without reading `rue`.
+    /// That way, the value can be parsed afterwards as usual:
+    ///
+    /// ~~~
+    /// use hifijson::token::{Kind, Lex};
+    /// let mut lexer = hifijson::SliceLexer::new(b"  [true]");
+    /// assert_eq!(lexer.peek_token(), Some(Kind::Array));
+    /// assert_eq!(lexer.as_slice(), b"[true]");
+    ///
+    /// let mut lexer = hifijson::SliceLexer::new(b" true");
+    /// assert_eq!(lexer.peek_token(), Some(Kind::Bool));
+    /// assert_eq!(lexer.exactly_one(hifijson::ignore::parse), Ok(()));
+    /// ~~~
+    fn peek_token(&mut self) -> Option<Kind> {
+        self.eat_whitespace();
+        Some(match self.peek_next()? {
+            b'n' => Kind::Null,
+            b't' | b'f' => Kind::Bool,
+            b'0'..=b'9' | b'-' => Kind::Number,
+            b'"' => Kind::String,
+            b'[' => Kind::Array,
+            b'{' => Kind::Object,
+            _ => return None,
+        })
+    }
+
     /// Return `out` if the input matches `s`, otherwise return an error.
     fn exact<const N: usize>(&mut self, s: [u8; N], out: Token) -> Token {
         // we are calling this function without having advanced before
         self.take_next();
-        if self.strip_prefix(s) {
+        if self.strip_prefix(&s) {
             out
         } else {
             Token::Error
         }
     }
 
+    /// Consume the keyword `kw` if the input starts with it, and return whether it did.
+    ///
+    /// This is to be called when the first byte of the keyword has not been consumed yet,
+    /// for example after [`Lex::eat_whitespace`].
+    /// If the first byte of the input differs from the first byte of `kw`,
+    /// nothing is consumed.
+    /// Otherwise, the first byte is consumed, and
+    /// when lexing from an iterator, the following bytes up to the first mismatch as well.
+    /// This allows for recognising keywords beyond `null`, `true`, and `false`:
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, SliceLexer};
+    /// let mut lexer = SliceLexer::new(b" undefined]");
+    /// lexer.eat_whitespace();
+    /// assert!(!lexer.keyword(b"null"));
+    /// assert!(lexer.keyword(b"undefined"));
+    /// assert_eq!(lexer.as_slice(), b"]");
+    /// ~~~
+    fn keyword(&mut self, kw: &[u8]) -> bool {
+        match kw.split_first() {
+            Some((first, rest)) => {
+                self.peek_next() == Some(first) && {
+                    self.take_next();
+                    self.strip_prefix(rest)
+                }
+            }
+            None => true,
+        }
+    }
+
     /// Convert a character to a token, such as '`:`' to `Token::Colon`.
     ///
     /// When the token consists of several characters, such as
@@ -155,40 +322,198 @@ pub trait Lex: crate::Read {
         Ok(key)
     }
 
+    /// Parse a string followed by a colon, returning whether the string equals `key`.
+    ///
+    /// The string is compared without allocating memory,
+    /// interpreting escape sequences such as `\u0061`.
+    /// In any case, the lexer is positioned before the value after the colon.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, Error, SliceLexer, Token};
+    /// let mut lexer = SliceLexer::new(br#""\u0061": 1"#);
+    /// let token = lexer.ws_token().unwrap();
+    /// assert_eq!(lexer.str_colon_eq(token, "a"), Ok(true));
+    /// assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+    /// ~~~
+    fn str_colon_eq(&mut self, token: Token, key: &str) -> Result<bool, crate::Error>
+    where
+        Self: crate::Lex,
+    {
+        self.str_colon(token, |lexer| lexer.str_eq(key).map_err(crate::Error::Str))
+    }
+
     /// Execute `f` for every item in the comma-separated sequence until `end`.
-    fn seq<E: From<Expect>, F>(&mut self, end: Token, mut f: F) -> Result<(), E>
+    fn seq<E: From<Expect>, F>(&mut self, end: Token, f: F) -> Result<(), E>
+    where
+        F: FnMut(Token, &mut Self) -> Result<(), E>,
+    {
+        self.seq_with(end, false, f)
+    }
+
+    /// Execute `f` for every item in the comma-separated sequence until `end`,
+    /// permitting a comma after the last item.
+    ///
+    /// For example, this accepts `[1, 2,]`, but still rejects `[,]`.
+    fn seq_trailing<E: From<Expect>, F>(&mut self, end: Token, f: F) -> Result<(), E>
+    where
+        F: FnMut(Token, &mut Self) -> Result<(), E>,
+    {
+        self.seq_with(end, true, f)
+    }
+
+    /// Execute `f` for every item in the comma-separated sequence until `end`,
+    /// permitting a comma after the last item if `trailing` is true.
+    fn seq_with<E: From<Expect>, F>(&mut self, end: Token, trailing: bool, f: F) -> Result<(), E>
+    where
+        F: FnMut(Token, &mut Self) -> Result<(), E>,
+    {
+        self.seq_by(end, trailing, |lexer| Ok(lexer.ws_token()), f)
+    }
+
+    /// Execute `f` for every item in the comma-separated sequence until `end`,
+    /// permitting a comma after the last item if `trailing` is true and
+    /// obtaining tokens with `token` instead of [`Lex::ws_token`].
+    ///
+    /// This allows for sequences that contain more than whitespace between tokens,
+    /// such as comments in [JSON5](crate::json5).
+    fn seq_by<E: From<Expect>, T, F>(
+        &mut self,
+        end: Token,
+        trailing: bool,
+        mut token: T,
+        mut f: F,
+    ) -> Result<(), E>
     where
+        T: FnMut(&mut Self) -> Result<Option<Token>, E>,
         F: FnMut(Token, &mut Self) -> Result<(), E>,
     {
-        let mut token = self.ws_token().ok_or(Expect::ValueOrEnd)?;
-        if token == end {
+        let mut tok = token(self)?.ok_or(Expect::ValueOrEnd)?;
+        if tok == end {
             return Ok(());
         };
 
         loop {
-            f(token, self)?;
-            token = self.ws_token().ok_or(Expect::CommaOrEnd)?;
-            if token == end {
+            f(tok, self)?;
+            tok = token(self)?.ok_or(Expect::CommaOrEnd)?;
+            if tok == end {
                 return Ok(());
-            } else if token == Token::Comma {
-                token = self.ws_token().ok_or(Expect::Value)?;
+            } else if tok == Token::Comma {
+                tok = token(self)?.ok_or(Expect::Value)?;
+                if trailing && tok == end {
+                    return Ok(());
+                }
             } else {
                 return Err(Expect::CommaOrEnd)?;
             }
         }
     }
 
+    /// Execute `f` for every value in an object whose key is contained in `keys`,
+    /// discarding all other values with [`ignore::parse`](crate::ignore::parse).
+    ///
+    /// This is to be called after the opening `{` of an object has been consumed.
+    /// The function `f` obtains the index of the key in `keys`, as well as
+    /// the first token of the value, which it has to consume.
+    /// Keys are compared without allocating memory,
+    /// interpreting escape sequences such as `\u0061`.
+    ///
+    /// ~~~
+    /// use hifijson::{ignore, token::Lex, Error, SliceLexer, Token};
+    /// let json = br#"{"a": 1, "b": [2], "c": {}, "a": 3}"#;
+    /// let mut lexer = SliceLexer::new(json);
+    /// let mut found = Vec::new();
+    /// assert_eq!(lexer.ws_token(), Some(Token::LCurly));
+    /// lexer.object_select(&["c", "a"], |i, token, lexer| {
+    ///     found.push(i);
+    ///     ignore::parse(token, lexer)
+    /// })?;
+    /// assert_eq!(found, [1, 0, 1]);
+    /// # Ok::<_, Error>(())
+    /// ~~~
+    fn object_select<E, F>(&mut self, keys: &[&str], mut f: F) -> Result<(), E>
+    where
+        Self: crate::Lex + Sized,
+        E: From<Expect> + From<crate::Error>,
+        F: FnMut(usize, Token, &mut Self) -> Result<(), E>,
+    {
+        self.seq(Token::RCurly, |token, lexer| {
+            let key = lexer.str_colon(token, |lexer| {
+                // index of a key that starts with the bytes read so far
+                let mut idx = keys.first().map(|_| 0);
+                let mut len = 0;
+                lexer
+                    .str_chunks(|chunk| {
+                        for c in chunk {
+                            idx = idx.and_then(|i| {
+                                let prefix = &keys[i].as_bytes()[..len];
+                                keys.iter().position(|k| {
+                                    let k = k.as_bytes();
+                                    k.get(..len) == Some(prefix) && k.get(len) == Some(c)
+                                })
+                            });
+                            len += 1;
+                        }
+                        Ok::<_, crate::str::Error>(())
+                    })
+                    .map_err(|e| E::from(crate::Error::Str(e)))?;
+                Ok::<_, E>(idx.and_then(|i| {
+                    let key = &keys[i].as_bytes()[..len];
+                    keys.iter().position(|k| k.as_bytes() == key)
+                }))
+            })?;
+            let token = lexer.ws_token().ok_or(Expect::Value)?;
+            match key {
+                Some(i) => f(i, token, lexer),
+                None => Ok(crate::ignore::parse(token, lexer)?),
+            }
+        })
+    }
+
+    /// Parse once using given function, leaving any input after the parsed value.
+    ///
+    /// In contrast to [`Lex::exactly_one`], this does not fail if
+    /// anything follows the value, so that further values can be parsed afterwards.
+    ///
+    /// ~~~
+    /// use hifijson::{ignore, token::Lex, SliceLexer};
+    /// let mut lexer = SliceLexer::new(b"[1] {} x");
+    /// lexer.parse_one(ignore::parse).unwrap();
+    /// assert_eq!(lexer.as_slice(), b" {} x");
+    /// ~~~
+    fn parse_one<T, E: From<Expect>, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(Token, &mut Self) -> Result<T, E>,
+    {
+        let token = self.ws_token().ok_or(Expect::Value)?;
+        f(token, self)
+    }
+
     /// Parse once using given function and assure that the function has consumed all tokens.
+    ///
+    /// Whitespace after the parsed value is consumed, so
+    /// input such as `42\n` or `42  ` is accepted.
+    /// If anything but whitespace follows the parsed value,
+    /// fail with [`Expect::TrailingByte`].
+    /// If the input is empty or contains only whitespace,
+    /// fail with [`Expect::EmptyInput`].
+    ///
+    /// ~~~
+    /// use hifijson::{ignore, token::Lex, Error, Expect, SliceLexer};
+    /// let v = SliceLexer::new(b"[1] }").exactly_one(ignore::parse);
+    /// let err = v.unwrap_err();
+    /// assert_eq!(err, Error::Token(Expect::TrailingByte(b'}')));
+    /// assert_eq!(err.to_string(), "end of file expected, found `}`");
+    /// ~~~
     fn exactly_one<T, E: From<Expect>, F>(&mut self, f: F) -> Result<T, E>
     where
         F: FnOnce(Token, &mut Self) -> Result<T, E>,
     {
-        let token = self.ws_token().ok_or(Expect::Value)?;
+        let token = self.ws_token().ok_or(Expect::EmptyInput)?;
         let v = f(token, self)?;
         self.eat_whitespace();
         match self.peek_next() {
             None => Ok(v),
-            Some(_) => Err(Expect::Eof)?,
+            Some(c) => Err(Expect::TrailingByte(*c))?,
         }
     }
 }
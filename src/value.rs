@@ -1,12 +1,13 @@
 //! Parsing and values.
 
-use crate::{num, str, token, Error, LexAlloc, Token};
-use alloc::vec::Vec;
+use crate::patch::{self, Op};
+use crate::{num, str, token, Error, LexAlloc, LimitKind, Position, Token};
+use alloc::{string::String, vec::Vec};
 use core::fmt;
-use core::ops::Deref;
+use core::ops::{ControlFlow, Deref};
 
 /// JSON value.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Value<Num, Str> {
     /// `null`
     Null,
@@ -22,6 +23,175 @@ pub enum Value<Num, Str> {
     Object(Vec<(Str, Self)>),
 }
 
+/// JSON value that stores arrays and objects in the containers given by `C`.
+///
+/// This is like [`Value`], which always stores arrays and objects as vectors.
+pub enum GenericValue<Num, Str, C: Containers<Num, Str>> {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// string representation of a number with positional information
+    Number((Num, num::Parts)),
+    /// string
+    String(Str),
+    /// array
+    Array(C::Array),
+    /// mapping from strings to values
+    Object(C::Object),
+}
+
+/// Containers that store the arrays and objects of a [`GenericValue`].
+///
+/// Values are parsed into containers via [`parse_in`],
+/// which collects the elements of arrays and objects via [`Extend`].
+pub trait Containers<Num, Str>: Sized {
+    /// container of array elements
+    type Array: Default + Extend<GenericValue<Num, Str, Self>>;
+    /// container of object key-value pairs
+    type Object: Default + Extend<(Str, GenericValue<Num, Str, Self>)>;
+}
+
+/// Store arrays and objects as vectors.
+///
+/// This preserves the order of object keys as well as duplicate keys.
+#[derive(Clone, Debug)]
+pub struct Vecs;
+
+impl<Num, Str> Containers<Num, Str> for Vecs {
+    type Array = Vec<GenericValue<Num, Str, Self>>;
+    type Object = Vec<(Str, GenericValue<Num, Str, Self>)>;
+}
+
+/// Store arrays as vectors and objects as maps ordered by key.
+///
+/// This allows looking up object keys in logarithmic time.
+/// If an object contains the same key several times, the last value is taken.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value::{self, GenericValue, Maps}, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"{"b": [1], "a": 2, "b": 3}"#);
+/// let v: GenericValue<_, _, Maps> = lexer.exactly_one(value::parse_in).unwrap();
+/// let obj = match v {
+///     GenericValue::Object(obj) => obj,
+///     _ => panic!(),
+/// };
+/// assert_eq!(obj.keys().collect::<Vec<_>>(), ["a", "b"]);
+/// assert_eq!(obj["b"], GenericValue::Number(("3", Default::default())));
+/// ~~~
+#[derive(Clone, Debug)]
+pub struct Maps;
+
+impl<Num, Str: Ord> Containers<Num, Str> for Maps {
+    type Array = Vec<GenericValue<Num, Str, Self>>;
+    type Object = alloc::collections::BTreeMap<Str, GenericValue<Num, Str, Self>>;
+}
+
+// The traits are implemented separately for every kind of containers,
+// because deriving them for arbitrary containers leads to cyclic trait bounds.
+macro_rules! impl_containers {
+    ($c:ty $(, $str:path)?) => {
+        impl<Num: Clone, Str: Clone $(+ $str)?> Clone for GenericValue<Num, Str, $c> {
+            fn clone(&self) -> Self {
+                use GenericValue::*;
+                match self {
+                    Null => Null,
+                    Bool(b) => Bool(*b),
+                    Number(n) => Number(n.clone()),
+                    String(s) => String(s.clone()),
+                    Array(a) => Array(a.clone()),
+                    Object(o) => Object(o.clone()),
+                }
+            }
+        }
+
+        impl<Num: fmt::Debug, Str: fmt::Debug $(+ $str)?> fmt::Debug
+            for GenericValue<Num, Str, $c>
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                use GenericValue::*;
+                match self {
+                    Null => f.write_str("Null"),
+                    Bool(b) => f.debug_tuple("Bool").field(b).finish(),
+                    Number(n) => f.debug_tuple("Number").field(n).finish(),
+                    String(s) => f.debug_tuple("String").field(s).finish(),
+                    Array(a) => f.debug_tuple("Array").field(a).finish(),
+                    Object(o) => f.debug_tuple("Object").field(o).finish(),
+                }
+            }
+        }
+
+        impl<Num: PartialEq, Str: PartialEq $(+ $str)?> PartialEq for GenericValue<Num, Str, $c> {
+            fn eq(&self, other: &Self) -> bool {
+                use GenericValue::*;
+                match (self, other) {
+                    (Null, Null) => true,
+                    (Bool(l), Bool(r)) => l == r,
+                    (Number(l), Number(r)) => l == r,
+                    (String(l), String(r)) => l == r,
+                    (Array(l), Array(r)) => l == r,
+                    (Object(l), Object(r)) => l == r,
+                    _ => false,
+                }
+            }
+        }
+    };
+}
+
+impl_containers!(Vecs);
+impl_containers!(Maps, Ord);
+
+/// Value that can be constructed by [`parse`].
+trait Build<Num, Str>: Sized {
+    /// container of array elements
+    type Arr: Default + Extend<Self>;
+    /// container of object key-value pairs
+    type Obj: Default + Extend<(Str, Self)>;
+
+    fn null() -> Self;
+    fn bool(b: bool) -> Self;
+    fn number(n: (Num, num::Parts)) -> Self;
+    fn string(s: Str) -> Self;
+    fn array(a: Self::Arr) -> Self;
+    fn object(o: Self::Obj) -> Self;
+}
+
+// Both value types have the same variants, so they are built the same way.
+macro_rules! build {
+    () => {
+        fn null() -> Self {
+            Self::Null
+        }
+        fn bool(b: bool) -> Self {
+            Self::Bool(b)
+        }
+        fn number(n: (Num, num::Parts)) -> Self {
+            Self::Number(n)
+        }
+        fn string(s: Str) -> Self {
+            Self::String(s)
+        }
+        fn array(a: Self::Arr) -> Self {
+            Self::Array(a)
+        }
+        fn object(o: Self::Obj) -> Self {
+            Self::Object(o)
+        }
+    };
+}
+
+impl<Num, Str> Build<Num, Str> for Value<Num, Str> {
+    type Arr = Vec<Self>;
+    type Obj = Vec<(Str, Self)>;
+    build!();
+}
+
+impl<Num, Str, C: Containers<Num, Str>> Build<Num, Str> for GenericValue<Num, Str, C> {
+    type Arr = C::Array;
+    type Obj = C::Object;
+    build!();
+}
+
 impl<NumL: PartialEq<NumR>, NumR, StrL: PartialEq<StrR>, StrR> PartialEq<Value<NumR, StrR>>
     for Value<NumL, StrL>
 {
@@ -42,6 +212,444 @@ impl<NumL: PartialEq<NumR>, NumR, StrL: PartialEq<StrR>, StrR> PartialEq<Value<N
     }
 }
 
+impl<Num: Eq, Str: Eq> Eq for Value<Num, Str> {}
+
+/// Compare two values by a total order.
+///
+/// Values of different kinds are ordered as follows:
+/// `null` < booleans < numbers < strings < arrays < objects.
+/// Within each kind, values are ordered as follows:
+///
+/// * `false` < `true`.
+/// * Numbers are ordered by their values, see [`num::cmp`].
+///   Numbers having the same value, such as `1` and `1.0`, are ordered as equal.
+/// * Strings are ordered by `Str`.
+/// * Arrays are ordered lexicographically.
+/// * Objects are ordered lexicographically by
+///   their key-value pairs sorted by key and value.
+///   Objects having the same sorted key-value pairs, but in different order,
+///   are ordered lexicographically by their original key-value pairs.
+///
+/// This order is part of the stable API and will not change between releases.
+///
+/// Because [`PartialEq`] compares numbers by their representations,
+/// values such as `1` and `1.0` are ordered as equal, but they are not equal.
+/// For this reason, [`Value`] does not implement [`Ord`];
+/// instead, this function can be used with methods like [`slice::sort_by`].
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// use core::cmp::Ordering;
+/// let l = SliceLexer::new(b"[1, 2.50]").exactly_one(value::parse_unbounded).unwrap();
+/// let r = SliceLexer::new(b"[1.0, 25e-1]").exactly_one(value::parse_unbounded).unwrap();
+/// assert_eq!(value::cmp(&l, &r), Ordering::Equal);
+/// assert_ne!(l, r);
+///
+/// let mut values = vec![r, l.clone(), value::Value::Null];
+/// values.sort_by(value::cmp);
+/// assert_eq!(values[0].to_string(), "null");
+/// ~~~
+pub fn cmp<Num: Deref<Target = str>, Str: Ord>(
+    l: &Value<Num, Str>,
+    r: &Value<Num, Str>,
+) -> core::cmp::Ordering {
+    use core::cmp::Ordering;
+    use Value::*;
+    // lexicographic order of two sequences, given an order on their elements
+    fn lex<T>(l: &[T], r: &[T], f: impl Fn(&T, &T) -> Ordering) -> Ordering {
+        let mut lr = l.iter().zip(r).map(|(l, r)| f(l, r));
+        let ord = lr.find(|o| o.is_ne());
+        ord.unwrap_or_else(|| l.len().cmp(&r.len()))
+    }
+    let rank = |v: &Value<Num, Str>| match v {
+        Null => 0,
+        Bool(_) => 1,
+        Number(_) => 2,
+        String(_) => 3,
+        Array(_) => 4,
+        Object(_) => 5,
+    };
+    let pair = |(kl, vl): &&(Str, Value<Num, Str>), (kr, vr): &&(Str, Value<Num, Str>)| {
+        kl.cmp(kr).then_with(|| cmp(vl, vr))
+    };
+    match (l, r) {
+        (Null, Null) => Ordering::Equal,
+        (Bool(l), Bool(r)) => l.cmp(r),
+        (Number((nl, pl)), Number((nr, pr))) => num::cmp(nl, pl, nr, pr),
+        (String(l), String(r)) => l.cmp(r),
+        (Array(l), Array(r)) => lex(l, r, |l, r| cmp(l, r)),
+        (Object(l), Object(r)) => {
+            let (mut ls, mut rs): (Vec<_>, Vec<_>) = (l.iter().collect(), r.iter().collect());
+            ls.sort_by(pair);
+            rs.sort_by(pair);
+            lex(&ls, &rs, pair).then_with(|| lex(l, r, |l, r| pair(&l, &r)))
+        }
+        (l, r) => rank(l).cmp(&rank(r)),
+    }
+}
+
+impl<Num, Str> Value<Num, Str> {
+    /// Return the boolean if the value is `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Return the number and its parts if the value is a number.
+    pub fn as_number(&self) -> Option<&(Num, num::Parts)> {
+        match self {
+            Self::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Return the elements if the value is an array.
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Return the key-value pairs if the value is an object.
+    pub fn as_object(&self) -> Option<&[(Str, Self)]> {
+        match self {
+            Self::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Return the boolean if the value is `true` or `false`, consuming the value.
+    pub fn into_bool(self) -> Option<bool> {
+        self.as_bool()
+    }
+
+    /// Return the string if the value is a string, consuming the value.
+    pub fn into_string(self) -> Option<Str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the elements if the value is an array, consuming the value.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let v = SliceLexer::new(br#"[{"a": "b"}, true]"#).exactly_one(value::parse_unbounded);
+    /// let mut a = v.unwrap().into_array().unwrap();
+    /// assert_eq!(a.pop().and_then(|v| v.into_bool()), Some(true));
+    /// let (k, v) = a.pop().and_then(|v| v.into_object()).unwrap().remove(0);
+    /// assert_eq!((&*k, &*v.into_string().unwrap()), ("a", "b"));
+    /// ~~~
+    pub fn into_array(self) -> Option<Vec<Self>> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Return the key-value pairs if the value is an object, consuming the value.
+    pub fn into_object(self) -> Option<Vec<(Str, Self)>> {
+        match self {
+            Self::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Drop the value without recursion.
+    ///
+    /// Dropping a value regularly recurses into its arrays and objects,
+    /// which overflows the stack for deeply nested values.
+    /// This keeps the arrays and objects that remain to be dropped on the heap instead.
+    ///
+    /// ~~~
+    /// use hifijson::value::Value;
+    /// let mut v = Value::<&str, &str>::Null;
+    /// for _ in 0..100_000 {
+    ///     v = Value::Array(vec![v]);
+    /// }
+    /// v.drop_iter();
+    /// ~~~
+    pub fn drop_iter(self) {
+        let mut stack = alloc::vec![self];
+        while let Some(v) = stack.pop() {
+            match v {
+                Self::Array(a) => stack.extend(a),
+                Self::Object(o) => stack.extend(o.into_iter().map(|(_, v)| v)),
+                _ => (),
+            }
+        }
+    }
+
+    /// Return the `i`-th element if the value is an array with more than `i` elements.
+    pub fn get_index(&self, i: usize) -> Option<&Self> {
+        self.as_array()?.get(i)
+    }
+
+    /// Return an iterator over the elements if the value is an array.
+    pub fn items(&self) -> Option<impl Iterator<Item = &Self>> {
+        Some(self.as_array()?.iter())
+    }
+
+    /// Return the key-value pairs sorted by key if the value is an object.
+    ///
+    /// This does not change the order of the pairs in the object.
+    /// Pairs with equal keys retain their original order.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let v = SliceLexer::new(br#"{"b": 1, "a": 2}"#).exactly_one(value::parse_unbounded);
+    /// let v = v.unwrap();
+    /// let keys: Vec<_> = v.entries_sorted().unwrap().into_iter().map(|(k, _)| &**k).collect();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// assert_eq!(v.to_string(), r#"{"b":1,"a":2}"#);
+    /// ~~~
+    pub fn entries_sorted(&self) -> Option<Vec<(&Str, &Self)>>
+    where
+        Str: Ord,
+    {
+        let mut entries: Vec<_> = self.as_object()?.iter().map(|(k, v)| (k, v)).collect();
+        entries.sort_by_key(|(k, _)| *k);
+        Some(entries)
+    }
+
+    /// Convert numbers and strings to owned strings, recursively.
+    ///
+    /// This is useful to keep a value parsed from a [`SliceLexer`](crate::SliceLexer)
+    /// after the input has been dropped.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let input = br#"{"a": [1, "b"]}"#.to_vec();
+    /// let v = SliceLexer::new(&input).exactly_one(value::parse_unbounded);
+    /// let v = v.unwrap().into_owned();
+    /// drop(input);
+    /// assert_eq!(v.to_string(), r#"{"a":[1,"b"]}"#);
+    /// ~~~
+    pub fn into_owned(self) -> Value<String, String>
+    where
+        Num: Into<String>,
+        Str: Into<String>,
+    {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => Value::Bool(b),
+            Self::Number((n, parts)) => Value::Number((n.into(), parts)),
+            Self::String(s) => Value::String(s.into()),
+            Self::Array(a) => Value::Array(a.into_iter().map(Self::into_owned).collect()),
+            Self::Object(o) => {
+                let iter = o.into_iter().map(|(k, v)| (k.into(), v.into_owned()));
+                Value::Object(iter.collect())
+            }
+        }
+    }
+}
+
+impl<'a> Value<&'a str, alloc::borrow::Cow<'a, str>> {
+    /// Return true if all strings in the value are borrowed from the input.
+    ///
+    /// This is the case for values parsed from a [`SliceLexer`](crate::SliceLexer)
+    /// unless they contain strings with escape sequences.
+    /// Numbers are always borrowed.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let parse = |s| SliceLexer::new(s).exactly_one(value::parse_unbounded).unwrap();
+    /// assert!(parse(br#"{"a": ["b", 1]}"#).is_fully_borrowed());
+    /// assert!(!parse(br#"{"a": ["b\n", 1]}"#).is_fully_borrowed());
+    /// ~~~
+    pub fn is_fully_borrowed(&self) -> bool {
+        use alloc::borrow::Cow;
+        let borrowed = |s: &Cow<str>| matches!(s, Cow::Borrowed(_));
+        match self {
+            Self::Null | Self::Bool(_) | Self::Number(_) => true,
+            Self::String(s) => borrowed(s),
+            Self::Array(a) => a.iter().all(Self::is_fully_borrowed),
+            Self::Object(o) => o.iter().all(|(k, v)| borrowed(k) && v.is_fully_borrowed()),
+        }
+    }
+}
+
+impl<Num, Str: Deref<Target = str>> Value<Num, Str> {
+    /// Return the string if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the value of the first occurrence of `key` if the value is an object.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let v = SliceLexer::new(br#"{"a": [1, true]}"#).exactly_one(value::parse_unbounded);
+    /// let v = v.unwrap();
+    /// assert_eq!(v.get("a").and_then(|a| a.get_index(1)?.as_bool()), Some(true));
+    /// assert!(v.get("b").is_none());
+    /// ~~~
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        let mut iter = self.as_object()?.iter();
+        iter.find(|(k, _)| &**k == key).map(|(_, v)| v)
+    }
+
+    /// Return an iterator over the key-value pairs if the value is an object.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let v = SliceLexer::new(br#"{"a": 1, "b": [2]}"#).exactly_one(value::parse_unbounded);
+    /// let v = v.unwrap();
+    /// let keys: Vec<_> = v.entries().unwrap().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// assert_eq!(v.get("b").and_then(|b| b.items()).map(|i| i.count()), Some(1));
+    /// assert!(v.items().is_none());
+    /// ~~~
+    pub fn entries(&self) -> Option<impl Iterator<Item = (&str, &Self)>> {
+        Some(self.as_object()?.iter().map(|(k, v)| (&**k, v)))
+    }
+}
+
+impl<Num: Deref<Target = str>, Str> Value<Num, Str> {
+    /// Return the number if the value is an integer that fits into `i64`.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let parse = |s| SliceLexer::new(s).exactly_one(value::parse_unbounded).unwrap();
+    /// assert_eq!(parse(b"-1").as_i64(), Some(-1));
+    /// assert_eq!(parse(b"1.5").as_i64(), None);
+    /// assert_eq!(parse(b"1.5").as_f64(), Some(1.5));
+    /// ~~~
+    pub fn as_i64(&self) -> Option<i64> {
+        let (n, parts) = self.as_number()?;
+        if parts.fits_i64(n) {
+            n.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Return the number if the value is an integer that fits into `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        let (n, parts) = self.as_number()?;
+        if parts.fits_u64(n) {
+            // `-0` fits into `u64`, but cannot be parsed as such
+            n.strip_prefix('-').unwrap_or(n).parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Return the (potentially rounded) number if the value is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        let (n, parts) = self.as_number()?;
+        Some(parts.to_f64(n))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Num: Deref<Target = str>, Str: Deref<Target = str>> Value<Num, Str> {
+    /// Write the value to `w` in the same compact form as [`Display`](fmt::Display).
+    ///
+    /// Unlike `write!(w, "{}", value)`, this writes the value piece by piece,
+    /// which avoids formatting overhead for large values.
+    pub fn write_to(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        self.write(w, None, 0)
+    }
+
+    /// Write the value to `w`, putting every array element and object member
+    /// on a separate line that is indented by `indent` per nesting level.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let v = SliceLexer::new(br#"{"a": [1, 2], "b": {}}"#).exactly_one(value::parse_unbounded);
+    /// let mut out = Vec::new();
+    /// v.unwrap().write_pretty_to(&mut out, "  ").unwrap();
+    /// let expected = "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}";
+    /// assert_eq!(String::from_utf8(out).unwrap(), expected);
+    /// ~~~
+    pub fn write_pretty_to(
+        &self,
+        w: &mut impl std::io::Write,
+        indent: &str,
+    ) -> std::io::Result<()> {
+        self.write(w, Some(indent), 0)
+    }
+
+    /// Return the canonical form of the value, as described in [`crate::fmt`].
+    ///
+    /// In contrast to [`Display`](fmt::Display),
+    /// this sorts object members by their keys and normalises numbers.
+    /// This fails if the value contains a number that
+    /// cannot be represented as finite double-precision float.
+    ///
+    /// ~~~
+    /// use hifijson::{token::Lex, value, SliceLexer};
+    /// let json = br#"{"b": 1E2, "a": [true, -0.0]}"#;
+    /// let v = SliceLexer::new(json).exactly_one(value::parse_unbounded).unwrap();
+    /// assert_eq!(v.to_canonical().unwrap(), r#"{"a":[true,0],"b":100}"#);
+    /// ~~~
+    pub fn to_canonical(&self) -> Result<String, crate::fmt::Error> {
+        let mut out = Vec::new();
+        crate::fmt::write_value(self, &mut out)?;
+        // canonical output consists of UTF-8 strings and ASCII characters
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn write<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        indent: Option<&str>,
+        level: usize,
+    ) -> std::io::Result<()> {
+        let newline = |w: &mut W, level| match indent {
+            Some(indent) => {
+                w.write_all(b"\n")?;
+                (0..level).try_for_each(|_| w.write_all(indent.as_bytes()))
+            }
+            None => Ok(()),
+        };
+        let colon: &[u8] = if indent.is_some() { b": " } else { b":" };
+        match self {
+            Value::Null => w.write_all(b"null"),
+            Value::Bool(true) => w.write_all(b"true"),
+            Value::Bool(false) => w.write_all(b"false"),
+            Value::Number((n, _)) => w.write_all(n.as_bytes()),
+            Value::String(s) => write!(w, "{}", str::Display::new(&**s)),
+            Value::Array(a) if a.is_empty() => w.write_all(b"[]"),
+            Value::Object(o) if o.is_empty() => w.write_all(b"{}"),
+            Value::Array(a) => {
+                w.write_all(b"[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        w.write_all(b",")?;
+                    }
+                    newline(w, level + 1)?;
+                    v.write(w, indent, level + 1)?;
+                }
+                newline(w, level)?;
+                w.write_all(b"]")
+            }
+            Value::Object(o) => {
+                w.write_all(b"{")?;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i > 0 {
+                        w.write_all(b",")?;
+                    }
+                    newline(w, level + 1)?;
+                    write!(w, "{}", str::Display::new(&**k))?;
+                    w.write_all(colon)?;
+                    v.write(w, indent, level + 1)?;
+                }
+                newline(w, level)?;
+                w.write_all(b"}")
+            }
+        }
+    }
+}
+
 impl<Num: Deref<Target = str>, Str: Deref<Target = str>> fmt::Display for Value<Num, Str> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Value::*;
@@ -70,33 +678,47 @@ impl<Num: Deref<Target = str>, Str: Deref<Target = str>> fmt::Display for Value<
     }
 }
 
-/// Parse a value, using `f` to parse recursive values inside arrays / objects.
-fn parse<L: LexAlloc>(
+/// Parse a value, using `key` to parse object keys and
+/// `f` to parse recursive values inside arrays / objects.
+///
+/// If `trailing` is true, permit a comma after the last item of arrays / objects.
+fn parse<L: LexAlloc, S, V, E, K>(
     token: Token,
     lexer: &mut L,
-    f: impl Fn(Token, &mut L) -> Result<Value<L::Num, L::Str>, Error>,
-) -> Result<Value<L::Num, L::Str>, Error> {
+    trailing: bool,
+    key: &mut K,
+    mut f: impl FnMut(Token, &mut L, &mut K) -> Result<V, E>,
+) -> Result<V, E>
+where
+    S: From<L::Str>,
+    V: Build<L::Num, S>,
+    E: From<Error> + From<token::Expect>,
+    K: FnMut(Token, &mut L) -> Result<S, E>,
+{
+    use core::iter::once;
     match token {
-        Token::Null => Ok(Value::Null),
-        Token::True => Ok(Value::Bool(true)),
-        Token::False => Ok(Value::Bool(false)),
-        Token::DigitOrMinus => Ok(Value::Number(lexer.num_string()?)),
-        Token::Quote => Ok(Value::String(lexer.str_string()?)),
-        Token::LSquare => Ok(Value::Array({
-            let mut arr = Vec::new();
-            lexer.seq(Token::RSquare, |token, lexer| {
-                arr.push(f(token, lexer)?);
-                Ok::<_, Error>(())
+        Token::Null => Ok(V::null()),
+        Token::True => Ok(V::bool(true)),
+        Token::False => Ok(V::bool(false)),
+        Token::DigitOrMinus => Ok(V::number(lexer.num_string().map_err(Error::Num)?)),
+        Token::Quote => Ok(V::string(lexer.str_string().map_err(Error::Str)?.into())),
+        Token::LSquare => Ok(V::array({
+            let mut arr = V::Arr::default();
+            lexer.seq_with(Token::RSquare, trailing, |token, lexer| {
+                arr.extend(once(f(token, lexer, key)?));
+                Ok::<_, E>(())
             })?;
             arr
         })),
-        Token::LCurly => Ok(Value::Object({
-            let mut obj = Vec::new();
-            lexer.seq(Token::RCurly, |token, lexer| {
-                let key = lexer.str_colon(token, |lexer| lexer.str_string().map_err(Error::Str))?;
-                let value = f(lexer.ws_token().ok_or(token::Expect::Value)?, lexer)?;
-                obj.push((key, value));
-                Ok::<_, Error>(())
+        Token::LCurly => Ok(V::object({
+            let mut obj = V::Obj::default();
+            lexer.seq_with(Token::RCurly, trailing, |token, lexer| {
+                let k = key(token, lexer)?;
+                let colon = lexer.ws_token().filter(|t| *t == Token::Colon);
+                colon.ok_or(token::Expect::Colon)?;
+                let token = lexer.ws_token().ok_or(token::Expect::Value)?;
+                obj.extend(once((k, f(token, lexer, key)?)));
+                Ok::<_, E>(())
             })?;
             obj
         })),
@@ -104,14 +726,191 @@ fn parse<L: LexAlloc>(
     }
 }
 
+/// Parse an object key that is a string.
+fn string_key<L: LexAlloc, E: From<Error> + From<token::Expect>>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<L::Str, E> {
+    if token != Token::Quote {
+        Err(token::Expect::StringFound(token))?
+    }
+    Ok(lexer.str_string().map_err(Error::Str)?)
+}
+
 /// Parse a value, not limiting the recursion depth.
 ///
-/// To prevent stack overflows, consider using [`parse_bounded`].
+/// To prevent stack overflows, consider using [`parse_bounded`] or [`parse_iter`].
 pub fn parse_unbounded<L: LexAlloc>(
     token: Token,
     lexer: &mut L,
 ) -> Result<Value<L::Num, L::Str>, Error> {
-    parse(token, lexer, parse_unbounded)
+    parse_with_key(token, lexer, &mut string_key)
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// store its arrays and objects in the containers given by `C`.
+///
+/// See [`Maps`] for an example.
+pub fn parse_in<L: LexAlloc, C: Containers<L::Num, L::Str>>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<GenericValue<L::Num, L::Str, C>, Error> {
+    parse(token, lexer, false, &mut string_key, |token, lexer, _| {
+        parse_in(token, lexer)
+    })
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// permit a comma after the last item of arrays and objects.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let v = SliceLexer::new(br#"[1, {"a": 2,},]"#).exactly_one(value::parse_trailing);
+/// assert_eq!(v.unwrap().to_string(), r#"[1,{"a":2}]"#);
+/// ~~~
+pub fn parse_trailing<L: LexAlloc>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    parse(token, lexer, true, &mut string_key, |token, lexer, _| {
+        parse_trailing(token, lexer)
+    })
+}
+
+/// Limits for [`parse_limited`].
+///
+/// By default, nothing is limited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// maximal number of nested arrays and objects,
+    /// where 0 permits only `null`, booleans, numbers, and strings
+    pub max_depth: usize,
+    /// maximal number of bytes consumed from the lexer
+    pub max_total_bytes: usize,
+    /// maximal number of values, including the values inside arrays and objects
+    pub max_values: usize,
+    /// maximal length of strings (including object keys), see [`str::LexAlloc::str_string_bounded`]
+    pub max_string_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_total_bytes: usize::MAX,
+            max_values: usize::MAX,
+            max_string_len: usize::MAX,
+        }
+    }
+}
+
+/// State for [`parse_limited`].
+struct Limiter {
+    limits: Limits,
+    /// position of the lexer at the start of parsing
+    start: usize,
+    /// number of values encountered so far
+    values: usize,
+}
+
+impl Limiter {
+    /// Fail if more bytes have been consumed than permitted, else return remaining bytes.
+    fn bytes_left<L: Position>(&self, lexer: &L) -> Result<usize, Error> {
+        let consumed = lexer.position().wrapping_sub(self.start);
+        let left = self.limits.max_total_bytes.checked_sub(consumed);
+        left.ok_or(Error::Limit(LimitKind::TotalBytes))
+    }
+
+    fn string<L: LexAlloc + Position>(&self, lexer: &mut L) -> Result<L::Str, Error> {
+        let (bytes_left, max_len) = (self.bytes_left(lexer)?, self.limits.max_string_len);
+        lexer
+            .str_string_bounded(bytes_left.min(max_len))
+            .map_err(|e| match e {
+                str::Error::TooLong if max_len <= bytes_left => Error::Limit(LimitKind::StringLen),
+                str::Error::TooLong => Error::Limit(LimitKind::TotalBytes),
+                e => Error::Str(e),
+            })
+    }
+
+    fn number<L: LexAlloc + Position>(&self, lexer: &mut L) -> Result<(L::Num, num::Parts), Error> {
+        let bytes_left = self.bytes_left(lexer)?;
+        lexer.num_string_bounded(bytes_left).map_err(|e| match e {
+            num::Error::TooLong => Error::Limit(LimitKind::TotalBytes),
+            e => Error::Num(e),
+        })
+    }
+
+    fn parse<L: LexAlloc + Position>(
+        &mut self,
+        depth: usize,
+        token: Token,
+        lexer: &mut L,
+    ) -> Result<Value<L::Num, L::Str>, Error> {
+        self.values += 1;
+        if self.values > self.limits.max_values {
+            return Err(Error::Limit(LimitKind::Values));
+        }
+        let nested = || depth.checked_sub(1).ok_or(Error::Limit(LimitKind::Depth));
+        let v = match token {
+            Token::DigitOrMinus => Value::Number(self.number(lexer)?),
+            Token::Quote => Value::String(self.string(lexer)?),
+            Token::LSquare => Value::Array({
+                let depth = nested()?;
+                let mut arr = Vec::new();
+                lexer.seq(Token::RSquare, |token, lexer| {
+                    arr.push(self.parse(depth, token, lexer)?);
+                    Ok::<_, Error>(())
+                })?;
+                arr
+            }),
+            Token::LCurly => Value::Object({
+                let depth = nested()?;
+                let mut obj = Vec::new();
+                lexer.seq(Token::RCurly, |token, lexer| {
+                    if token != Token::Quote {
+                        Err(token::Expect::StringFound(token))?
+                    }
+                    let key = self.string(lexer)?;
+                    let colon = lexer.ws_token().filter(|t| *t == Token::Colon);
+                    colon.ok_or(token::Expect::Colon)?;
+                    let token = lexer.ws_token().ok_or(token::Expect::Value)?;
+                    obj.push((key, self.parse(depth, token, lexer)?));
+                    Ok::<_, Error>(())
+                })?;
+                obj
+            }),
+            _ => parse_unbounded(token, lexer)?,
+        };
+        self.bytes_left(lexer)?;
+        Ok(v)
+    }
+}
+
+/// Parse a value, failing with [`Error::Limit`] as soon as any of the given limits is exceeded.
+///
+/// This serves to protect against malicious input.
+/// Bytes are counted from the start of this function, so
+/// the token passed to this function is not counted.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value::{self, Limits}, Error, LimitKind, SliceLexer};
+/// let limits = Limits { max_values: 3, ..Limits::default() };
+/// let mut lexer = SliceLexer::new(b"[1, 2, 3]");
+/// let v = lexer.exactly_one(|token, lexer| value::parse_limited(token, lexer, limits));
+/// assert_eq!(v.unwrap_err(), Error::Limit(LimitKind::Values));
+/// ~~~
+pub fn parse_limited<L: LexAlloc + Position>(
+    token: Token,
+    lexer: &mut L,
+    limits: Limits,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    let start = lexer.position();
+    let mut limiter = Limiter {
+        limits,
+        start,
+        values: 0,
+    };
+    limiter.parse(limits.max_depth, token, lexer)
 }
 
 /// Parse an value, limiting the recursion to `depth`.
@@ -123,5 +922,905 @@ pub fn parse_bounded<L: LexAlloc>(
     lexer: &mut L,
 ) -> Result<Value<L::Num, L::Str>, Error> {
     let d = depth.checked_sub(1).ok_or(Error::Depth)?;
-    parse(token, lexer, |token, lexer| parse_bounded(d, token, lexer))
+    parse(token, lexer, false, &mut string_key, |token, lexer, _| {
+        parse_bounded(d, token, lexer)
+    })
+}
+
+/// Array or object whose parsing has started, but not yet finished.
+enum Partial<Num, Str> {
+    Array(Vec<Value<Num, Str>>),
+    /// key-value pairs and the key of the value currently being parsed
+    Object(Vec<(Str, Value<Num, Str>)>, Str),
+}
+
+/// Parse a value like [`parse_unbounded`], but without recursion.
+///
+/// Instead of the call stack, this keeps arrays and objects that
+/// are currently being parsed on the heap.
+/// That way, it can parse arbitrarily deeply nested values without stack overflows.
+///
+/// However, dropping, printing, or comparing the returned value
+/// recurses into its arrays and objects, so
+/// these operations may still overflow the stack for deeply nested values.
+/// To drop such values, use [`Value::drop_iter`].
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let json = "[".repeat(10000) + &"]".repeat(10000);
+/// let v = SliceLexer::new(json.as_bytes()).exactly_one(value::parse_iter);
+/// v.unwrap().drop_iter();
+/// ~~~
+pub fn parse_iter<L: LexAlloc>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    use token::Expect;
+    let mut stack = Vec::new();
+    // read an object key and return it together with the token that starts its value
+    let key = |token, lexer: &mut L| -> Result<_, Error> {
+        let key = string_key::<_, Error>(token, lexer)?;
+        lexer
+            .ws_token()
+            .filter(|t| *t == Token::Colon)
+            .ok_or(Expect::Colon)?;
+        Ok((key, lexer.ws_token().ok_or(Expect::Value)?))
+    };
+
+    let mut token = token;
+    loop {
+        // read a value that starts with `token`
+        let mut value = match token {
+            Token::Null => Value::Null,
+            Token::True => Value::Bool(true),
+            Token::False => Value::Bool(false),
+            Token::DigitOrMinus => Value::Number(lexer.num_string().map_err(Error::Num)?),
+            Token::Quote => Value::String(lexer.str_string().map_err(Error::Str)?),
+            Token::LSquare => match lexer.ws_token().ok_or(Expect::ValueOrEnd)? {
+                Token::RSquare => Value::Array(Vec::new()),
+                next => {
+                    stack.push(Partial::Array(Vec::new()));
+                    token = next;
+                    continue;
+                }
+            },
+            Token::LCurly => match lexer.ws_token().ok_or(Expect::ValueOrEnd)? {
+                Token::RCurly => Value::Object(Vec::new()),
+                next => {
+                    let (k, next) = key(next, lexer)?;
+                    stack.push(Partial::Object(Vec::new(), k));
+                    token = next;
+                    continue;
+                }
+            },
+            _ => return Err(Expect::Value.into()),
+        };
+
+        // the value is complete, so add it to its parent and
+        // close arrays / objects until we find a comma
+        token = loop {
+            match stack.pop() {
+                None => return Ok(value),
+                Some(Partial::Array(mut arr)) => {
+                    arr.push(value);
+                    match lexer.ws_token().ok_or(Expect::CommaOrEnd)? {
+                        Token::RSquare => value = Value::Array(arr),
+                        Token::Comma => {
+                            stack.push(Partial::Array(arr));
+                            break lexer.ws_token().ok_or(Expect::Value)?;
+                        }
+                        _ => return Err(Expect::CommaOrEnd.into()),
+                    }
+                }
+                Some(Partial::Object(mut obj, k)) => {
+                    obj.push((k, value));
+                    match lexer.ws_token().ok_or(Expect::CommaOrEnd)? {
+                        Token::RCurly => value = Value::Object(obj),
+                        Token::Comma => {
+                            let next = lexer.ws_token().ok_or(Expect::Value)?;
+                            let (k, next) = key(next, lexer)?;
+                            stack.push(Partial::Object(obj, k));
+                            break next;
+                        }
+                        _ => return Err(Expect::CommaOrEnd.into()),
+                    }
+                }
+            }
+        };
+    }
+}
+
+/// Value with the number and string types of a lexer.
+type LexValue<L> = Value<<L as num::LexWrite>::Num, <L as str::LexAlloc>::Str>;
+
+/// State for [`parse_recovering`].
+struct Recoverer {
+    /// position of the lexer at the start of parsing
+    start: usize,
+    /// errors encountered so far, together with their offset
+    errors: Vec<(usize, Error)>,
+}
+
+impl Recoverer {
+    fn error<L: Position>(&mut self, lexer: &L, e: impl Into<Error>) {
+        let e = (lexer.position().wrapping_sub(self.start), e.into());
+        // do not report the same error at the same place several times,
+        // which happens for example when several arrays are unterminated
+        if self.errors.last() != Some(&e) {
+            self.errors.push(e)
+        }
+    }
+
+    /// Skip input until a comma or a closing delimiter that does not belong to the skipped input.
+    fn skip<L: LexAlloc>(lexer: &mut L) {
+        let mut depth = 0_usize;
+        loop {
+            lexer.eat_whitespace();
+            match lexer.peek_next().copied() {
+                None => return,
+                Some(b',' | b']' | b'}') if depth == 0 => return,
+                Some(b'[' | b'{') => depth += 1,
+                Some(b']' | b'}') => depth -= 1,
+                Some(b'"') => {
+                    lexer.take_next();
+                    // errors inside skipped input are not reported
+                    let _ = lexer.str_ignore();
+                    continue;
+                }
+                Some(_) => (),
+            }
+            lexer.take_next();
+        }
+    }
+
+    fn parse<L: LexAlloc + Position>(&mut self, token: Token, lexer: &mut L) -> LexValue<L> {
+        match token {
+            Token::LSquare => Value::Array(self.array(lexer)),
+            Token::LCurly => Value::Object(self.object(lexer)),
+            _ => parse_unbounded(token, lexer).unwrap_or_else(|e| {
+                let in_str = matches!(e, Error::Str(str::Error::Escape(_) | str::Error::Control));
+                self.error(lexer, e);
+                if in_str {
+                    Self::skip_str(lexer)
+                }
+                Self::skip(lexer);
+                Value::Null
+            }),
+        }
+    }
+
+    /// Skip the remainder of a string in which an error occurred.
+    fn skip_str<L: LexAlloc>(lexer: &mut L) {
+        let mut escaped = false;
+        lexer.skip_until(|c| match c {
+            _ if core::mem::take(&mut escaped) => false,
+            b'\\' => {
+                escaped = true;
+                false
+            }
+            c => c == b'"',
+        });
+        lexer.take_next();
+    }
+
+    /// Read the delimiter after an item of an array or object that ends with `end`.
+    ///
+    /// Return `None` if the array or object is finished,
+    /// else the first token of the next item.
+    fn delimiter<L: LexAlloc + Position>(
+        &mut self,
+        lexer: &mut L,
+        end: Token,
+    ) -> Option<Option<Token>> {
+        use token::Expect;
+        loop {
+            match lexer.ws_token() {
+                Some(Token::Comma) => return Some(lexer.ws_token()),
+                Some(t) if t == end => return None,
+                // a wrong closing delimiter also ends the array or object
+                None | Some(Token::RSquare | Token::RCurly) => {
+                    self.error(lexer, Expect::CommaOrEnd);
+                    return None;
+                }
+                // a comma is missing before the next item
+                Some(t) if t.kind().is_some() => {
+                    self.error(lexer, Expect::CommaOrEnd);
+                    return Some(Some(t));
+                }
+                Some(_) => {
+                    self.error(lexer, Expect::CommaOrEnd);
+                    Self::skip(lexer)
+                }
+            }
+        }
+    }
+
+    fn array<L: LexAlloc + Position>(&mut self, lexer: &mut L) -> Vec<LexValue<L>> {
+        use token::Expect;
+        let mut arr = Vec::new();
+        let mut token = match lexer.ws_token() {
+            Some(Token::RSquare) => return arr,
+            None => {
+                self.error(lexer, Expect::ValueOrEnd);
+                return arr;
+            }
+            token => token,
+        };
+        loop {
+            match token {
+                Some(Token::Comma) => {
+                    self.error(lexer, Expect::Value);
+                    arr.push(Value::Null);
+                    token = lexer.ws_token();
+                    continue;
+                }
+                None | Some(Token::RSquare | Token::RCurly) => {
+                    self.error(lexer, Expect::Value);
+                    return arr;
+                }
+                Some(token) => arr.push(self.parse(token, lexer)),
+            }
+            match self.delimiter(lexer, Token::RSquare) {
+                Some(next) => token = next,
+                None => return arr,
+            }
+        }
+    }
+
+    fn object<L: LexAlloc + Position>(&mut self, lexer: &mut L) -> Vec<(L::Str, LexValue<L>)> {
+        use token::Expect;
+        let mut obj = Vec::new();
+        let mut token = match lexer.ws_token() {
+            Some(Token::RCurly) => return obj,
+            None => {
+                self.error(lexer, Expect::ValueOrEnd);
+                return obj;
+            }
+            token => token,
+        };
+        loop {
+            match token {
+                Some(Token::Comma) => {
+                    self.error(lexer, Expect::String);
+                    token = lexer.ws_token();
+                    continue;
+                }
+                None | Some(Token::RSquare | Token::RCurly) => {
+                    self.error(lexer, Expect::String);
+                    return obj;
+                }
+                Some(token) => match self.member(token, lexer) {
+                    Some(member) => obj.push(member),
+                    None => Self::skip(lexer),
+                },
+            }
+            match self.delimiter(lexer, Token::RCurly) {
+                Some(next) => token = next,
+                None => return obj,
+            }
+        }
+    }
+
+    /// Read an object member, returning `None` if its key could not be read.
+    fn member<L: LexAlloc + Position>(
+        &mut self,
+        token: Token,
+        lexer: &mut L,
+    ) -> Option<(L::Str, LexValue<L>)> {
+        use token::Expect;
+        if token != Token::Quote {
+            self.error(lexer, Expect::String);
+            return None;
+        }
+        let key = match lexer.str_string() {
+            Ok(key) => key,
+            Err(e) => {
+                self.error(lexer, e);
+                return None;
+            }
+        };
+        // we only peek at the following characters,
+        // in order not to consume the end of the object
+        lexer.eat_whitespace();
+        if lexer.peek_next() == Some(&b':') {
+            lexer.take_next();
+            lexer.eat_whitespace();
+        } else {
+            self.error(lexer, Expect::Colon);
+        }
+        let value = match lexer.peek_next() {
+            None | Some(b',' | b']' | b'}') => {
+                self.error(lexer, Expect::Value);
+                Value::Null
+            }
+            Some(_) => match lexer.ws_token() {
+                Some(token) => self.parse(token, lexer),
+                None => Value::Null,
+            },
+        };
+        Some((key, value))
+    }
+}
+
+/// Parse a value, recovering from errors inside arrays and objects.
+///
+/// Instead of failing at the first error, this records the error and
+/// continues parsing at the next comma or closing delimiter,
+/// using `null` in place of any value that could not be parsed.
+/// The result is the best-effort value and all errors that occurred, together with
+/// the number of bytes consumed since the start of this function when the error was detected.
+/// Recovery is heuristic, so later errors may be consequences of earlier ones.
+///
+/// Like [`parse_unbounded`], this does not limit the recursion depth.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, Error, Expect, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"[1, tru, {"a" 2}, [3 4]]"#);
+/// let token = lexer.ws_token().unwrap();
+/// let (v, errors) = value::parse_recovering(token, &mut lexer);
+/// assert_eq!(v.to_string(), r#"[1,null,{"a":2},[3,4]]"#);
+/// let errors: Vec<_> = errors.into_iter().map(|(_offset, e)| e).collect();
+/// assert_eq!(errors, [Expect::Value, Expect::Colon, Expect::CommaOrEnd].map(Error::Token));
+/// ~~~
+pub fn parse_recovering<L: LexAlloc + Position>(
+    token: Token,
+    lexer: &mut L,
+) -> (LexValue<L>, Vec<(usize, Error)>) {
+    let mut recoverer = Recoverer {
+        start: lexer.position(),
+        errors: Vec::new(),
+    };
+    let v = recoverer.parse(token, lexer);
+    (v, recoverer.errors)
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// also accept strings enclosed in single quotes, such as `'a'`.
+///
+/// Single-quoted strings may contain unescaped double quotes, and
+/// escape sequences are treated like in double-quoted strings.
+/// In particular, single quotes in single-quoted strings have to be written as `\u0027`.
+///
+/// Because a single quote does not start any [`Token`],
+/// this reads the first token of the value itself.
+/// This does not limit the recursion depth.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"['a', "b", {'c"': 'd\u0027'}]"#);
+/// let v = value::parse_relaxed(&mut lexer).unwrap();
+/// assert_eq!(v.to_string(), r#"["a","b",{"c\"":"d'"}]"#);
+/// assert_eq!(lexer.ws_token(), None);
+/// ~~~
+pub fn parse_relaxed<L: LexAlloc>(lexer: &mut L) -> Result<Value<L::Num, L::Str>, Error>
+where
+    L::Str: From<String>,
+{
+    use token::Expect;
+    if let Some(s) = single_quoted(lexer)? {
+        return Ok(Value::String(s.into()));
+    }
+    Ok(match lexer.ws_token().ok_or(Expect::Value)? {
+        Token::LSquare => {
+            let mut arr = Vec::new();
+            relaxed_seq(lexer, b']', |lexer| {
+                arr.push(parse_relaxed(lexer)?);
+                Ok(())
+            })?;
+            Value::Array(arr)
+        }
+        Token::LCurly => {
+            let mut obj = Vec::new();
+            relaxed_seq(lexer, b'}', |lexer| {
+                let key = match single_quoted(lexer)? {
+                    Some(key) => key.into(),
+                    None => {
+                        let token = lexer.ws_token().ok_or(Expect::String)?;
+                        if token != Token::Quote {
+                            Err(Expect::StringFound(token))?
+                        }
+                        lexer.str_string().map_err(Error::Str)?
+                    }
+                };
+                let colon = lexer.ws_token().filter(|t| *t == Token::Colon);
+                colon.ok_or(Expect::Colon)?;
+                obj.push((key, parse_relaxed(lexer)?));
+                Ok(())
+            })?;
+            Value::Object(obj)
+        }
+        token => parse(token, lexer, false, &mut string_key, |_, _, _| {
+            Err(Error::from(Expect::Value))
+        })?,
+    })
+}
+
+/// Run `f` on every item of a sequence until `end`, where the opening bracket is consumed.
+fn relaxed_seq<L: LexAlloc>(
+    lexer: &mut L,
+    end: u8,
+    mut f: impl FnMut(&mut L) -> Result<(), Error>,
+) -> Result<(), Error> {
+    lexer.eat_whitespace();
+    match lexer.peek_next() {
+        None => return Err(token::Expect::ValueOrEnd.into()),
+        Some(c) if *c == end => {
+            lexer.take_next();
+            return Ok(());
+        }
+        Some(_) => (),
+    }
+    loop {
+        f(lexer)?;
+        lexer.eat_whitespace();
+        match lexer.take_next() {
+            Some(b',') => (),
+            Some(c) if c == end => return Ok(()),
+            _ => return Err(token::Expect::CommaOrEnd.into()),
+        }
+    }
+}
+
+/// If the next non-whitespace character is a single quote,
+/// read the string that it starts and decode its escape sequences.
+fn single_quoted<L: LexAlloc>(lexer: &mut L) -> Result<Option<String>, Error> {
+    use crate::escape;
+    lexer.eat_whitespace();
+    if lexer.peek_next() != Some(&b'\'') {
+        return Ok(None);
+    }
+    lexer.take_next();
+    let mut raw = Vec::new();
+    lexer.str_foreach_quote(b'\'', |c| raw.push(c))?;
+
+    let mut out = Vec::with_capacity(raw.len());
+    let mut rest = &raw[..];
+    while let Some(pos) = rest.iter().position(|c| *c == b'\\') {
+        out.extend_from_slice(&rest[..pos]);
+        let (c, len) = escape::decode(&rest[pos + 1..]).map_err(str::Error::Escape)?;
+        out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        rest = &rest[pos + 1 + len..];
+    }
+    out.extend_from_slice(rest);
+    let s = String::from_utf8(out).map_err(|e| str::Error::Utf8(e.utf8_error()))?;
+    Ok(Some(s))
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// parse object keys with the function `key` instead of requiring them to be strings.
+///
+/// Like values, object keys start with a token, which is passed to `key`.
+/// Strings outside of object keys are converted with [`From`].
+/// Using `key` to parse strings, this behaves like [`parse_unbounded`].
+///
+/// ~~~
+/// use hifijson::{token::Lex, value::{self, Value}, SliceLexer};
+/// // accept any value as object key, storing its JSON representation
+/// let mut key = |token, lexer: &mut SliceLexer| {
+///     let key = value::parse_unbounded(token, lexer)?;
+///     Ok::<_, hifijson::Error>(key.to_string())
+/// };
+/// let mut lexer = SliceLexer::new(br#"{1: "a", [true]: "b", "c": "d"}"#);
+/// let v = lexer.exactly_one(|token, lexer| value::parse_with_key(token, lexer, &mut key));
+/// let keys: Vec<_> = v.unwrap().into_object().unwrap().into_iter().map(|(k, _)| k).collect();
+/// assert_eq!(keys, ["1", "[true]", r#""c""#]);
+/// ~~~
+pub fn parse_with_key<L: LexAlloc, K: From<L::Str>, F>(
+    token: Token,
+    lexer: &mut L,
+    key: &mut F,
+) -> Result<Value<L::Num, K>, Error>
+where
+    F: FnMut(Token, &mut L) -> Result<K, Error>,
+{
+    parse(token, lexer, false, key, parse_with_key)
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// sort the key-value pairs of every object by key.
+///
+/// Pairs with equal keys retain their original order.
+/// This makes it possible to compare values regardless of the order of keys.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let v = SliceLexer::new(br#"{"b": 1, "a": {"d": 2, "c": 3}}"#).exactly_one(value::parse_sorted);
+/// assert_eq!(v.unwrap().to_string(), r#"{"a":{"c":3,"d":2},"b":1}"#);
+/// ~~~
+pub fn parse_sorted<L: LexAlloc>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    let mut v = parse(token, lexer, false, &mut string_key, |token, lexer, _| {
+        parse_sorted(token, lexer)
+    })?;
+    if let Value::Object(o) = &mut v {
+        o.sort_by(|(k1, _), (k2, _)| Ord::cmp(&**k1, &**k2));
+    }
+    Ok(v)
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// fail with [`Error::DuplicateKey`] if any object contains the same key twice.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, Error, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"[{"a": 1, "b": 2}, {"a": 1, "a": 2}]"#);
+/// let v = lexer.exactly_one(value::parse_unique);
+/// assert_eq!(v.unwrap_err(), Error::DuplicateKey);
+/// ~~~
+pub fn parse_unique<L: LexAlloc>(
+    token: Token,
+    lexer: &mut L,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    let v = parse(token, lexer, false, &mut string_key, |token, lexer, _| {
+        parse_unique(token, lexer)
+    })?;
+    if let Value::Object(o) = &v {
+        let mut keys: Vec<&str> = o.iter().map(|(k, _)| &**k).collect();
+        keys.sort_unstable();
+        if keys.windows(2).any(|w| w[0] == w[1]) {
+            return Err(Error::DuplicateKey);
+        }
+    }
+    Ok(v)
+}
+
+/// Parse a value like [`parse_unbounded`], but
+/// fail with [`Error::UnexpectedKind`] before parsing if it is not of the given kind.
+///
+/// ~~~
+/// use hifijson::{token::{Kind, Lex}, value, Error, SliceLexer};
+/// let mut lexer = SliceLexer::new(b"[1, 2]");
+/// let v = lexer.exactly_one(|token, lexer| value::parse_expect(token, lexer, Kind::Object));
+/// let err = Error::UnexpectedKind { expected: Kind::Object, found: Kind::Array };
+/// assert_eq!(v.unwrap_err(), err);
+/// assert_eq!(err.to_string(), "object expected, found array");
+/// ~~~
+pub fn parse_expect<L: LexAlloc>(
+    token: Token,
+    lexer: &mut L,
+    kind: token::Kind,
+) -> Result<Value<L::Num, L::Str>, Error> {
+    let found = token.kind().ok_or(token::Expect::Value)?;
+    if found != kind {
+        return Err(Error::UnexpectedKind {
+            expected: kind,
+            found,
+        });
+    }
+    parse_unbounded(token, lexer)
+}
+
+/// Parse only the value at the given JSON Pointer (RFC 6901), such as `/a/0`.
+///
+/// All values not on the path are discarded without allocating them.
+/// Return `None` if the pointer does not refer to any value,
+/// in particular if it is neither empty nor starts with `/`.
+/// Array indices must be `0` or start with a nonzero digit, so
+/// `01` and `+1` do not refer to any element.
+/// Like any index past the last array element, `-` never refers to an element.
+/// If an object contains a key several times, its first occurrence is taken.
+/// The whole value is consumed in any case.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let json = br#"{"data": {"items": [{"name": "a"}, {"name": "b/c"}], "a~b": 1}}"#;
+/// let get = |ptr| SliceLexer::new(json).exactly_one(|t, l| value::parse_pointer(ptr, t, l));
+/// let get = |ptr| get(ptr).unwrap().map(|v| v.to_string());
+/// assert_eq!(get("/data/items/1/name").as_deref(), Some(r#""b/c""#));
+/// assert_eq!(get("/data/a~0b").as_deref(), Some("1"));
+/// assert_eq!(get("/data/items/2"), None);
+/// assert_eq!(get("data"), None);
+/// ~~~
+pub fn parse_pointer<L: LexAlloc>(
+    pointer: &str,
+    token: Token,
+    lexer: &mut L,
+) -> Result<Option<LexValue<L>>, Error> {
+    if pointer.is_empty() {
+        return parse_unbounded(token, lexer).map(Some);
+    }
+    let pointer = match pointer.strip_prefix('/') {
+        Some(pointer) => pointer,
+        None => return crate::ignore::parse(token, lexer).map(|()| None),
+    };
+    let (head, rest) = match pointer.find('/') {
+        Some(pos) => (&pointer[..pos], &pointer[pos..]),
+        None => (pointer, ""),
+    };
+    let head = patch::unescape(head);
+
+    let mut found = None;
+    let mut target = |token: Token, lexer: &mut L, matches: bool| {
+        if matches && found.is_none() {
+            found = parse_pointer(rest, token, lexer)?;
+            Ok(())
+        } else {
+            crate::ignore::parse(token, lexer)
+        }
+    };
+    match token {
+        Token::LSquare => {
+            let idx = patch::array_index(&head);
+            let mut i = 0;
+            lexer.seq(Token::RSquare, |token, lexer| {
+                target(token, lexer, idx == Some(i))?;
+                i += 1;
+                Ok::<_, Error>(())
+            })?
+        }
+        Token::LCurly => lexer.seq(Token::RCurly, |token, lexer| {
+            let matches = lexer.str_colon_eq(token, &head)?;
+            let token = lexer.ws_token().ok_or(token::Expect::Value)?;
+            target(token, lexer, matches)
+        })?,
+        token => crate::ignore::parse(token, lexer)?,
+    }
+    Ok(found)
+}
+
+/// Parse a value, running `hook` on every number and failing if `hook` fails.
+///
+/// This can be used to centrally validate numbers, for example
+/// to reject numbers that are out of some range.
+/// The hook may return any error type that can hold parse errors.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, Error};
+/// let mut lexer = hifijson::SliceLexer::new(b"[1, -2, 3.5]");
+/// let mut ints = 0;
+/// let v = lexer.exactly_one(|token, lexer| {
+///     value::parse_with_number_hook(token, lexer, &mut |_num, parts| {
+///         ints += usize::from(parts.is_int());
+///         Ok::<_, Error>(())
+///     })
+/// });
+/// assert!(v.is_ok());
+/// assert_eq!(ints, 2);
+/// ~~~
+pub fn parse_with_number_hook<L: LexAlloc, E: From<Error> + From<token::Expect>, F>(
+    token: Token,
+    lexer: &mut L,
+    hook: &mut F,
+) -> Result<Value<L::Num, L::Str>, E>
+where
+    F: FnMut(&str, &num::Parts) -> Result<(), E>,
+{
+    if token == Token::DigitOrMinus {
+        let (n, parts) = lexer.num_string().map_err(Error::Num)?;
+        hook(&n, &parts)?;
+        return Ok(Value::Number((n, parts)));
+    }
+    parse(token, lexer, false, &mut string_key, |token, lexer, _| {
+        parse_with_number_hook(token, lexer, hook)
+    })
+}
+
+/// Parse a sequence of values separated by whitespace, until the end of input.
+///
+/// After an error has been yielded, the iterator yields nothing more.
+///
+/// ~~~
+/// let mut lexer = hifijson::SliceLexer::new(b"1 [2] {}");
+/// let values = hifijson::value::parse_many(&mut lexer).collect::<Result<Vec<_>, _>>();
+/// assert_eq!(values.unwrap().len(), 3);
+/// ~~~
+pub fn parse_many<'a, L: LexAlloc + 'a>(
+    lexer: &'a mut L,
+) -> impl Iterator<Item = Result<Value<L::Num, L::Str>, Error>> + 'a {
+    many(lexer, |_| Ok(()))
+}
+
+/// Parse at most `max` values separated by whitespace.
+///
+/// This is like [`parse_many`], but stops after `max` values,
+/// leaving the remaining input unconsumed.
+///
+/// ~~~
+/// use hifijson::{value, SliceLexer};
+/// let mut lexer = SliceLexer::new(b"1 2 3");
+/// assert_eq!(value::parse_many_limited(&mut lexer, 2).count(), 2);
+/// assert_eq!(lexer.as_slice(), b" 3");
+/// ~~~
+pub fn parse_many_limited<'a, L: LexAlloc + 'a>(
+    lexer: &'a mut L,
+    max: usize,
+) -> impl Iterator<Item = Result<Value<L::Num, L::Str>, Error>> + 'a {
+    parse_many(lexer).take(max)
+}
+
+/// Parse a sequence of newline-delimited values, until the end of input.
+///
+/// This is like [`parse_many`], but fails if two values are on the same line.
+///
+/// ~~~
+/// let mut lexer = hifijson::SliceLexer::new(b"1\n[2] {}");
+/// let mut values = hifijson::value::parse_lines(&mut lexer);
+/// assert!(values.next().unwrap().is_ok());
+/// assert!(values.next().unwrap().is_err());
+/// assert!(values.next().is_none());
+/// ~~~
+pub fn parse_lines<'a, L: LexAlloc + 'a>(
+    lexer: &'a mut L,
+) -> impl Iterator<Item = Result<Value<L::Num, L::Str>, Error>> + 'a {
+    many(lexer, |lexer| Ok(lexer.line_end()?))
+}
+
+/// Parse values until the end of input, running `after` after every value.
+fn many<'a, L: LexAlloc + 'a>(
+    lexer: &'a mut L,
+    after: impl Fn(&mut L) -> Result<(), Error> + 'a,
+) -> impl Iterator<Item = Result<Value<L::Num, L::Str>, Error>> + 'a {
+    let mut done = false;
+    core::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let v = parse_unbounded(lexer.ws_token()?, lexer);
+        let v = v.and_then(|v| after(lexer).map(|()| v));
+        done = v.is_err();
+        Some(v)
+    })
+}
+
+/// Parse newline-delimited values and run `f` on each of them, until `f` breaks.
+///
+/// Every value has to be followed by a newline (or the end of input);
+/// that means that two values on the same line yield an error.
+/// When `f` breaks, the input following the current value is left unconsumed.
+///
+/// ~~~
+/// use core::ops::ControlFlow;
+/// let mut lexer = hifijson::SliceLexer::new(b"1\n2\n\n3\n");
+/// let mut sum = 0;
+/// hifijson::value::for_each_line(&mut lexer, |v| {
+///     sum += v.to_string().parse::<usize>().unwrap();
+///     ControlFlow::Continue(())
+/// }).unwrap();
+/// assert_eq!(sum, 6);
+/// ~~~
+pub fn for_each_line<L: LexAlloc>(
+    lexer: &mut L,
+    mut f: impl FnMut(Value<L::Num, L::Str>) -> ControlFlow<()>,
+) -> Result<(), Error> {
+    for v in parse_lines(lexer) {
+        if let ControlFlow::Break(()) = f(v?) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Outcome of parsing an array element in [`find`].
+enum Found<T> {
+    /// element that satisfies the predicate, used to stop the parsing of the array
+    Value(T),
+    /// parse error
+    Error(Error),
+}
+
+impl<T> From<Error> for Found<T> {
+    fn from(e: Error) -> Self {
+        Self::Error(e)
+    }
+}
+
+impl<T> From<token::Expect> for Found<T> {
+    fn from(e: token::Expect) -> Self {
+        Self::Error(e.into())
+    }
+}
+
+/// Return the first element of an array that satisfies `pred`.
+///
+/// The elements are parsed one by one, and
+/// parsing stops as soon as an element satisfies `pred`,
+/// leaving the remaining input unconsumed.
+/// If the value is not an array, return it if it satisfies `pred`.
+///
+/// ~~~
+/// use hifijson::{token::Lex, value, SliceLexer};
+/// let mut lexer = SliceLexer::new(br#"[{"a": 1}, {"b": 2}, {"c": 3}]"#);
+/// let token = lexer.ws_token().unwrap();
+/// let v = value::find(token, &mut lexer, |v| v.to_string().contains("b"));
+/// assert_eq!(v.unwrap().unwrap().to_string(), r#"{"b":2}"#);
+/// assert_eq!(lexer.as_slice(), br#", {"c": 3}]"#);
+/// ~~~
+pub fn find<L: LexAlloc, F>(
+    token: Token,
+    lexer: &mut L,
+    mut pred: F,
+) -> Result<Option<LexValue<L>>, Error>
+where
+    F: FnMut(&LexValue<L>) -> bool,
+{
+    if token != Token::LSquare {
+        let v = parse_unbounded(token, lexer)?;
+        return Ok(if pred(&v) { Some(v) } else { None });
+    }
+    let found = lexer.seq(Token::RSquare, |token, lexer| {
+        let v = parse_unbounded(token, lexer)?;
+        if pred(&v) {
+            Err(Found::Value(v))
+        } else {
+            Ok(())
+        }
+    });
+    match found {
+        Ok(()) => Ok(None),
+        Err(Found::Value(v)) => Ok(Some(v)),
+        Err(Found::Error(e)) => Err(e),
+    }
+}
+
+/// Compute patch operations that transform `from` into `to`.
+///
+/// Objects are compared key by key and arrays element by element,
+/// replacing only those values that differ.
+/// If an object contains a key several times, only its first occurrence is considered.
+pub fn diff<Num, Str>(from: &Value<Num, Str>, to: &Value<Num, Str>) -> Vec<Op<Num, Str>>
+where
+    Num: Clone + PartialEq,
+    Str: Clone + PartialEq + Deref<Target = str>,
+{
+    let mut ops = Vec::new();
+    diff_at(from, to, &mut String::new(), &mut ops);
+    ops
+}
+
+fn diff_at<Num, Str>(
+    from: &Value<Num, Str>,
+    to: &Value<Num, Str>,
+    path: &mut String,
+    ops: &mut Vec<Op<Num, Str>>,
+) where
+    Num: Clone + PartialEq,
+    Str: Clone + PartialEq + Deref<Target = str>,
+{
+    use fmt::Write;
+    let len = path.len();
+    match (from, to) {
+        (Value::Array(l), Value::Array(r)) => {
+            for (i, (l, r)) in l.iter().zip(r).enumerate() {
+                write!(path, "/{}", i).unwrap();
+                diff_at(l, r, path, ops);
+                path.truncate(len);
+            }
+            for (i, r) in r.iter().enumerate().skip(l.len()) {
+                write!(path, "/{}", i).unwrap();
+                ops.push(Op::Add(path.clone(), r.clone()));
+                path.truncate(len);
+            }
+            // remove from the back so that indices stay valid
+            for i in (r.len()..l.len()).rev() {
+                write!(path, "/{}", i).unwrap();
+                ops.push(Op::Remove(path.clone()));
+                path.truncate(len);
+            }
+        }
+        (Value::Object(l), Value::Object(r)) => {
+            let pos = |o: &[(Str, _)], k: &str| o.iter().position(|(k_, _)| **k_ == *k);
+            for (i, (k, lv)) in l.iter().enumerate() {
+                if pos(l, k) != Some(i) {
+                    continue;
+                }
+                patch::push_key(path, k);
+                match pos(r, k) {
+                    Some(j) => diff_at(lv, &r[j].1, path, ops),
+                    None => ops.push(Op::Remove(path.clone())),
+                }
+                path.truncate(len);
+            }
+            for (j, (k, rv)) in r.iter().enumerate() {
+                if pos(l, k).is_none() && pos(r, k) == Some(j) {
+                    patch::push_key(path, k);
+                    ops.push(Op::Add(path.clone(), rv.clone()));
+                    path.truncate(len);
+                }
+            }
+        }
+        (l, r) if l == r => (),
+        (_, r) => ops.push(Op::Replace(path.clone(), r.clone())),
+    }
 }
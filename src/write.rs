@@ -3,6 +3,12 @@ pub trait Write {
 
     /// Write input to `bytes` until `stop` yields true.
     fn write_until(&mut self, bytes: &mut Self::Bytes, stop: impl FnMut(u8) -> bool);
+
+    /// Write input to `bytes` until the end of a string contents,
+    /// namely `"`, `\`, or a control character.
+    fn write_until_str_end(&mut self, bytes: &mut Self::Bytes) {
+        self.write_until(bytes, crate::str::string_end)
+    }
 }
 
 impl<'a> Write for crate::SliceLexer<'a> {
@@ -14,6 +20,21 @@ impl<'a> Write for crate::SliceLexer<'a> {
         *bytes = &self.slice[..pos];
         self.slice = &self.slice[pos..]
     }
+
+    #[cfg(feature = "memchr")]
+    fn write_until_str_end(&mut self, bytes: &mut &'a [u8]) {
+        let end = memchr::memchr2(b'"', b'\\', self.slice).unwrap_or(self.slice.len());
+        let (head, _) = self.slice.split_at(end);
+        // this is branch-free, so that it can be vectorised
+        let control = head.iter().fold(false, |acc, c| acc | (*c < 0x20));
+        let pos = if control {
+            head.iter().position(|c| *c < 0x20).unwrap_or(end)
+        } else {
+            end
+        };
+        *bytes = &self.slice[..pos];
+        self.slice = &self.slice[pos..]
+    }
 }
 
 #[cfg(feature = "alloc")]
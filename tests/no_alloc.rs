@@ -0,0 +1,45 @@
+//! Tests that do not require the feature flag `alloc`.
+//!
+//! Run them via `cargo test --no-default-features --test no_alloc`.
+
+use hifijson::{num, token::Lex, IterLexer, SliceLexer, Token};
+
+fn iter_of_slice(slice: &[u8]) -> impl Iterator<Item = Result<u8, ()>> + '_ {
+    slice.iter().copied().map(Ok)
+}
+
+#[test]
+fn num_ignore() {
+    use num::Lex;
+    let mut lexer = IterLexer::new(iter_of_slice(b"-1.5e3, 42"));
+    assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+    let parts = lexer.num_ignore().unwrap();
+    assert!(parts.neg && !parts.is_int());
+    assert_eq!(lexer.ws_token(), Some(Token::Comma));
+    assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+    assert!(lexer.num_ignore().unwrap().is_int());
+    assert_eq!(lexer.ws_token(), None);
+}
+
+#[test]
+fn num_foreach() {
+    use num::Lex;
+    let s = b"[123.25e-2]";
+    let mut buf = [0; 16];
+
+    let mut lexer = IterLexer::new(iter_of_slice(s));
+    assert_eq!(lexer.ws_token(), Some(Token::LSquare));
+    assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+    let mut len = 0;
+    let parts = lexer.num_foreach(|c| {
+        buf[len] = c;
+        len += 1
+    });
+    assert_eq!(&buf[..len], b"123.25e-2");
+    assert_eq!(lexer.ws_token(), Some(Token::RSquare));
+
+    let mut lexer = SliceLexer::new(&s[1..]);
+    assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+    assert_eq!(lexer.num_foreach(|_| ()), parts);
+    assert_eq!(lexer.as_slice(), b"]");
+}
@@ -7,6 +7,7 @@ pub fn from_slice<'a, T: Deserialize<'a>>(s: &'a [u8]) -> Result<T, hifijson::se
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn basic() {
     assert_eq!((), from_slice(b"null").unwrap());
     assert_eq!(true, from_slice(b"true").unwrap());
@@ -14,6 +15,7 @@ fn basic() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn numbers() {
     assert_eq!(0, from_slice(b"0").unwrap());
     assert_eq!(42, from_slice(b"42").unwrap());
@@ -24,6 +26,23 @@ fn numbers() {
 #[test]
 fn strings() {
     assert_eq!("asdf", from_slice::<String>(br#""asdf""#).unwrap());
+    assert_eq!("a\nb", from_slice::<String>(br#""a\nb""#).unwrap());
+}
+
+#[test]
+fn borrowed_strings() {
+    use std::collections::HashMap;
+    assert_eq!("asdf", from_slice::<&str>(br#""asdf""#).unwrap());
+    // strings with escape sequences cannot be borrowed
+    assert!(from_slice::<&str>(br#""a\nb""#).is_err());
+
+    let m: HashMap<&str, Vec<&str>> = from_slice(br#"{"a": ["b", "c"]}"#).unwrap();
+    assert_eq!(m, HashMap::from([("a", vec!["b", "c"])]));
+
+    let iter = br#""asdf""#.iter().copied().map(Ok::<_, ()>);
+    let mut lexer = hifijson::IterLexer::new(iter);
+    let s: String = hifijson::serde::exactly_one(&mut lexer).unwrap();
+    assert_eq!(s, "asdf");
 }
 
 #[test]
@@ -45,3 +64,227 @@ fn objects() {
     assert_eq!(a, from_slice(br#"{"a": 1}"#).unwrap());
     assert_eq!(b, from_slice(br#"{"a": 1, "b": 2}"#).unwrap());
 }
+
+#[test]
+fn overflow() {
+    use hifijson::serde::Error;
+    let big = "99999999999999999999";
+    let err = from_slice::<serde::de::IgnoredAny>(big.as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Number(n) if n == big));
+}
+
+#[test]
+fn builder() {
+    use hifijson::serde::{Deserializer, Error};
+    use std::collections::HashMap;
+    type Map = HashMap<String, Vec<u8>>;
+    let config = Deserializer::builder()
+        .max_depth(2)
+        .deny_duplicate_keys(true);
+    let parse = |s: &[u8]| {
+        config
+            .clone()
+            .build(&mut hifijson::SliceLexer::new(s))
+            .exactly_one::<Map>()
+    };
+
+    let m = parse(br#"{"a": [1], "b": []}"#).unwrap();
+    assert_eq!(
+        m,
+        HashMap::from([("a".into(), vec![1]), ("b".into(), vec![])])
+    );
+    let err = parse(br#"{"a": [1], "a": []}"#).unwrap_err();
+    assert!(matches!(err, Error::DuplicateKey(k) if k == "a"));
+    let err = parse(br#"{"a\n": [1], "a\u000a": []}"#).unwrap_err();
+    assert!(matches!(err, Error::DuplicateKey(k) if k == "a\n"));
+    let mut lexer = hifijson::SliceLexer::new(br#"{"a": [1]}"#);
+    let de = config.max_depth(1).build(&mut lexer);
+    let err = de.exactly_one::<Map>().unwrap_err();
+    assert!(matches!(err, Error::Parse(hifijson::Error::Depth)));
+
+    // by default, the last duplicate key wins
+    let m: Map = from_slice(br#"{"a": [1], "a": []}"#).unwrap();
+    assert_eq!(m, HashMap::from([("a".into(), vec![])]));
+
+    let mut lexer = hifijson::SliceLexer::new(b"[1] [2, 3] 4");
+    let mut de = Deserializer::builder().build(&mut lexer);
+    assert_eq!(de.one::<Vec<u8>>().unwrap(), [1]);
+    let rest: Vec<_> = de.iter::<Vec<u8>>().collect();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(rest[0].as_ref().unwrap(), &[2, 3]);
+    assert!(rest[1].is_err());
+}
+
+#[test]
+fn adjacently_tagged() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "t", content = "c")]
+    enum Shape {
+        Circle(f64),
+        Rect { w: u8, h: u8 },
+        Empty,
+    }
+    let circle = from_slice::<Shape>(br#"{"t": "Circle", "c": 1.5}"#).unwrap();
+    assert_eq!(circle, Shape::Circle(1.5));
+    let rect = from_slice::<Shape>(br#"{"c": {"h": 2, "w": 1}, "t": "Rect"}"#).unwrap();
+    assert_eq!(rect, Shape::Rect { w: 1, h: 2 });
+    assert_eq!(
+        from_slice::<Shape>(br#"{"t": "Empty"}"#).unwrap(),
+        Shape::Empty
+    );
+}
+
+#[test]
+fn bytes() {
+    #[derive(Debug, PartialEq)]
+    struct ByteBuf(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for ByteBuf {
+        fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            struct Visitor;
+            impl<'de> serde::de::Visitor<'de> for Visitor {
+                type Value = ByteBuf;
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                    Ok(ByteBuf(v.to_vec()))
+                }
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+                    Ok(ByteBuf(v))
+                }
+            }
+            d.deserialize_byte_buf(Visitor)
+        }
+    }
+
+    assert_eq!(
+        from_slice::<ByteBuf>(br#""hi""#).unwrap(),
+        ByteBuf(b"hi".to_vec())
+    );
+    // escape sequences are decoded
+    let s = br#""a\n\u00e4""#;
+    let expected = "a\n\u{e4}".as_bytes().to_vec();
+    assert_eq!(from_slice::<ByteBuf>(s).unwrap(), ByteBuf(expected));
+    assert!(from_slice::<ByteBuf>(b"1").is_err());
+
+    // strings without escape sequences can be borrowed
+    assert_eq!(from_slice::<&[u8]>(br#""hi""#).unwrap(), b"hi");
+    assert!(from_slice::<&[u8]>(br#""a\n""#).is_err());
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Payload<'a> {
+        data: &'a [u8],
+    }
+    let p: Payload = from_slice(br#"{"data": "raw"}"#).unwrap();
+    assert_eq!(p.data, b"raw");
+}
+
+#[test]
+fn externally_tagged() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum E {
+        A,
+        B(i32),
+        C { x: i32 },
+        D(i32, String),
+    }
+
+    assert_eq!(from_slice::<E>(br#""A""#).unwrap(), E::A);
+    assert_eq!(from_slice::<E>(br#"{"A": null}"#).unwrap(), E::A);
+    assert_eq!(from_slice::<E>(br#"{"B": 1}"#).unwrap(), E::B(1));
+    assert_eq!(
+        from_slice::<E>(br#"{"C": {"x": 2}}"#).unwrap(),
+        E::C { x: 2 }
+    );
+    let d = E::D(3, "d".into());
+    assert_eq!(from_slice::<E>(br#" { "D" : [3, "d"] } "#).unwrap(), d);
+
+    let es: Vec<E> = from_slice(br#"["A", {"B": 1}]"#).unwrap();
+    assert_eq!(es, [E::A, E::B(1)]);
+
+    assert!(from_slice::<E>(br#""X""#).is_err());
+    assert!(from_slice::<E>(br#"{"B": 1, "A": null}"#).is_err());
+    assert!(from_slice::<E>(br#"{"B" 1}"#).is_err());
+    assert!(from_slice::<E>(b"1").is_err());
+}
+
+#[test]
+fn tuples() {
+    let t: (i32, String) = from_slice(br#"[3, "d"]"#).unwrap();
+    assert_eq!(t, (3, "d".into()));
+    assert!(from_slice::<(i32, String)>(br#"[3, "d", 4]"#).is_err());
+    assert!(from_slice::<(i32, String)>(br#"[3]"#).is_err());
+    let ts: Vec<(u8, bool)> = from_slice(br#"[[1, true], [2, false]]"#).unwrap();
+    assert_eq!(ts, [(1, true), (2, false)]);
+}
+
+#[test]
+fn options() {
+    assert_eq!(from_slice::<Option<i32>>(b"null").unwrap(), None);
+    assert_eq!(from_slice::<Option<i32>>(b" 5 ").unwrap(), Some(5));
+    let v: Vec<Option<String>> = from_slice(br#"["a", null]"#).unwrap();
+    assert_eq!(v, [Some("a".to_string()), None]);
+    assert!(from_slice::<Option<i32>>(b"nul").is_err());
+    assert!(from_slice::<Option<i32>>(b"true").is_err());
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct S {
+        a: Option<u8>,
+        b: Option<Vec<u8>>,
+    }
+    let s: S = from_slice(br#"{"a": null, "b": [1]}"#).unwrap();
+    assert_eq!(
+        s,
+        S {
+            a: None,
+            b: Some(vec![1])
+        }
+    );
+}
+
+#[test]
+fn structs() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Nothing;
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Meters(f64);
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Name(String);
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Wrapped(Vec<Meters>);
+
+    assert_eq!(from_slice::<Nothing>(b"null").unwrap(), Nothing);
+    assert!(from_slice::<Nothing>(b"0").is_err());
+    assert_eq!(from_slice::<Meters>(b"1.5").unwrap(), Meters(1.5));
+    assert_eq!(from_slice::<Name>(br#""x""#).unwrap(), Name("x".into()));
+    let w = Wrapped(vec![Meters(1.0), Meters(2.0)]);
+    assert_eq!(from_slice::<Wrapped>(b"[1, 2]").unwrap(), w);
+    assert!(from_slice::<Meters>(b"[1.5]").is_err());
+}
+
+#[test]
+fn map_keys() {
+    use std::collections::{BTreeMap, HashMap};
+    let m: HashMap<u32, String> = from_slice(br#"{"1": "a", "23": "b"}"#).unwrap();
+    assert_eq!(
+        m,
+        HashMap::from([(1, "a".to_string()), (23, "b".to_string())])
+    );
+    let m: BTreeMap<i8, ()> = from_slice(br#"{"-1": null, "2": null}"#).unwrap();
+    assert_eq!(m.into_keys().collect::<Vec<_>>(), [-1, 2]);
+    let m: HashMap<u64, f64> = from_slice(br#"{"18446744073709551615": 1.5}"#).unwrap();
+    assert_eq!(m, HashMap::from([(u64::MAX, 1.5)]));
+
+    assert!(from_slice::<HashMap<u32, ()>>(br#"{"a": null}"#).is_err());
+    assert!(from_slice::<HashMap<u8, ()>>(br#"{"256": null}"#).is_err());
+    // numbers are only parsed from keys, not from values
+    assert!(from_slice::<HashMap<String, u32>>(br#"{"a": "1"}"#).is_err());
+
+    #[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+    enum Key {
+        A,
+    }
+    let m: HashMap<Key, u8> = from_slice(br#"{"A": 1}"#).unwrap();
+    assert_eq!(m, HashMap::from([(Key::A, 1)]));
+}
@@ -1,19 +1,25 @@
 use core::num::NonZeroUsize;
+use core::ops::Deref;
 use hifijson::token::Lex;
 use hifijson::value::{self, Value};
-use hifijson::{escape, ignore, num, str, Error, Expect, IterLexer, SliceLexer};
+use hifijson::{escape, ignore, num, str, Error, Expect, IterLexer, SliceLexer, Token};
 
 fn bol<Num, Str>(b: bool) -> Value<Num, Str> {
     Value::Bool(b)
 }
 
-fn num<Num, Str>(n: Num, dot: Option<usize>, exp: Option<usize>) -> Value<Num, Str> {
+fn num<Num: Deref<Target = str>, Str>(
+    n: Num,
+    dot: Option<usize>,
+    exp: Option<usize>,
+) -> Value<Num, Str> {
+    let neg = n.starts_with('-');
     let dot = dot.map(|i| NonZeroUsize::new(i).unwrap());
     let exp = exp.map(|i| NonZeroUsize::new(i).unwrap());
-    Value::Number((n, hifijson::num::Parts { dot, exp }))
+    Value::Number((n, hifijson::num::Parts { neg, dot, exp }))
 }
 
-fn int<Num, Str>(i: Num) -> Value<Num, Str> {
+fn int<Num: Deref<Target = str>, Str>(i: Num) -> Value<Num, Str> {
     num(i, None, None)
 }
 
@@ -71,7 +77,7 @@ fn basic() -> Result<(), Error> {
     fails_with(b"t", Expect::Value.into());
     fails_with(b"a", Expect::Value.into());
 
-    fails_with(b"true false", Expect::Eof.into());
+    fails_with(b"true false", Expect::TrailingByte(b'f').into());
 
     Ok(())
 }
@@ -159,10 +165,2735 @@ fn objects() -> Result<(), Error> {
     )?;
 
     fails_with(b"{", Expect::ValueOrEnd.into());
-    fails_with(b"{0", Expect::String.into());
+    let ignored = SliceLexer::new(b"{0").exactly_one(ignore::parse);
+    assert_eq!(ignored, Err(Expect::String.into()));
+    parse_fails_with(b"{0", Expect::StringFound(Token::DigitOrMinus).into());
     fails_with(br#"{"a" 1"#, Expect::Colon.into());
     fails_with(br#"{"a": 1"#, Expect::CommaOrEnd.into());
     fails_with(br#"{"a": 1,"#, Expect::Value.into());
 
+    let e = SliceLexer::new(b"{1:2}").exactly_one(value::parse_unbounded);
+    let e = e.unwrap_err();
+    assert_eq!(e, Expect::StringFound(Token::DigitOrMinus).into());
+    assert_eq!(e.to_string(), "string expected, found number");
+    let e = value::parse_relaxed(&mut SliceLexer::new(b"{[]: 2}")).unwrap_err();
+    assert_eq!(e, Expect::StringFound(Token::LSquare).into());
+
+    Ok(())
+}
+
+#[test]
+fn exact_decimal() {
+    use hifijson::num::LexWrite;
+    let dec = |s: &str| {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        let dec = num::to_exact_decimal(n, &parts);
+        (
+            String::from_utf8(dec.digits).unwrap(),
+            dec.exponent,
+            dec.negative,
+        )
+    };
+    assert_eq!(dec("0.001"), ("1".into(), -3, false));
+    assert_eq!(dec("1000"), ("1000".into(), 0, false));
+    assert_eq!(dec("1.5e-3"), ("15".into(), -4, false));
+    assert_eq!(dec("-42"), ("42".into(), 0, true));
+    assert_eq!(dec("-0"), ("".into(), 0, true));
+    assert_eq!(dec("12.5E+2"), ("125".into(), 1, false));
+}
+
+#[test]
+fn leading_zero() {
+    use hifijson::num::Lex;
+    let strict = |s: &[u8]| SliceLexer::new(s).num_ignore_strict();
+    assert_eq!(strict(b"007"), Err(num::Error::LeadingZero));
+    assert_eq!(strict(b"00"), Err(num::Error::LeadingZero));
+    assert_eq!(strict(b"-01"), Err(num::Error::LeadingZero));
+    assert!(strict(b"0").unwrap().is_int());
+    assert!(!strict(b"0.07").unwrap().is_int());
+    assert!(!strict(b"0e7").unwrap().is_int());
+
+    // the lenient lexer stops after the leading zero
+    let mut lexer = SliceLexer::new(b"007");
+    assert!(lexer.num_ignore().unwrap().is_int());
+    assert_eq!(lexer.as_slice(), b"07");
+}
+
+#[test]
+fn schema() {
+    use hifijson::schema::{check, Error, Schema};
+    use hifijson::token::Kind;
+
+    let point = Schema {
+        kind: Some(Kind::Object),
+        required: vec!["x".into(), "y".into()],
+        properties: vec![
+            ("x".into(), Schema::kind(Kind::Number)),
+            ("y".into(), Schema::kind(Kind::Number)),
+        ],
+        ..Schema::default()
+    };
+    let schema = Schema {
+        kind: Some(Kind::Object),
+        required: vec!["points".into()],
+        properties: vec![(
+            "points".into(),
+            Schema {
+                kind: Some(Kind::Array),
+                items: Some(Box::new(point)),
+                ..Schema::default()
+            },
+        )],
+        ..Schema::default()
+    };
+    let check = |s: &[u8]| SliceLexer::new(s).exactly_one(|t, l| check(&schema, t, l));
+
+    let conforming = br#"{"name": "path", "points": [{"x": 0, "y": 1}, {"y": 2, "x": 3}]}"#;
+    assert_eq!(check(conforming), Ok(()));
+
+    let wrong_kind = br#"{"points": [{"x": 0, "y": 1}, {"x": "3", "y": 2}]}"#;
+    let err = Error::Kind {
+        path: "/points/1/x".into(),
+        expected: Kind::Number,
+        found: Kind::String,
+    };
+    assert_eq!(check(wrong_kind), Err(err));
+
+    let missing = br#"{"points": [{"x": 0}]}"#;
+    let err = Error::Required {
+        path: "/points/0".into(),
+        key: "y".into(),
+    };
+    assert_eq!(check(missing), Err(err));
+
+    assert_eq!(
+        check(b"[]").unwrap_err().to_string(),
+        ": object expected, found array"
+    );
+    assert_eq!(check(b"{"), Err(Error::Parse(Expect::ValueOrEnd.into())));
+}
+
+#[test]
+fn to_f64() {
+    use hifijson::num::LexWrite;
+    let check = |s: &str| {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        let expected = s.parse::<f64>().unwrap();
+        assert_eq!(parts.to_f64(n).to_bits(), expected.to_bits(), "{}", s);
+    };
+
+    for i in -1000_i32..1000 {
+        check(&i.to_string());
+        check(&format!("{}.{:03}", i / 10, (i % 10).abs() * 7));
+        check(&format!("{}e{}", i, i % 30));
+        check(&format!("{}.5E-{}", i, i.abs() % 25));
+    }
+    let others = [
+        "-0",
+        "0.1",
+        "0.3",
+        "3.1415",
+        "1e22",
+        "1e23",
+        "1e-400",
+        "1e400",
+        "9007199254740993",
+        "18446744073709551615",
+        "18446744073709551616",
+        "123456789012345678901234567890",
+        "0.000000000000000000000000000001",
+        "2.2250738585072014e-308",
+    ];
+    others.iter().for_each(|s| check(s));
+}
+
+#[test]
+fn fits_int() {
+    use hifijson::num::LexWrite;
+    let fits = |s: &str| {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        (parts.fits_u64(n), parts.fits_i64(n))
+    };
+    assert_eq!(fits("0"), (true, true));
+    assert_eq!(fits("-0"), (true, true));
+    assert_eq!(fits("-1"), (false, true));
+    assert_eq!(fits("1.0"), (false, false));
+    assert_eq!(fits("1e3"), (false, false));
+    assert_eq!(fits("9223372036854775807"), (true, true));
+    assert_eq!(fits("9223372036854775808"), (true, false));
+    assert_eq!(fits("-9223372036854775808"), (false, true));
+    assert_eq!(fits("-9223372036854775809"), (false, false));
+    assert_eq!(fits("18446744073709551615"), (true, false));
+    assert_eq!(fits("18446744073709551616"), (false, false));
+    assert_eq!(fits("99999999999999999999"), (false, false));
+}
+
+#[test]
+fn num_split() {
+    use hifijson::num::{LexWrite, NumberComponents};
+    type Split<'a> = (
+        Option<char>,
+        &'a str,
+        Option<&'a str>,
+        Option<char>,
+        Option<&'a str>,
+    );
+    fn split(s: &str) -> Split<'_> {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        let c = parts.split(n);
+        (c.sign, c.int, c.frac, c.exp_sign, c.exp)
+    }
+    assert_eq!(split("0"), (None, "0", None, None, None));
+    assert_eq!(split("-12"), (Some('-'), "12", None, None, None));
+    assert_eq!(split("1.50"), (None, "1", Some("50"), None, None));
+    assert_eq!(split("-1e5"), (Some('-'), "1", None, None, Some("5")));
+    assert_eq!(split("1E+05"), (None, "1", None, Some('+'), Some("05")));
+    assert_eq!(
+        split("-0.5e-3"),
+        (Some('-'), "0", Some("5"), Some('-'), Some("3"))
+    );
+
+    let c = NumberComponents::default();
+    assert_eq!((c.sign, c.int, c.frac), (None, "", None));
+}
+
+#[test]
+fn exp_style() {
+    use hifijson::num::{ExpStyle, LexWrite};
+    fn style(s: &[u8]) -> Option<ExpStyle> {
+        let (n, parts) = SliceLexer::new(s).num_string().unwrap();
+        let style = parts.exp_style(n);
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert_eq!(lexer.ws_token(), Some(Token::DigitOrMinus));
+        let (n, parts) = lexer.num_string().unwrap();
+        assert_eq!(parts.exp_style(&n), style);
+        style
+    }
+    let style_of = |upper, sign| Some(ExpStyle { upper, sign });
+
+    assert_eq!(style(b"-12"), None);
+    assert_eq!(style(b"1.0"), None);
+    assert_eq!(style(b"1e5"), style_of(false, None));
+    assert_eq!(style(b"1.0E5"), style_of(true, None));
+    assert_eq!(style(b"-1e+5"), style_of(false, Some('+')));
+    assert_eq!(style(b"1E-05"), style_of(true, Some('-')));
+}
+
+#[test]
+fn for_each_line() -> Result<(), Error> {
+    use core::ops::ControlFlow;
+    let lines = b"{\"id\": 1}\n{\"id\": 2}\r\n\n  {\"id\": 3}\n{\"id\": 4}\n";
+
+    let mut lexer = IterLexer::new(iter_of_slice(lines));
+    let mut seen = Vec::new();
+    value::for_each_line(&mut lexer, |v| {
+        seen.push(v.to_string());
+        if seen.len() == 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    assert_eq!(seen, [r#"{"id":1}"#, r#"{"id":2}"#]);
+
+    // the remaining records have not been consumed
+    let rest = value::parse_unbounded(lexer.ws_token().unwrap(), &mut lexer)?;
+    assert_eq!(rest.to_string(), r#"{"id":3}"#);
+
+    let mut lexer = SliceLexer::new(b"1\n2 3\n4");
+    let mut count = 0;
+    let result = value::for_each_line(&mut lexer, |_| {
+        count += 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(result, Err(Expect::Newline.into()));
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
+#[test]
+fn raw_elements() -> Result<(), Error> {
+    use hifijson::array::raw_elements;
+    let json = br#"[null, 1.5e3 ,"a\"b", [[], {}] , {"k": [1, 2]}]"#;
+    let mut lexer = SliceLexer::new(json);
+    assert_eq!(lexer.ws_token(), Some(Token::LSquare));
+    let elems = raw_elements(&mut lexer).collect::<Result<Vec<_>, _>>()?;
+    assert!(lexer.as_slice().is_empty());
+
+    let joined = [&b"["[..], &elems.join(&b","[..]), b"]"].concat();
+    let reparse = |s| SliceLexer::new(s).exactly_one(value::parse_unbounded);
+    assert_eq!(reparse(&joined)?, reparse(json)?);
+
+    let mut lexer = SliceLexer::new(b"[]");
+    lexer.ws_token();
+    assert_eq!(raw_elements(&mut lexer).count(), 0);
+
+    let mut lexer = SliceLexer::new(b"[1 2]");
+    lexer.ws_token();
+    let elems: Vec<_> = raw_elements(&mut lexer).collect();
+    assert_eq!(elems, [Ok(&b"1"[..]), Err(Expect::CommaOrEnd.into())]);
+
+    Ok(())
+}
+
+#[test]
+fn strip_zeros() {
+    use hifijson::num::{LexWrite, StripZeros};
+    let strip = |s: &str| {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        StripZeros::new(n, &parts).to_string()
+    };
+    assert_eq!(strip("1.500"), "1.5");
+    assert_eq!(strip("1.0"), "1");
+    assert_eq!(strip("100"), "100");
+    assert_eq!(strip("-0.000"), "-0");
+    assert_eq!(strip("1.0e3"), "1e3");
+    assert_eq!(strip("1.050E-10"), "1.05E-10");
+    assert_eq!(strip("10e10"), "10e10");
+}
+
+/// Build a value from events.
+fn from_events<L: hifijson::LexAlloc>(lexer: L) -> Result<Value<L::Num, L::Str>, Error> {
+    use hifijson::events::{Event, Parser};
+    // partially built arrays and objects, with the key under which to insert them
+    let mut stack: Vec<(Option<L::Str>, Value<_, _>)> = Vec::new();
+    let mut key = None;
+    for event in Parser::new(lexer) {
+        let v = match event? {
+            Event::Key(k) => {
+                key = Some(k);
+                continue;
+            }
+            Event::StartArray => {
+                stack.push((key.take(), Value::Array(Vec::new())));
+                continue;
+            }
+            Event::StartObject => {
+                stack.push((key.take(), Value::Object(Vec::new())));
+                continue;
+            }
+            Event::EndArray | Event::EndObject => {
+                let (k, v) = stack.pop().unwrap();
+                key = k;
+                v
+            }
+            Event::Null => Value::Null,
+            Event::Bool(b) => Value::Bool(b),
+            Event::Number(n) => Value::Number(n),
+            Event::String(s) => Value::String(s),
+        };
+        match stack.last_mut() {
+            None => return Ok(v),
+            Some((_, Value::Array(arr))) => arr.push(v),
+            Some((_, Value::Object(obj))) => obj.push((key.take().unwrap(), v)),
+            Some(_) => unreachable!(),
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn events() -> Result<(), Error> {
+    let docs: [&[u8]; 6] = [
+        b"null",
+        b"[]",
+        b"{}",
+        br#"[null, true, false, "hello", 0, 3.1415, [1, 2], {"x": 1, "y": 2}]"#,
+        br#"{"a": [{"b": []}, {}], "c": {"d": [[1]]}}"#,
+        br#""a\nb""#,
+    ];
+    for doc in docs {
+        let parsed = SliceLexer::new(doc).exactly_one(value::parse_unbounded)?;
+        assert_eq!(from_events(SliceLexer::new(doc))?, parsed);
+        assert_eq!(from_events(IterLexer::new(iter_of_slice(doc)))?, parsed);
+    }
+
+    let errs: [(&[u8], Expect); 6] = [
+        (b"", Expect::Value),
+        (b"[", Expect::ValueOrEnd),
+        (b"[1 2", Expect::CommaOrEnd),
+        (b"[1,", Expect::Value),
+        (b"{0", Expect::String),
+        (br#"{"a" 1"#, Expect::Colon),
+    ];
+    for (doc, e) in errs {
+        assert_eq!(from_events(SliceLexer::new(doc)), Err(e.into()));
+    }
+
+    // deep nesting does not overflow the stack
+    let depth = 100_000;
+    let deep = [vec![b'['; depth], vec![b']'; depth]].concat();
+    let events = hifijson::events::Parser::new(SliceLexer::new(&deep));
+    assert_eq!(
+        events.map(|e| e.map(|_| 1)).sum::<Result<usize, _>>()?,
+        2 * depth
+    );
+
+    Ok(())
+}
+
+#[test]
+fn number_hook() {
+    #[derive(Debug, PartialEq)]
+    enum HookError {
+        Parse(Error),
+        TooLarge(String),
+    }
+    impl From<Error> for HookError {
+        fn from(e: Error) -> Self {
+            Self::Parse(e)
+        }
+    }
+    impl From<Expect> for HookError {
+        fn from(e: Expect) -> Self {
+            Self::Parse(e.into())
+        }
+    }
+
+    fn parse(s: &[u8]) -> Result<Value<&str, std::borrow::Cow<'_, str>>, HookError> {
+        SliceLexer::new(s).exactly_one(|token, lexer| {
+            value::parse_with_number_hook(token, lexer, &mut |n, parts| {
+                if parts.to_f64(n) < 1000.0 {
+                    Ok(())
+                } else {
+                    Err(HookError::TooLarge(n.to_string()))
+                }
+            })
+        })
+    }
+
+    let v = parse(br#"{"a": [1, 999.5, -5000], "b": 0}"#).unwrap();
+    assert_eq!(v.to_string(), r#"{"a":[1,999.5,-5000],"b":0}"#);
+
+    let err = HookError::TooLarge("1e3".into());
+    assert_eq!(parse(br#"{"a": [1, {"b": 1e3}]}"#), Err(err));
+    let err = HookError::Parse(Expect::CommaOrEnd.into());
+    assert_eq!(parse(b"[1 2"), Err(err));
+}
+
+#[test]
+fn parse_many() -> Result<(), Error> {
+    let input = b" 1 [2,\n3] \n {\"a\": 4}\n\"x\"\n";
+    let expected = ["1", "[2,3]", r#"{"a":4}"#, r#""x""#];
+
+    let many = |lexer: &mut SliceLexer| {
+        let vs = value::parse_many(lexer).collect::<Result<Vec<_>, _>>();
+        vs.map(|vs| vs.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+    };
+    assert_eq!(many(&mut SliceLexer::new(input))?, expected);
+    assert_eq!(many(&mut SliceLexer::new(b"  "))?, Vec::<String>::new());
+
+    let mut lexer = IterLexer::new(iter_of_slice(input));
+    let vs = value::parse_many(&mut lexer).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(vs.len(), expected.len());
+
+    // after an error, nothing more is yielded
+    let mut lexer = SliceLexer::new(b"1 -x 2");
+    let vs: Vec<_> = value::parse_many(&mut lexer).map(|v| v.is_ok()).collect();
+    assert_eq!(vs, [true, false]);
+
+    let input = b"1\n[2,\n3]\r\n\n{}";
+    let vs = value::parse_lines(&mut SliceLexer::new(input)).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(vs.len(), 3);
+    let mut lexer = IterLexer::new(iter_of_slice(input));
+    let vs = value::parse_lines(&mut lexer).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(vs.len(), 3);
+
+    let mut lexer = SliceLexer::new(b"1\n2 3\n4");
+    let vs: Vec<_> = value::parse_lines(&mut lexer).collect();
+    assert_eq!(vs.len(), 2);
+    assert_eq!(vs[1], Err(Expect::Newline.into()));
+
+    Ok(())
+}
+
+#[test]
+fn jsonp() -> Result<(), Error> {
+    use hifijson::jsonp;
+    fn unwrap<L: hifijson::LexAlloc>(mut lexer: L) -> Result<String, Error> {
+        let v = jsonp::unwrap(&mut lexer, value::parse_unbounded)?;
+        assert_eq!(lexer.ws_token(), None);
+        Ok(v.to_string())
+    }
+    let both = |s: &[u8]| {
+        let v = unwrap(SliceLexer::new(s));
+        assert_eq!(v, unwrap(IterLexer::new(iter_of_slice(s))));
+        v
+    };
+
+    assert_eq!(both(br#"cb({"a":1});"#)?, r#"{"a":1}"#);
+    assert_eq!(both(br#" jQuery.cb_1 ( [1, 2] ) "#)?, "[1,2]");
+    assert_eq!(both(br#"{"a":1}"#)?, r#"{"a":1}"#);
+    assert_eq!(both(b"null")?, "null");
+    assert_eq!(both(b" false ")?, "false");
+    assert_eq!(both(b"nullify(true)")?, "true");
+
+    assert_eq!(both(b"cb(1"), Err(Expect::RParen.into()));
+    assert_eq!(both(b"cb"), Err(Expect::Value.into()));
+    assert_eq!(both(b"nul"), Err(Expect::Value.into()));
+    Ok(())
+}
+
+#[test]
+fn diff() -> Result<(), Error> {
+    use hifijson::patch::{self, Op};
+    let parse = |s: &'static [u8]| SliceLexer::new(s).exactly_one(value::parse_unbounded);
+    let cases: [(&[u8], &[u8]); 6] = [
+        (b"1", b"2"),
+        (b"[1, 2, 3]", b"[1, 3]"),
+        (b"[1]", b"[0, 1, [2]]"),
+        (
+            br#"{"a": 1, "b": [true]}"#,
+            br#"{"b": [false, null], "c": {}}"#,
+        ),
+        (
+            br#"{"x": {"y": {"z": [1, {"w": "a"}]}}, "a/b": 1}"#,
+            br#"{"x": {"y": {"z": [1, {"w": "b", "v": 2}]}}, "a/b": 2, "~": 3}"#,
+        ),
+        (b"[]", b"{}"),
+    ];
+    for (from, to) in cases {
+        let (mut from, to) = (parse(from)?, parse(to)?);
+        let ops = value::diff(&from, &to);
+        patch::apply(&mut from, &ops).unwrap();
+        assert_eq!(from, to);
+    }
+
+    let (from, to) = (
+        parse(br#"{"a": [1, 2], "b": 0}"#)?,
+        parse(br#"{"a": [1], "c": 0}"#)?,
+    );
+    let ops: Vec<_> = value::diff(&from, &to)
+        .iter()
+        .map(|op| op.to_string())
+        .collect();
+    let expected = [
+        r#"{"op":"remove","path":"/a/1"}"#,
+        r#"{"op":"remove","path":"/b"}"#,
+        r#"{"op":"add","path":"/c","value":0}"#,
+    ];
+    assert_eq!(ops, expected);
+
+    assert_eq!(value::diff(&from, &from), []);
+    let mut v = parse(b"[1]")?;
+    let err = patch::Error::Path("/5".into());
+    assert_eq!(patch::apply(&mut v, &[Op::Remove("/5".into())]), Err(err));
+    // array indices must not have a sign or leading zeros
+    for path in ["/+0", "/00", "/01"] {
+        let ops = [Op::Replace(path.into(), v.clone())];
+        assert_eq!(
+            patch::apply(&mut v, &ops),
+            Err(patch::Error::Path(path.into()))
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn trailing_commas() -> Result<(), Error> {
+    let parse = |s: &[u8]| {
+        let v = SliceLexer::new(s).exactly_one(value::parse_trailing);
+        let iv = IterLexer::new(iter_of_slice(s)).exactly_one(value::parse_trailing);
+        let (v, iv) = (v.map(|v| v.to_string()), iv.map(|v| v.to_string()));
+        assert_eq!(v, iv);
+        v
+    };
+    assert_eq!(parse(b"[1, 2,]")?, "[1,2]");
+    assert_eq!(parse(b"[1, [2 , ] ,\n]")?, "[1,[2]]");
+    assert_eq!(parse(br#"{"a": 1,}"#)?, r#"{"a":1}"#);
+    assert_eq!(parse(b"[]")?, "[]");
+
+    assert_eq!(parse(b"[,]"), Err(Expect::Value.into()));
+    assert_eq!(parse(b"{,}"), Err(Expect::StringFound(Token::Comma).into()));
+    assert_eq!(parse(b"[1,,]"), Err(Expect::Value.into()));
+
+    // the default remains strict
+    fails_with(b"[1, 2,]", Expect::Value.into());
+    parse_fails_with(br#"{"a": 1,}"#, Expect::StringFound(Token::RCurly).into());
+    Ok(())
+}
+
+#[test]
+fn get_field_or() -> Result<(), Error> {
+    use hifijson::object::{get_field, get_field_or};
+    fn port<L: hifijson::LexAlloc>(mut lexer: L) -> Result<u16, Error> {
+        lexer.exactly_one(|token, lexer| {
+            get_field_or(token, lexer, "port", 80, |token, lexer| {
+                token.equals_or(Token::DigitOrMinus, Expect::Value)?;
+                Ok(lexer.num_string()?.0.parse().unwrap())
+            })
+        })
+    }
+    let port = |s: &[u8]| {
+        let p = port(SliceLexer::new(s));
+        assert_eq!(p, port(IterLexer::new(iter_of_slice(s))));
+        p
+    };
+
+    assert_eq!(port(br#"{"host": "a", "port": 8080, "x": [1]}"#)?, 8080);
+    assert_eq!(port(br#"{"host": "a"}"#)?, 80);
+    assert_eq!(port(br#"{"port": null, "host": "a"}"#)?, 80);
+    assert_eq!(port(br#"{"port": 1, "port": 2}"#)?, 1);
+    assert_eq!(port(br#"{"port": "1"}"#), Err(Expect::Value.into()));
+    assert_eq!(port(br#"[]"#), Err(Expect::Value.into()));
+
+    let mut lexer = SliceLexer::new(br#"{"a": null}"#);
+    let a = lexer.exactly_one(|t, l| get_field(t, l, "a", value::parse_unbounded))?;
+    assert_eq!(a, Some(Value::Null));
+    Ok(())
+}
+
+#[test]
+fn parse_many_limited() -> Result<(), Error> {
+    let input = br#"1 "two" [3] {"four": 4} null"#;
+    let mut lexer = IterLexer::new(iter_of_slice(input));
+    let first = value::parse_many_limited(&mut lexer, 2).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(first, [int("1"), Value::String("two")]);
+    let rest = value::parse_many(&mut lexer).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(rest.len(), 3);
+
+    let mut lexer = SliceLexer::new(input);
+    assert_eq!(value::parse_many_limited(&mut lexer, 10).count(), 5);
+    Ok(())
+}
+
+#[test]
+fn str_bounded() {
+    use hifijson::str::{Lex, LexAlloc};
+    let mut long = vec![b'a'; 1 << 20];
+    long.push(b'"');
+
+    assert_eq!(
+        SliceLexer::new(&long).str_ignore_bounded(1024),
+        Err(str::Error::TooLong)
+    );
+    assert_eq!(
+        SliceLexer::new(&long).str_string_bounded(1024),
+        Err(str::Error::TooLong)
+    );
+
+    // the iterator lexer stops reading shortly after the limit
+    let mut read = 0;
+    let iter = long.iter().map(|c| {
+        read += 1;
+        Ok::<_, ()>(*c)
+    });
+    let mut lexer = IterLexer::new(iter);
+    assert_eq!(lexer.str_string_bounded(1024), Err(str::Error::TooLong));
+    assert!(read <= 1026);
+
+    let s = br#"ab\u00e4\n""#;
+    assert_eq!(SliceLexer::new(s).str_ignore_bounded(10), Ok(()));
+    assert_eq!(
+        SliceLexer::new(s).str_string_bounded(10).as_deref(),
+        Ok("abä\n")
+    );
+    let mut lexer = IterLexer::new(iter_of_slice(s));
+    assert_eq!(lexer.str_string_bounded(10).as_deref(), Ok("abä\n"));
+    assert_eq!(
+        SliceLexer::new(s).str_ignore_bounded(9),
+        Err(str::Error::TooLong)
+    );
+    assert_eq!(
+        SliceLexer::new(s).str_string_bounded(9),
+        Err(str::Error::TooLong)
+    );
+}
+
+#[test]
+fn parse_expect() -> Result<(), Error> {
+    use hifijson::token::Kind;
+    use std::borrow::Cow;
+    fn expect(s: &[u8], kind: Kind) -> Result<Value<&str, Cow<'_, str>>, Error> {
+        let v = SliceLexer::new(s).exactly_one(|t, l| value::parse_expect(t, l, kind));
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        match (
+            &v,
+            lexer.exactly_one(|t, l| value::parse_expect(t, l, kind)),
+        ) {
+            (Ok(v), Ok(w)) => assert_eq!(v, &w),
+            (v, w) => assert_eq!(v.as_ref().err(), w.err().as_ref()),
+        }
+        v
+    }
+
+    assert_eq!(
+        expect(br#"{"a": 1}"#, Kind::Object)?,
+        obj([("a", int("1"))])
+    );
+    assert_eq!(expect(b"false", Kind::Bool)?, bol::<&str, &str>(false));
+    let err = |expected, found| Err(Error::UnexpectedKind { expected, found });
+    assert_eq!(
+        expect(b"[1, 2]", Kind::Object),
+        err(Kind::Object, Kind::Array)
+    );
+    assert_eq!(
+        expect(b"\"1\"", Kind::Number),
+        err(Kind::Number, Kind::String)
+    );
+    assert_eq!(expect(b"null", Kind::Array), err(Kind::Array, Kind::Null));
+    // kind mismatches are reported before parsing the value
+    assert_eq!(
+        expect(b"[1, ", Kind::Object),
+        err(Kind::Object, Kind::Array)
+    );
+    assert_eq!(expect(b"]", Kind::Array), Err(Expect::Value.into()));
+    Ok(())
+}
+
+#[test]
+fn str_chunks() -> Result<(), str::Error> {
+    use core::hash::Hasher;
+    use hifijson::str::{Lex, LexAlloc};
+    use std::collections::hash_map::DefaultHasher;
+
+    let mut s = String::from("\"");
+    for i in 0..10_000 {
+        s.push_str(["abcdefg", "\\u00e4", "\\n", "\\ud83d\\ude00", "ö"][i % 5]);
+    }
+    s.push('"');
+    let s = s.as_bytes();
+
+    let hash = |lexer: &mut dyn FnMut(&mut DefaultHasher) -> Result<(), str::Error>| {
+        let mut hasher = DefaultHasher::new();
+        lexer(&mut hasher).map(|()| hasher.finish())
+    };
+    let expected = hash(&mut |h| {
+        let mut lexer = SliceLexer::new(&s[1..]);
+        h.write(lexer.str_string()?.as_bytes());
+        Ok(())
+    })?;
+
+    let mut lexer = SliceLexer::new(&s[1..]);
+    let slice = hash(&mut |h| {
+        lexer.str_chunks(|c| {
+            h.write(c);
+            Ok::<_, str::Error>(())
+        })
+    })?;
+    assert_eq!(slice, expected);
+
+    let mut lexer = IterLexer::new(iter_of_slice(&s[1..]));
+    let iter = hash(&mut |h| {
+        lexer.str_chunks(|c| {
+            h.write(c);
+            Ok::<_, str::Error>(())
+        })
+    })?;
+    assert_eq!(iter, expected);
+
+    let mut max = 0;
+    SliceLexer::new(&s[1..]).str_chunks(|c| {
+        max = max.max(c.len());
+        Ok::<_, str::Error>(())
+    })?;
+    assert!(max <= 1024);
+
+    let chunks = |s: &[u8]| {
+        let mut chunks = Vec::new();
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        lexer
+            .str_chunks(|c| {
+                chunks.push(c.to_vec());
+                Ok::<_, str::Error>(())
+            })
+            .map(|()| chunks)
+    };
+    assert_eq!(chunks(br#"ab\nc""#)?, [&b"ab"[..], b"\n", b"c"]);
+    assert_eq!(chunks(br#"\ud83d\ude00""#)?, ["😀".as_bytes()]);
+    assert_eq!(
+        chunks(br#"\ud83d""#),
+        Err(escape::Error::ExpectedLowSurrogate.into())
+    );
+    assert_eq!(chunks(b"ab"), Err(str::Error::Eof));
+    Ok(())
+}
+
+#[test]
+fn to_f64_checked() {
+    use num::LexWrite;
+    let checked = |s: &[u8]| {
+        let (n, parts) = SliceLexer::new(s).num_string().unwrap();
+        assert_eq!(parts.fits_f64_exactly(n), num::to_f64_checked(n, &parts).1);
+        num::to_f64_checked(n, &parts)
+    };
+    assert_eq!(checked(b"0.5"), (0.5, true));
+    assert_eq!(checked(b"0.1"), (0.1, false));
+    assert_eq!(checked(b"-2.50000e0"), (-2.5, true));
+    assert_eq!(checked(b"0.0e999"), (0.0, true));
+    assert_eq!(checked(b"-0"), (-0.0, true));
+    assert_eq!(checked(b"9007199254740992"), (9007199254740992.0, true));
+    assert_eq!(checked(b"9007199254740993"), (9007199254740992.0, false));
+    assert_eq!(checked(b"1e22"), (1e22, true));
+    assert_eq!(checked(b"1e23"), (1e23, false));
+    assert_eq!(checked(b"1e400"), (f64::INFINITY, false));
+    assert_eq!(checked(b"1e-400"), (0.0, false));
+    assert_eq!(checked(b"5e-324"), (5e-324, false));
+    // 2^-60
+    let s = b"0.000000000000000000867361737988403547205962240695953369140625";
+    assert_eq!(checked(s), (2f64.powi(-60), true));
+    assert!(checked(b"867361737988403547205962240695953369140625e-60").1);
+    assert!(!checked(b"8673617379884035472059622406959533691406251e-61").1);
+}
+
+#[test]
+fn num_slice() {
+    let mut lexer = SliceLexer::new(b"-0.25E+2]");
+    let (num, parts) = lexer.num_slice().unwrap();
+    assert_eq!(num, b"-0.25E+2");
+    assert_eq!(parts.dot, NonZeroUsize::new(2));
+    assert_eq!(parts.exp, NonZeroUsize::new(5));
+    assert_eq!(lexer.as_slice(), b"]");
+
+    assert_eq!(
+        SliceLexer::new(b"-").num_slice(),
+        Err(num::Error::ExpectedDigit)
+    );
+    assert_eq!(
+        SliceLexer::new(b"").num_slice(),
+        Err(num::Error::ExpectedDigit)
+    );
+}
+
+#[test]
+fn accessors() -> Result<(), Error> {
+    let v = SliceLexer::new(br#"{"a": [null, "x", 1.5], "b": {}, "a": true}"#)
+        .exactly_one(value::parse_unbounded)?;
+    assert!(v.as_array().is_none());
+    assert_eq!(v.as_object().map(|o| o.len()), Some(3));
+    let a = v.get("a").unwrap();
+    assert_eq!(a.as_array().map(|a| a.len()), Some(3));
+    assert_eq!(a.get_index(0), Some(&Value::Null));
+    assert_eq!(a.get_index(1).and_then(Value::as_str), Some("x"));
+    let (n, parts) = a.get_index(2).and_then(Value::as_number).unwrap();
+    assert_eq!((&**n, parts.dot), ("1.5", NonZeroUsize::new(1)));
+    assert!(a.get_index(3).is_none());
+    assert!(a.get("a").is_none());
+    assert_eq!(
+        v.get("b").and_then(Value::as_object).map(|o| o.len()),
+        Some(0)
+    );
+    assert!(v.get("c").is_none());
+    assert_eq!(Value::<&str, &str>::Bool(true).as_bool(), Some(true));
+    assert!(Value::<&str, &str>::Null.as_bool().is_none());
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn canonicalize() {
+    fn canonical(s: &[u8]) -> String {
+        let mut out = Vec::new();
+        hifijson::fmt::canonicalize(&mut SliceLexer::new(s), &mut out).unwrap();
+        let mut iter_out = Vec::new();
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        hifijson::fmt::canonicalize(&mut lexer, &mut iter_out).unwrap();
+        assert_eq!(out, iter_out);
+        let out = String::from_utf8(out).unwrap();
+
+        let v = SliceLexer::new(s).exactly_one(value::parse_unbounded);
+        assert_eq!(v.unwrap().to_canonical().unwrap(), out);
+        out
+    }
+
+    // example from RFC 8785, section 3.2.2
+    let input = br#"{
+      "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+      "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+      "literals": [null, true, false]
+    }"#;
+    let output = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+    assert_eq!(canonical(input), output);
+
+    // example from RFC 8785, section 3.2.3
+    let input = r#"{
+      "€": "Euro Sign",
+      "\r": "Carriage Return",
+      "דּ": "Hebrew Letter Dalet With Dagesh",
+      "1": "One",
+      "😀": "Emoji: Grinning Face",
+      "\u0080": "Control",
+      "ö": "Latin Small Letter O With Diaeresis"
+    }"#;
+    let input = input.as_bytes();
+    let keys = ["\\r", "1", "\u{80}", "ö", "€", "😀", "\u{fb33}"];
+    let out = canonical(input);
+    let pos: Vec<_> = keys.iter().map(|k| out.find(k).unwrap()).collect();
+    assert!(pos.windows(2).all(|w| w[0] < w[1]));
+
+    let numbers =
+        b"[0, -0, -0.0e5, 1e21, 1e20, 123e-8, 1.5e-6, 1e-7, -5e-324, 1.7976931348623157e308]";
+    let out = "[0,0,0,1e+21,100000000000000000000,0.00000123,0.0000015,1e-7,-5e-324,1.7976931348623157e+308]";
+    assert_eq!(canonical(numbers), out);
+    assert_eq!(canonical(br#""\b\f\u001f""#), r#""\b\f\u001f""#);
+
+    let mut lexer = SliceLexer::new(b"1e400");
+    let result = hifijson::fmt::canonicalize(&mut lexer, &mut Vec::new());
+    assert!(matches!(result, Err(hifijson::fmt::Error::Number(n)) if n == "1e400"));
+    let v = SliceLexer::new(b"[1e400]").exactly_one(value::parse_unbounded);
+    let result = v.unwrap().to_canonical();
+    assert!(matches!(result, Err(hifijson::fmt::Error::Number(n)) if n == "1e400"));
+
+    // object members with equal keys keep their order
+    let v = SliceLexer::new(br#"{"b": 1, "a": 2, "b": 0}"#).exactly_one(value::parse_unbounded);
+    assert_eq!(v.unwrap().to_canonical().unwrap(), r#"{"a":2,"b":1,"b":0}"#);
+}
+
+#[test]
+fn into_owned() -> Result<(), Error> {
+    let input = br#"[null, true, -1.5e3, "a\nb", {"c": ["d"]}]"#.to_vec();
+    let v = SliceLexer::new(&input).exactly_one(value::parse_unbounded)?;
+    let v: Value<String, String> = v.into_owned();
+    drop(input);
+    let expected: Value<&str, &str> = arr([
+        Value::Null,
+        bol(true),
+        num("-1.5e3", Some(2), Some(4)),
+        Value::String("a\nb"),
+        obj([("c", arr([Value::String("d")]))]),
+    ]);
+    assert_eq!(v, expected);
+    Ok(())
+}
+
+#[test]
+fn ord() -> Result<(), Error> {
+    // groups of values that are ordered as equal, in ascending order
+    let sorted: &[&[&str]] = &[
+        &["null"],
+        &["false"],
+        &["true"],
+        &["-1e400"],
+        &["-2"],
+        &["-1.5"],
+        &["-0", "0", "0.0"],
+        &["1", "1.0", "10e-1"],
+        &["9007199254740992"],
+        &["9007199254740993"],
+        &["1e400"],
+        &[r#""""#],
+        &[r#""a""#],
+        &[r#""b""#],
+        &["[]"],
+        &["[1]", "[1.0]"],
+        &["[1, 2]"],
+        &["[2]"],
+        &["{}"],
+        &[r#"{"a": 1, "b": 1}"#],
+        &[r#"{"b": 1, "a": 1}"#],
+        &[r#"{"a": 2}"#],
+        &[r#"{"b": 0}"#],
+    ];
+    let values = sorted
+        .iter()
+        .enumerate()
+        .flat_map(|(i, group)| group.iter().map(move |s| (i, s)))
+        .map(|(i, s)| {
+            let v = SliceLexer::new(s.as_bytes()).exactly_one(value::parse_unbounded);
+            v.map(|v| (i, v))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, (gl, l)) in values.iter().enumerate() {
+        for (j, (gr, r)) in values.iter().enumerate() {
+            assert_eq!(value::cmp(l, r), gl.cmp(gr), "{} vs {}", l, r);
+            // equality compares numbers by their representation
+            assert_eq!(l == r, i == j);
+        }
+    }
+
+    let mut shuffled: Vec<_> = values.iter().rev().map(|(_, v)| v.clone()).collect();
+    shuffled.swap(3, 17);
+    shuffled.sort_by(value::cmp);
+    assert!(shuffled
+        .windows(2)
+        .all(|w| value::cmp(&w[0], &w[1]).is_le()));
+    Ok(())
+}
+
+#[test]
+fn progress() -> Result<(), Error> {
+    use hifijson::progress::ProgressLexer;
+    let mut input = b"[".to_vec();
+    for i in 0..10_000 {
+        input.extend_from_slice(format!(r#"{{"a\n": {}, "b": [true]}},"#, i).as_bytes());
+    }
+    input.extend_from_slice(b"null]");
+
+    let check = |reports: Vec<usize>| {
+        assert_eq!(reports.len(), input.len() / 1000);
+        for (i, r) in reports.into_iter().enumerate() {
+            assert!(
+                (i + 1) * 1000 <= r && r < (i + 1) * 1000 + 16,
+                "{}: {}",
+                i,
+                r
+            );
+        }
+    };
+
+    let mut reports = Vec::new();
+    let mut lexer = ProgressLexer::new(SliceLexer::new(&input), 1000, |n| reports.push(n));
+    let v = lexer.exactly_one(value::parse_unbounded)?;
+    assert_eq!(v.as_array().map(|a| a.len()), Some(10_001));
+    check(reports);
+
+    let mut reports = Vec::new();
+    let lexer = IterLexer::new(iter_of_slice(&input));
+    let mut lexer = ProgressLexer::new(lexer, 1000, |n| reports.push(n));
+    lexer.exactly_one(ignore::parse)?;
+    check(reports);
+    Ok(())
+}
+
+#[test]
+fn parse_unique() -> Result<(), Error> {
+    let unique = |s: &[u8]| {
+        let v = SliceLexer::new(s)
+            .exactly_one(value::parse_unique)
+            .map(|_| ());
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert_eq!(v, lexer.exactly_one(value::parse_unique).map(|_| ()));
+        v
+    };
+    unique(br#"{"a": 1, "b": {"a": 2}, "c": [{"a": 3}, {"a": 4}]}"#)?;
+    assert_eq!(
+        unique(br#"{"a": 1, "b": 2, "a": 3}"#),
+        Err(Error::DuplicateKey)
+    );
+    assert_eq!(
+        unique(br#"[{"x": {"a": 1, "a": 1}}]"#),
+        Err(Error::DuplicateKey)
+    );
+    // keys are compared after interpreting escape sequences
+    assert_eq!(
+        unique(br#"{"a": 1, "\u0061": 2}"#),
+        Err(Error::DuplicateKey)
+    );
+
+    // by default, duplicate keys are kept
+    let v = SliceLexer::new(br#"{"a": 1, "a": 2}"#).exactly_one(value::parse_unbounded)?;
+    assert_eq!(v, obj([("a", int("1")), ("a", int("2"))]));
+    Ok(())
+}
+
+#[test]
+fn kind_histogram() -> Result<(), Error> {
+    let s = br#"{"a": [null, true, false, 1, -2.5, "x"], "b": {"c": {}, "d": []}, "e": null}"#;
+    let counts = SliceLexer::new(s).exactly_one(ignore::kind_histogram)?;
+    assert_eq!(counts, [2, 2, 2, 1, 2, 3]);
+    let mut lexer = IterLexer::new(iter_of_slice(s));
+    assert_eq!(lexer.exactly_one(ignore::kind_histogram)?, counts);
+
+    let counts = SliceLexer::new(b"42").exactly_one(ignore::kind_histogram)?;
+    assert_eq!(counts, [0, 0, 1, 0, 0, 0]);
+    let err = SliceLexer::new(b"[1, }").exactly_one(ignore::kind_histogram);
+    assert_eq!(err, Err(Expect::Value.into()));
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn write_to() -> Result<(), Error> {
+    let s = br#"[null, true, false, -1.50e3, "a\"\n", [], {}, {"a": [1, {"b\\": []}]}]"#;
+    let v = SliceLexer::new(s).exactly_one(value::parse_unbounded)?;
+    let mut out = Vec::new();
+    v.write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), v.to_string());
+
+    let mut out = Vec::new();
+    v.write_pretty_to(&mut out, "\t").unwrap();
+    let expected = r#"[
+	null,
+	true,
+	false,
+	-1.50e3,
+	"a\"\n",
+	[],
+	{},
+	{
+		"a": [
+			1,
+			{
+				"b\\": []
+			}
+		]
+	}
+]"#;
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+    // pretty output can be parsed again
+    let v2 = SliceLexer::new(expected.as_bytes()).exactly_one(value::parse_unbounded)?;
+    assert_eq!(v, v2);
+    Ok(())
+}
+
+#[test]
+fn parse_limited() {
+    use hifijson::{value::Limits, LimitKind};
+    let s = br#"{"key": ["abcdefgh", [1, 2, 3]], "x": null}"#;
+    let limited = |limits| {
+        let v = SliceLexer::new(s).exactly_one(|t, l| value::parse_limited(t, l, limits));
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let w = lexer.exactly_one(|t, l| value::parse_limited(t, l, limits));
+        assert_eq!(v.as_ref().err(), w.as_ref().err());
+        v.map(|_| ())
+    };
+    let limit = |kind| Err(Error::Limit(kind));
+
+    assert_eq!(limited(Limits::default()), Ok(()));
+    let exact = Limits {
+        max_depth: 3,
+        max_total_bytes: s.len(),
+        max_values: 8,
+        max_string_len: 8,
+    };
+    assert_eq!(limited(exact), Ok(()));
+
+    let depth = Limits {
+        max_depth: 2,
+        ..exact
+    };
+    assert_eq!(limited(depth), limit(LimitKind::Depth));
+    let bytes = Limits {
+        max_total_bytes: 20,
+        ..exact
+    };
+    assert_eq!(limited(bytes), limit(LimitKind::TotalBytes));
+    let values = Limits {
+        max_values: 7,
+        ..exact
+    };
+    assert_eq!(limited(values), limit(LimitKind::Values));
+    let string_len = Limits {
+        max_string_len: 7,
+        ..exact
+    };
+    assert_eq!(limited(string_len), limit(LimitKind::StringLen));
+    // object keys are limited as well
+    let string_len = Limits {
+        max_string_len: 2,
+        ..exact
+    };
+    assert_eq!(limited(string_len), limit(LimitKind::StringLen));
+
+    // strings that exceed the remaining bytes are not read to their end
+    let bytes = Limits {
+        max_total_bytes: 12,
+        ..exact
+    };
+    assert_eq!(limited(bytes), limit(LimitKind::TotalBytes));
+
+    // numbers that exceed the remaining bytes are not read to their end
+    let num = [b'1'; 1000];
+    let mut read = 0;
+    let mut lexer = IterLexer::new(iter_of_slice(&num).inspect(|_| read += 1));
+    let bytes = Limits {
+        max_total_bytes: 10,
+        ..Limits::default()
+    };
+    let v = lexer.exactly_one(|t, l| value::parse_limited(t, l, bytes));
+    assert_eq!(v.map(|_| ()), limit(LimitKind::TotalBytes));
+    assert!(read < 16);
+}
+
+#[test]
+fn encode() {
+    let s = "a\"\\/\n\r\t\u{8}\u{c}\u{0}\u{1f} ä";
+    let expected = r#""a\"\\/\n\r\t\b\f\u0000\u001f ä""#;
+
+    let mut out = String::new();
+    str::encode(s, &mut out).unwrap();
+    assert_eq!(out, expected);
+
+    #[cfg(feature = "std")]
+    {
+        let mut out = Vec::new();
+        str::encode_to(s, &mut out).unwrap();
+        assert_eq!(out, expected.as_bytes());
+    }
+
+    // encoded strings are read back unchanged
+    let mut lexer = SliceLexer::new(expected.as_bytes());
+    let parsed = lexer.exactly_one(|token, lexer| {
+        assert_eq!(token, Token::Quote);
+        str::LexAlloc::str_string(lexer).map_err(Error::Str)
+    });
+    assert_eq!(parsed.unwrap(), s);
+}
+
+#[test]
+fn display_control() {
+    let json = r#""\u0014\u001f\n""#;
+    let mut lexer = SliceLexer::new(json.as_bytes());
+    let parsed = lexer.exactly_one(|_, lexer| str::LexAlloc::str_string(lexer).map_err(Error::Str));
+    let s = parsed.unwrap();
+    assert_eq!(s, "\u{14}\u{1f}\n");
+
+    let shown = str::Display::new(&*s).to_string();
+    assert_eq!(shown, json);
+    let mut lexer = SliceLexer::new(shown.as_bytes());
+    let reparsed =
+        lexer.exactly_one(|_, lexer| str::LexAlloc::str_string(lexer).map_err(Error::Str));
+    assert_eq!(reparsed.unwrap(), s);
+}
+
+#[test]
+fn num_f64_and_str() {
+    use num::LexWrite;
+    let inputs: [&[u8]; 6] = [b"0", b"-0", b"42", b"-1.25", b"6.02214076e23", b"1e-7"];
+    for s in inputs {
+        let (f, num, parts) = SliceLexer::new(s).num_f64_and_str().unwrap();
+        assert_eq!(num.as_bytes(), s);
+        assert_eq!(f, num.parse::<f64>().unwrap());
+        assert_eq!(f, parts.to_f64(num));
+
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let parsed = lexer.exactly_one(|_, lexer| lexer.num_f64_and_str().map_err(Error::Num));
+        let (f_iter, num_iter, _) = parsed.unwrap();
+        assert_eq!((f_iter, &*num_iter), (f, num));
+    }
+}
+
+#[test]
+fn get_field_bounded() {
+    use hifijson::object::get_field_bounded;
+    fn get<L: hifijson::LexAlloc>(mut lexer: L) -> Result<Option<bool>, Error> {
+        lexer.exactly_one(|token, lexer| {
+            get_field_bounded(token, lexer, "ok", 4, |token, _| Ok(token == Token::True))
+        })
+    }
+    let get = |s: &[u8]| {
+        let v = get(SliceLexer::new(s));
+        assert_eq!(v, get(IterLexer::new(iter_of_slice(s))));
+        v
+    };
+
+    assert_eq!(get(br#"{"a": 1, "abcd": 2, "ok": true}"#), Ok(Some(true)));
+    assert_eq!(get(br#"{"abcde": 1, "ok": true}"#), Err(Error::KeyTooLong));
+    // keys after the requested one are checked as well
+    assert_eq!(get(br#"{"ok": true, "abcde": 1}"#), Err(Error::KeyTooLong));
+    // escape sequences count by their length in the input
+    assert_eq!(get(br#"{"\u00e4": 1}"#), Err(Error::KeyTooLong));
+    // values are not limited
+    assert_eq!(get(br#"{"ok": false, "a": "abcdefgh"}"#), Ok(Some(false)));
+}
+
+#[test]
+fn str_lenient() {
+    fn lenient<L: str::LexAlloc>(mut lexer: L) -> Result<String, str::Error> {
+        lexer.str_string_lenient().map(|s| s.to_string())
+    }
+    let lenient = |s: &[u8]| {
+        let slice = lenient(SliceLexer::new(s));
+        assert_eq!(slice, lenient(IterLexer::new(iter_of_slice(s))));
+        slice
+    };
+
+    assert_eq!(lenient(b"a\tb\""), Ok("a\tb".into()));
+    assert_eq!(lenient(b"\x00\n\\n\x1f\""), Ok("\0\n\n\u{1f}".into()));
+    assert_eq!(lenient(b"a\tb"), Err(str::Error::Eof));
+    assert_eq!(
+        lenient(b"a\t\\x\""),
+        Err(str::Error::Escape(escape::Error::UnknownKind))
+    );
+
+    use str::Lex;
+    let mut bytes = Vec::new();
+    let mut lexer = SliceLexer::new(b"a\tb\"");
+    lexer.str_foreach_lenient(|c| bytes.push(c)).unwrap();
+    assert_eq!(bytes, b"a\tb");
+
+    // the strict variants still reject control characters
+    assert_eq!(
+        SliceLexer::new(b"a\tb\"").str_ignore(),
+        Err(str::Error::Control)
+    );
+    use str::LexAlloc;
+    assert_eq!(
+        SliceLexer::new(b"a\tb\"").str_string(),
+        Err(str::Error::Control)
+    );
+}
+
+#[test]
+fn is_fully_borrowed() {
+    let parse = |s: &'static [u8]| {
+        let mut lexer = SliceLexer::new(s);
+        lexer.exactly_one(value::parse_unbounded).unwrap()
+    };
+    assert!(parse(b"null").is_fully_borrowed());
+    assert!(parse(br#"[1.5e3, "abc", {"k": [true, "v"]}]"#).is_fully_borrowed());
+    assert!(!parse(br#""a\"b""#).is_fully_borrowed());
+    // escapes in keys
+    assert!(!parse(br#"{"\u00e4": 1}"#).is_fully_borrowed());
+    // escapes deep inside
+    assert!(!parse(br#"[1, {"k": ["v", "\t"]}]"#).is_fully_borrowed());
+}
+
+#[test]
+fn str_bytes_checked() {
+    use escape::Error::{ExpectedLowSurrogate, InvalidChar};
+    fn checked<L: str::LexWrite>(mut lexer: L) -> Result<Vec<u8>, str::Error>
+    where
+        L::Bytes: AsRef<[u8]>,
+    {
+        let mut bytes = Default::default();
+        lexer.str_bytes_checked(&mut bytes)?;
+        Ok(bytes.as_ref().to_vec())
+    }
+    let checked = |s: &[u8]| {
+        let slice = checked(SliceLexer::new(s));
+        assert_eq!(slice, checked(IterLexer::new(iter_of_slice(s))));
+        slice
+    };
+    let esc = |e| Err(str::Error::Escape(e));
+
+    assert_eq!(
+        checked(br#"a\uD801\uDC37b""#),
+        Ok(br#"a\uD801\uDC37b"#.to_vec())
+    );
+    assert_eq!(checked(br#"\n\u00e4""#), Ok(br#"\n\u00e4"#.to_vec()));
+    assert_eq!(checked(br#"\uD801""#), esc(ExpectedLowSurrogate));
+    assert_eq!(checked(br#"\uD801x\uDC37""#), esc(ExpectedLowSurrogate));
+    assert_eq!(checked(br#"\uD801\n""#), esc(ExpectedLowSurrogate));
+    assert_eq!(checked(br#"\uD801\uD801""#), esc(ExpectedLowSurrogate));
+    assert_eq!(checked(br#"\uDC37""#), esc(InvalidChar(0xDC37)));
+
+    // the unchecked variant lets lone surrogates through
+    let mut bytes = Default::default();
+    let mut lexer = SliceLexer::new(br#"\uD801""#);
+    assert_eq!(str::LexWrite::str_bytes(&mut lexer, &mut bytes), Ok(()));
+    assert_eq!(bytes, br#"\uD801"#);
+}
+
+#[test]
+fn entries_sorted() {
+    let mut lexer = SliceLexer::new(br#"{"c": 1, "a": [2], "b": null, "a": 3}"#);
+    let v = lexer.exactly_one(value::parse_unbounded).unwrap();
+    let sorted = v.entries_sorted().unwrap();
+    let sorted: Vec<_> = sorted.iter().map(|(k, v)| (&***k, v.to_string())).collect();
+    let expected = [("a", "[2]"), ("a", "3"), ("b", "null"), ("c", "1")];
+    assert_eq!(sorted, expected.map(|(k, v)| (k, v.to_string())));
+    // the original order is preserved
+    assert_eq!(v.to_string(), r#"{"c":1,"a":[2],"b":null,"a":3}"#);
+
+    let mut lexer = SliceLexer::new(b"[1, 2]");
+    let v = lexer.exactly_one(value::parse_unbounded).unwrap();
+    assert!(v.entries_sorted().is_none());
+}
+
+#[test]
+fn remaining() {
+    let mut lexer = SliceLexer::new(b"[1, 2] 3");
+    assert_eq!(lexer.remaining(), 8);
+    let token = lexer.ws_token().unwrap();
+    ignore::parse(token, &mut lexer).unwrap();
+    assert_eq!(lexer.remaining(), 2);
+    assert!(!lexer.is_empty());
+    let token = lexer.ws_token().unwrap();
+    ignore::parse(token, &mut lexer).unwrap();
+    assert_eq!(lexer.remaining(), 0);
+    assert!(lexer.is_empty());
+
+    let mut lexer = IterLexer::new(iter_of_slice(b"[1, 2] 3"));
+    let token = lexer.ws_token().unwrap();
+    ignore::parse(token, &mut lexer).unwrap();
+    assert!(!lexer.is_exhausted());
+    let token = lexer.ws_token().unwrap();
+    ignore::parse(token, &mut lexer).unwrap();
+    assert!(lexer.is_exhausted());
+
+    let mut lexer = IterLexer::new(iter_of_slice(b"[] x"));
+    assert!(lexer.exactly_one(ignore::parse).is_err());
+    assert!(!lexer.is_exhausted());
+}
+
+#[test]
+fn json5() {
+    fn parse<L: hifijson::Lex>(mut lexer: L) -> Result<String, Error> {
+        hifijson::json5::parse(&mut lexer).map(|v| v.to_string())
+    }
+    let parse = |s: &[u8]| {
+        let slice = parse(SliceLexer::new(s));
+        assert_eq!(slice, parse(IterLexer::new(iter_of_slice(s))));
+        slice
+    };
+
+    let doc = br#"
+    // A JSON5 document.
+    {
+      unquoted: 'and you can quote me on that',
+      singleQuotes: 'I can use "double quotes" here',
+      lineBreaks: "Look, Mom! \
+No \\n's!",
+      hexadecimal: 0xdecaf,
+      leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+      positiveSign: +1,
+      trailingComma: 'in objects', andIn: ['arrays',],
+      "backwardsCompatible": "with JSON",
+      /* a block
+       * comment */ $_id0: ['\'', '\x41B\u0043D', -0X10,], // end
+    }
+    "#;
+    let expected = concat!(
+        r#"{"unquoted":"and you can quote me on that","#,
+        r#""singleQuotes":"I can use \"double quotes\" here","#,
+        r#""lineBreaks":"Look, Mom! No \\n's!","#,
+        r#""hexadecimal":912559,"#,
+        r#""leadingDecimalPoint":0.8675309,"andTrailing":8675309,"#,
+        r#""positiveSign":1,"#,
+        r#""trailingComma":"in objects","andIn":["arrays"],"#,
+        r#""backwardsCompatible":"with JSON","#,
+        r#""$_id0":["'","ABCD",-16]}"#,
+    );
+    assert_eq!(parse(doc), Ok(expected.to_string()));
+
+    assert_eq!(
+        parse(b"[Infinity, -Infinity, +NaN]"),
+        Ok("[Infinity,-Infinity,NaN]".into())
+    );
+    assert_eq!(
+        parse(b"0x123456789abcdef0123"),
+        Ok("5373003642731685151011".into())
+    );
+    assert_eq!(parse(b"1.5e+3 /**/"), Ok("1.5e+3".into()));
+    // plain JSON is JSON5
+    assert_eq!(
+        parse(br#"{"a": [1, null, true]}"#),
+        Ok(r#"{"a":[1,null,true]}"#.into())
+    );
+
+    assert_eq!(parse(b"/* open"), Err(Expect::CommentEnd.into()));
+    assert_eq!(parse(b"[,]"), Err(Expect::Value.into()));
+    assert_eq!(parse(b"{a 1}"), Err(Expect::Colon.into()));
+    assert_eq!(parse(b"{0: 1}"), Err(Expect::String.into()));
+    assert_eq!(parse(b"'abc"), Err(str::Error::Eof.into()));
+    assert_eq!(parse(b"01"), Err(num::Error::ExpectedDigit.into()));
+    assert_eq!(parse(b"."), Err(num::Error::ExpectedDigit.into()));
+    assert_eq!(parse(b"0x"), Err(num::Error::ExpectedDigit.into()));
+    for n in [
+        "1Infinity",
+        ".Infinity",
+        "Infinity1",
+        "+-1",
+        "-+1",
+        "--1",
+        "+-Infinity",
+    ] {
+        assert_eq!(parse(n.as_bytes()), Err(num::Error::ExpectedDigit.into()));
+    }
+    assert_eq!(parse(b"[1, 2,,]"), Err(Expect::Value.into()));
+    assert_eq!(parse(b"[1 /* c */ , 2 // c\n , ]"), Ok("[1,2]".into()));
+    assert_eq!(parse(b"1 2"), Err(Expect::TrailingByte(b'2').into()));
+}
+
+#[test]
+fn str_string_reuse() {
+    let mut lexer = IterLexer::new(iter_of_slice(br#"abcdefgh" "x\ty" "a"#));
+    let mut scratch = String::new();
+    assert_eq!(lexer.str_string_reuse(&mut scratch), Ok("abcdefgh"));
+    let capacity = scratch.capacity();
+
+    lexer.ws_token();
+    assert_eq!(lexer.str_string_reuse(&mut scratch), Ok("x\ty"));
+    assert_eq!(scratch.capacity(), capacity);
+
+    lexer.ws_token();
+    assert_eq!(lexer.str_string_reuse(&mut scratch), Err(str::Error::Eof));
+    assert!(scratch.is_empty());
+    assert_eq!(scratch.capacity(), capacity);
+
+    let mut lexer = IterLexer::new(iter_of_slice(b"a\nb\""));
+    assert_eq!(
+        lexer.str_string_reuse(&mut scratch),
+        Err(str::Error::Control)
+    );
+    let mut lexer = IterLexer::new(iter_of_slice(b"\xff\""));
+    assert!(matches!(
+        lexer.str_string_reuse(&mut scratch),
+        Err(str::Error::Utf8(_))
+    ));
+    assert!(scratch.is_empty());
+}
+
+#[test]
+fn str_scan() {
+    fn string<L: str::LexAlloc>(mut lexer: L) -> Result<String, str::Error> {
+        lexer.str_string().map(|s| s.to_string())
+    }
+    let long = "x".repeat(100);
+    let inputs = [
+        format!(r#"{}""#, long),
+        format!(r#"{0}\n{0}\"{0}""#, long),
+        format!("{}\u{1f}{}\"", long, long),
+        format!(r#"{}ä{}"#, long, long),
+        format!("{}\t\\n\"", long),
+        String::new(),
+    ];
+    for input in inputs {
+        let slice = string(SliceLexer::new(input.as_bytes()));
+        assert_eq!(
+            slice,
+            string(IterLexer::new(iter_of_slice(input.as_bytes())))
+        );
+    }
+    let long_with = |s: &str| format!("{}{}{}\"", long, s, long);
+    assert_eq!(
+        string(SliceLexer::new(long_with("\u{1f}").as_bytes())),
+        Err(str::Error::Control)
+    );
+    let escaped = string(SliceLexer::new(long_with(r#"\""#).as_bytes()));
+    assert_eq!(escaped, Ok(format!("{}\"{}", long, long)));
+}
+
+#[test]
+fn ignore_bounded() {
+    let deep = |n| "[".repeat(n) + &"]".repeat(n);
+    let parse = |depth, s: &str| {
+        let slice = SliceLexer::new(s.as_bytes())
+            .exactly_one(|token, lexer| ignore::parse_bounded(depth, token, lexer));
+        let iter = IterLexer::new(iter_of_slice(s.as_bytes()))
+            .exactly_one(|token, lexer| ignore::parse_bounded(depth, token, lexer));
+        assert_eq!(slice, iter);
+        slice
+    };
+
+    assert_eq!(parse(100, &deep(10000)), Err(Error::Depth));
+    assert_eq!(parse(100, &deep(100)), Ok(()));
+    assert_eq!(parse(100, &deep(101)), Err(Error::Depth));
+    assert_eq!(parse(1, "1"), Ok(()));
+    assert_eq!(parse(2, r#"{"a": [], "b": 1}"#), Ok(()));
+    assert_eq!(parse(2, r#"{"a": [[]]}"#), Err(Error::Depth));
+    assert_eq!(parse(3, "[1, }"), Err(Expect::Value.into()));
+}
+
+#[test]
+fn validate_iter() {
+    let validate = |depth, s: &[u8]| {
+        let f = |token, lexer: &mut _| ignore::validate_iter(token, lexer, depth);
+        let slice = SliceLexer::new(s).exactly_one(f);
+        let f = |token, lexer: &mut _| ignore::validate_iter(token, lexer, depth);
+        assert_eq!(slice, IterLexer::new(iter_of_slice(s)).exactly_one(f));
+        slice
+    };
+
+    let inputs: [&[u8]; 16] = [
+        b"null",
+        b"[]",
+        b"{}",
+        br#"[1, "a", [true, {}], {"b": [null], "c": {"d": -1.5e3}}]"#,
+        b"[",
+        b"[1",
+        b"[1,",
+        b"[1,]",
+        b"[,]",
+        b"[1 2]",
+        b"{1: 2}",
+        br#"{"a" 1}"#,
+        br#"{"a": 1,}"#,
+        br#"{"a": 1]"#,
+        br#"[{"a": 1}}"#,
+        b"[nul]",
+    ];
+    for input in inputs {
+        let expected = SliceLexer::new(input).exactly_one(ignore::parse);
+        assert_eq!(validate(usize::MAX, input), expected);
+    }
+
+    let deep = |n| "[".repeat(n) + &"]".repeat(n);
+    assert_eq!(validate(0, b"1"), Ok(()));
+    assert_eq!(validate(0, b"[]"), Err(Error::Depth));
+    assert_eq!(validate(3, br#"[{"a": [1]}]"#), Ok(()));
+    assert_eq!(validate(2, br#"[{"a": [1]}]"#), Err(Error::Depth));
+    // this would overflow the stack when using recursion
+    assert_eq!(validate(100_000, deep(100_000).as_bytes()), Ok(()));
+    assert_eq!(validate(1000, deep(100_000).as_bytes()), Err(Error::Depth));
+}
+
+#[test]
+fn num_validate() {
+    use num::validate;
+    for s in [
+        "0",
+        "-0",
+        "42",
+        "-1.25",
+        "1e5",
+        "1E+5",
+        "0.5e-3",
+        "123456789012345678901234567890",
+    ] {
+        let parts = validate(s.as_bytes()).unwrap();
+        let (num, lexed) = SliceLexer::new(s.as_bytes()).num_slice().unwrap();
+        assert_eq!((num, parts), (s.as_bytes(), lexed));
+    }
+    assert_eq!(validate(b""), Err(num::Error::ExpectedDigit));
+    assert_eq!(validate(b"-"), Err(num::Error::ExpectedDigit));
+    assert_eq!(validate(b"+1"), Err(num::Error::ExpectedDigit));
+    assert_eq!(validate(b".5"), Err(num::Error::ExpectedDigit));
+    assert_eq!(validate(b"1e"), Err(num::Error::ExpectedDigit));
+    assert_eq!(validate(b"-01"), Err(num::Error::LeadingZero));
+    assert_eq!(validate(b"1 "), Err(num::Error::Trailing));
+    assert_eq!(validate(b"1.5.5"), Err(num::Error::Trailing));
+    assert_eq!(validate(b"1e5e5"), Err(num::Error::Trailing));
+}
+
+#[test]
+fn escape_decode() {
+    use escape::{decode, Error};
+    for (s, c) in [("\"", '"'), ("/", '/'), ("t", '\t'), ("u0041", 'A')] {
+        assert_eq!(decode(s.as_bytes()), Ok((c, s.len())));
+    }
+    assert_eq!(decode(br"uDBFF\uDFFFabc"), Ok(('\u{10FFFF}', 11)));
+    assert_eq!(decode(b""), Err(Error::Eof));
+    assert_eq!(decode(b"u00"), Err(Error::Eof));
+    assert_eq!(decode(b"u00g0"), Err(Error::InvalidHex));
+    assert_eq!(decode(b"uDC37"), Err(Error::InvalidChar(0xDC37)));
+    assert_eq!(decode(br"uD801\n"), Err(Error::ExpectedLowSurrogate));
+    assert_eq!(decode(b"uD801u0041"), Err(Error::ExpectedLowSurrogate));
+}
+
+#[test]
+fn entries_items() {
+    let v = SliceLexer::new(br#"{"a": [1, "x"], "a\n": null}"#).exactly_one(value::parse_unbounded);
+    let v = v.unwrap();
+    let entries: Vec<_> = v.entries().unwrap().collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1], ("a\n", &Value::Null));
+    let items: Vec<_> = entries[0].1.items().unwrap().collect();
+    assert_eq!(items[1].as_str(), Some("x"));
+    assert!(items[0].entries().is_none());
+    assert!(v.items().is_none());
+}
+
+#[test]
+fn into_variants() {
+    let v =
+        SliceLexer::new(br#"[null, false, "s", {"k": []}]"#).exactly_one(value::parse_unbounded);
+    let mut a = v.unwrap().into_array().unwrap();
+    let mut o = a.pop().unwrap().into_object().unwrap();
+    assert_eq!(o.pop().unwrap().1.into_array(), Some(Vec::new()));
+    assert_eq!(a.pop().unwrap().into_string().as_deref(), Some("s"));
+    assert_eq!(a.pop().unwrap().into_bool(), Some(false));
+    let null = a.pop().unwrap();
+    assert!(null.clone().into_bool().is_none());
+    assert!(null.clone().into_string().is_none());
+    assert!(null.clone().into_array().is_none());
+    assert!(null.into_object().is_none());
+}
+
+#[test]
+fn num_foreach() {
+    use num::{Lex, LexWrite};
+    let inputs: [&[u8]; 5] = [b"0", b"-12", b"3.25", b"1e+9", b"-0.5E-3"];
+    for s in inputs {
+        let mut streamed = Vec::new();
+        let parts = SliceLexer::new(s)
+            .num_foreach(|c| streamed.push(c))
+            .unwrap();
+        let mut bytes = Default::default();
+        let parts_bytes = SliceLexer::new(s).num_bytes(&mut bytes).unwrap();
+        assert_eq!((&streamed[..], &parts), (bytes, &parts_bytes));
+
+        let mut streamed_iter = Vec::new();
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let parts_iter = lexer.exactly_one(|_, lexer| {
+            lexer
+                .num_foreach(|c| streamed_iter.push(c))
+                .map_err(Error::Num)
+        });
+        assert_eq!((streamed_iter, parts_iter.unwrap()), (streamed, parts));
+    }
+}
+
+#[test]
+fn num_bytes_bounded() {
+    use num::LexWrite;
+    let long = [b'1'; 10000];
+    let mut bytes = Default::default();
+    let slice = SliceLexer::new(&long).num_bytes_bounded(&mut bytes, 64);
+    assert_eq!(slice, Err(num::Error::TooLong));
+
+    let mut lexer = IterLexer::new(iter_of_slice(&long));
+    let mut bytes = Vec::new();
+    let iter = lexer.exactly_one(|_, lexer| {
+        let bounded = lexer.num_bytes_bounded(&mut bytes, 64);
+        bounded.map_err(Error::Num)
+    });
+    assert_eq!(iter, Err(Error::Num(num::Error::TooLong)));
+    // reading stops early
+    assert_eq!(bytes.len(), 64);
+
+    let exact = |s: &[u8], max| {
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let mut bytes = Vec::new();
+        lexer.ws_token();
+        let iter = lexer.num_bytes_bounded(&mut bytes, max);
+        let mut slice = Default::default();
+        assert_eq!(SliceLexer::new(s).num_bytes_bounded(&mut slice, max), iter);
+        iter
+    };
+    assert!(exact(b"1.5e10", 64).is_ok());
+    assert!(exact(b"-1.5e+10", 8).is_ok());
+    assert_eq!(exact(b"-1.5e+10", 7), Err(num::Error::TooLong));
+    assert_eq!(exact(b"-0", 1), Err(num::Error::TooLong));
+}
+
+#[test]
+fn slice_reset() {
+    let buffers: [&[u8]; 2] = [br#"{"a": [1, 2]} trailing"#, br#" "b" "#];
+    let mut lexer = SliceLexer::new(buffers[0]);
+    let v = lexer
+        .ws_token()
+        .map(|t| value::parse_unbounded(t, &mut lexer));
+    assert_eq!(v.unwrap().unwrap().to_string(), r#"{"a":[1,2]}"#);
+    assert!(!lexer.is_empty());
+
+    lexer.reset(buffers[1]);
+    assert_eq!(lexer.remaining(), buffers[1].len());
+    let v = lexer.exactly_one(value::parse_unbounded).unwrap();
+    assert_eq!(v.as_str(), Some("b"));
+    assert!(lexer.is_empty());
+}
+
+#[test]
+fn peek_token() {
+    use hifijson::token::Kind;
+    let input = br#" null true false -1 "s" [ { ] , x"#;
+    let kinds = [Kind::Null, Kind::Bool, Kind::Bool, Kind::Number];
+    let kinds = kinds
+        .into_iter()
+        .chain([Kind::String, Kind::Array, Kind::Object]);
+    let kinds: Vec<_> = kinds.map(Some).chain([None, None, None]).collect();
+
+    let mut slice = SliceLexer::new(input);
+    let mut iter = IterLexer::new(iter_of_slice(input));
+    for kind in kinds {
+        assert_eq!(slice.peek_token(), kind);
+        assert_eq!(iter.peek_token(), kind);
+        // peeking twice yields the same kind
+        assert_eq!(slice.peek_token(), kind);
+        assert_eq!(iter.peek_token(), kind);
+        let token = slice.ws_token().unwrap();
+        assert_eq!(token.kind(), kind);
+        assert_eq!(iter.ws_token().unwrap().kind(), kind);
+        if token == Token::DigitOrMinus {
+            use num::Lex;
+            slice.num_ignore().unwrap();
+            iter.num_ignore().unwrap();
+        } else if token == Token::Quote {
+            str::Lex::str_ignore(&mut slice).unwrap();
+            str::Lex::str_ignore(&mut iter).unwrap();
+        }
+    }
+    assert_eq!(slice.peek_token(), None);
+    assert_eq!(iter.peek_token(), None);
+}
+
+#[test]
+fn parse_one() {
+    let input = br#"{"a": 1} [true]"null" 4"#;
+    let mut rest = &input[..];
+    let mut values = Vec::new();
+    while !rest.iter().all(u8::is_ascii_whitespace) {
+        let (v, r) = SliceLexer::new(rest)
+            .parse_prefix(value::parse_unbounded)
+            .unwrap();
+        values.push(v.to_string());
+        rest = r;
+    }
+    assert_eq!(values, [r#"{"a":1}"#, "[true]", r#""null""#, "4"]);
+
+    let mut lexer = IterLexer::new(iter_of_slice(input));
+    for v in &values {
+        assert_eq!(
+            &lexer.parse_one(value::parse_unbounded).unwrap().to_string(),
+            v
+        );
+    }
+    assert_eq!(
+        lexer.parse_one(value::parse_unbounded),
+        Err(Expect::Value.into())
+    );
+
+    let parsed = SliceLexer::new(b"[1 2]").parse_prefix(ignore::parse);
+    assert_eq!(parsed.err(), Some(Expect::CommaOrEnd.into()));
+}
+
+#[test]
+fn parse_iter() {
+    let inputs: [&[u8]; 16] = [
+        b"null",
+        b"[]",
+        b"{}",
+        br#"[1, "a", [], {}, [[true]], {"a": {"b": [false]}}]"#,
+        br#"{"a": 1, "a": [null, {"c": "d\n"}], "": {}}"#,
+        b"[1, 2",
+        b"[1 2]",
+        b"[1,]",
+        b"[,]",
+        b"[",
+        br#"{"a"}"#,
+        br#"{"a": }"#,
+        br#"{"a": 1,}"#,
+        b"{1: 2}",
+        b"[1]]",
+        b"]",
+    ];
+    for input in inputs {
+        let iter = SliceLexer::new(input).exactly_one(value::parse_iter);
+        assert_eq!(
+            iter,
+            SliceLexer::new(input).exactly_one(value::parse_unbounded)
+        );
+        let mut lexer = IterLexer::new(iter_of_slice(input));
+        assert_eq!(
+            lexer.exactly_one(value::parse_iter),
+            iter.map(Value::into_owned)
+        );
+    }
+
+    let depth = 100_000;
+    let json = "[{\"a\":".repeat(depth) + "0" + &"}]".repeat(depth);
+    let v = SliceLexer::new(json.as_bytes())
+        .exactly_one(value::parse_iter)
+        .unwrap();
+    let mut levels = 0;
+    let mut inner = &v;
+    while let Some(arr) = inner.as_array() {
+        inner = &arr[0].as_object().unwrap()[0].1;
+        levels += 1;
+    }
+    assert_eq!((levels, inner.to_string()), (depth, "0".to_string()));
+    v.drop_iter();
+}
+
+#[test]
+fn parse_sorted() {
+    let sorted = |s| {
+        SliceLexer::new(s)
+            .exactly_one(value::parse_sorted)
+            .unwrap()
+            .to_string()
+    };
+    let unsorted = |s| {
+        SliceLexer::new(s)
+            .exactly_one(value::parse_unbounded)
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(sorted(br#"{"b":1,"a":2}"#), r#"{"a":2,"b":1}"#);
+    assert_eq!(unsorted(br#"{"b":1,"a":2}"#), r#"{"b":1,"a":2}"#);
+    // equal keys keep their order
+    let dup = br#"[{"b": 1, "a": 2, "b": 0, "a": [{"y": 1, "x": 2}]}]"#;
+    let expected = r#"[{"a":2,"a":[{"x":2,"y":1}],"b":1,"b":0}]"#;
+    assert_eq!(sorted(dup), expected);
+
+    let l = SliceLexer::new(br#"{"x": {"q": 1, "p": 2}, "y": 3}"#).exactly_one(value::parse_sorted);
+    let r = SliceLexer::new(br#"{"y": 3, "x": {"p": 2, "q": 1}}"#).exactly_one(value::parse_sorted);
+    assert_eq!(l.unwrap(), r.unwrap());
+}
+
+#[test]
+fn parse_with_key() {
+    use hifijson::LexAlloc;
+    fn string_key<L: LexAlloc>(token: Token, lexer: &mut L) -> Result<L::Str, Error> {
+        if token != Token::Quote {
+            return Err(Expect::StringFound(token).into());
+        }
+        str::LexAlloc::str_string(lexer).map_err(Error::Str)
+    }
+    let inputs: [&[u8]; 6] = [
+        br#"{"a": [1, {"b": null}], "c": "d"}"#,
+        b"[{}, []]",
+        b"{1: 2}",
+        br#"{"a" 1}"#,
+        br#"{"a": 1,}"#,
+        b"[1,]",
+    ];
+    for input in inputs {
+        let mut lexer = SliceLexer::new(input);
+        let with_key = lexer.exactly_one(|t, l| value::parse_with_key(t, l, &mut string_key));
+        assert_eq!(
+            with_key,
+            SliceLexer::new(input).exactly_one(value::parse_unbounded)
+        );
+    }
+
+    // numeric keys
+    let mut num_key = |token: Token, lexer: &mut IterLexer<_, _>| {
+        token.equals_or(Token::DigitOrMinus, Expect::String)?;
+        let (n, parts) = num::LexWrite::num_string(lexer).map_err(Error::Num)?;
+        Ok(if parts.is_int() { n } else { n + "!" })
+    };
+    let mut lexer = IterLexer::new(iter_of_slice(br#"{1: [{2.5: "a"}], 3: {}}"#));
+    let v = lexer.exactly_one(|t, l| value::parse_with_key(t, l, &mut num_key));
+    let v = v.unwrap();
+    assert_eq!(v.to_string(), r#"{"1":[{"2.5!":"a"}],"3":{}}"#);
+    let mut lexer = IterLexer::new(iter_of_slice(br#"{"1": 2}"#));
+    let v = lexer.exactly_one(|t, l| value::parse_with_key(t, l, &mut num_key));
+    assert_eq!(v, Err(Expect::String.into()));
+}
+
+#[test]
+fn num_neg() {
+    use num::{Lex, LexWrite};
+    let inputs: [(&[u8], bool); 6] = [
+        (b"0", false),
+        (b"-0", true),
+        (b"12e-3", false),
+        (b"-1.5E+2", true),
+        (b"1-", false),
+        (b"-", true),
+    ];
+    for (s, neg) in inputs {
+        let ignored = SliceLexer::new(s).num_ignore().map(|p| p.neg);
+        let slice = SliceLexer::new(s).num_string().map(|(_, p)| p.neg);
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        lexer.ws_token();
+        let iter = lexer.num_string().map(|(_, p)| p.neg);
+        assert_eq!((&ignored, &slice), (&iter, &iter));
+        assert_eq!(iter.unwrap_or(neg), neg);
+    }
+}
+
+#[test]
+fn trailing_byte() {
+    let err = |s: &[u8]| SliceLexer::new(s).exactly_one(ignore::parse).unwrap_err();
+    assert_eq!(err(b"true false"), Expect::TrailingByte(b'f').into());
+    assert_eq!(err(b"{} \n ]"), Expect::TrailingByte(b']').into());
+    let mut lexer = IterLexer::new(iter_of_slice(b"1 \xff"));
+    let e = lexer.exactly_one(ignore::parse).unwrap_err();
+    assert_eq!(e, Expect::TrailingByte(0xff).into());
+    assert_eq!(e.to_string(), r"end of file expected, found `\xff`");
+}
+
+#[test]
+fn str_raw() {
+    fn raw(s: &[u8]) -> Result<(&[u8], &[u8]), str::Error> {
+        let mut lexer = SliceLexer::new(s);
+        assert_eq!(lexer.ws_token(), Some(Token::Quote));
+        lexer.str_raw().map(|r| (r, lexer.as_slice()))
+    }
+    assert_eq!(raw(br#""""#), Ok((&b""[..], &b""[..])));
+    let s = r#""a\"b\\ä\uD83D\uDE00😀" rest"#.as_bytes();
+    assert_eq!(raw(s), Ok((&s[1..s.len() - 6], &b" rest"[..])));
+    assert_eq!(raw(b"\"\xff\""), Ok((&b"\xff"[..], &b""[..])));
+    assert_eq!(raw(br#""abc"#), Err(str::Error::Eof));
+    assert_eq!(raw(b"\"a\nb\""), Err(str::Error::Control));
+    assert!(raw(br#""\x""#).is_err());
+    assert!(raw(br#""\u00g0""#).is_err());
+}
+
+#[test]
+fn exactly_one_whitespace() {
+    let inputs: [&[u8]; 4] = [b"42\n", b"42  ", b" \t42\r\n \n", b"[]\n\n"];
+    for s in inputs {
+        assert!(SliceLexer::new(s).exactly_one(ignore::parse).is_ok());
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert!(lexer.exactly_one(ignore::parse).is_ok());
+        assert!(lexer.is_exhausted());
+    }
+
+    let inputs: [(&[u8], u8); 3] = [(b"42  x", b'x'), (b"42\n\n43", b'4'), (b"42 \x0c", 0x0c)];
+    for (s, c) in inputs {
+        let err = Error::Token(Expect::TrailingByte(c));
+        assert_eq!(SliceLexer::new(s).exactly_one(ignore::parse), Err(err));
+    }
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn to_bigint() {
+    use num::LexWrite;
+    let big = |s: &str| {
+        let (n, parts) = SliceLexer::new(s.as_bytes()).num_string().unwrap();
+        parts.to_bigint(n).map(|i| i.to_string())
+    };
+    let i128_max = i128::MAX.to_string();
+    assert_eq!(big(&i128_max).as_deref(), Some(&*i128_max));
+    let beyond = "1".to_string() + &"0".repeat(50);
+    assert_eq!(big(&beyond), Some(beyond.clone()));
+    assert_eq!(big("-0").as_deref(), Some("0"));
+    assert_eq!(big("1.0"), None);
+    assert_eq!(big("1e3"), None);
+}
+
+#[test]
+fn parse_counting() {
+    use ignore::Counts;
+    let json = br#"[null, true, false, "hello", 0, 3.1415, [1, 2], {"x": 1, "y": 2}]"#;
+    let counts = SliceLexer::new(json).exactly_one(ignore::parse_counting);
+    let expected = Counts {
+        values: 13,
+        arrays: 2,
+        objects: 1,
+        strings: 1,
+        numbers: 6,
+    };
+    assert_eq!(counts, Ok(expected));
+    let mut lexer = IterLexer::new(iter_of_slice(json));
+    assert_eq!(lexer.exactly_one(ignore::parse_counting), Ok(expected));
+
+    let scalar = SliceLexer::new(b"\"s\"").exactly_one(ignore::parse_counting);
+    let expected = Counts {
+        values: 1,
+        strings: 1,
+        ..Counts::default()
+    };
+    assert_eq!(scalar, Ok(expected));
+    let invalid = SliceLexer::new(b"[1, }").exactly_one(ignore::parse_counting);
+    assert_eq!(invalid, Err(Expect::Value.into()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn from_read() {
+    let item = br#"{"a": [1, 2.5, "x\ny"], "b": null}"#;
+    let mut json = b"[".to_vec();
+    for i in 0..1000 {
+        if i > 0 {
+            json.push(b',');
+        }
+        json.extend_from_slice(item);
+    }
+    json.push(b']');
+    assert!(json.len() > 16 * 1024);
+
+    let slice = SliceLexer::new(&json)
+        .exactly_one(value::parse_unbounded)
+        .unwrap();
+    let mut lexer = IterLexer::from_read(std::io::Cursor::new(&json));
+    let read = lexer.exactly_one(value::parse_unbounded).unwrap();
+    assert_eq!(slice, read);
+    assert!(lexer.error.is_none());
+}
+
+#[test]
+fn as_numbers() {
+    let get = |s: &[u8]| {
+        let v = SliceLexer::new(s)
+            .exactly_one(value::parse_unbounded)
+            .unwrap();
+        (v.as_i64(), v.as_u64(), v.as_f64())
+    };
+    assert_eq!(get(b"1"), (Some(1), Some(1), Some(1.0)));
+    assert_eq!(get(b"-1"), (Some(-1), None, Some(-1.0)));
+    assert_eq!(get(b"-0"), (Some(0), Some(0), Some(-0.0)));
+    assert_eq!(get(b"1.5"), (None, None, Some(1.5)));
+    assert_eq!(get(b"1e2"), (None, None, Some(100.0)));
+    assert_eq!(
+        get(b"18446744073709551615"),
+        (None, Some(u64::MAX), Some(1.8446744073709552e19))
+    );
+    assert_eq!(
+        get(b"-9223372036854775808"),
+        (Some(i64::MIN), None, Some(-9.223372036854776e18))
+    );
+    assert_eq!(get(b"18446744073709551616").0, None);
+    assert_eq!(get(b"18446744073709551616").1, None);
+    assert_eq!(get(br#""1""#), (None, None, None));
+}
+
+#[test]
+fn display_ascii() {
+    use str::Display;
+    let cases = [
+        ("\u{e9}", r#""\u00e9""#),
+        ("\u{1F600}", r#""\ud83d\ude00""#),
+        ("a\u{7f}\n\u{80}", "\"a\u{7f}\\n\\u0080\""),
+        ("\u{FFFF}\u{10FFFF}", r#""\uffff\udbff\udfff""#),
+    ];
+    for (s, expected) in cases {
+        let ascii = Display::ascii(s).to_string();
+        assert_eq!(ascii, expected);
+        assert!(ascii.is_ascii());
+        let parsed = SliceLexer::new(ascii.as_bytes()).exactly_one(value::parse_unbounded);
+        assert_eq!(parsed.unwrap().as_str(), Some(s));
+    }
+}
+
+#[test]
+fn parse_relaxed() {
+    let relaxed = |s: &[u8]| {
+        let mut lexer = SliceLexer::new(s);
+        let v = value::parse_relaxed(&mut lexer).map(|v| v.to_string());
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert_eq!(value::parse_relaxed(&mut lexer).map(|v| v.to_string()), v);
+        v
+    };
+    assert_eq!(relaxed(br#"['a', "b"]"#).unwrap(), r#"["a","b"]"#);
+    assert_eq!(
+        relaxed(br#"{'k': '\u00e4\n', "l": ['"']}"#).unwrap(),
+        r#"{"k":"ä\n","l":["\""]}"#
+    );
+    assert_eq!(relaxed(b"''").unwrap(), r#""""#);
+
+    // without single quotes, this behaves like `parse_unbounded`
+    let inputs: [&[u8]; 8] = [
+        br#"{"a": [1, null, {}], "b": []}"#,
+        b"[1 2]",
+        b"[1,]",
+        b"[",
+        br#"{"a" 1}"#,
+        b"{1: 2}",
+        b"",
+        b"]",
+    ];
+    for s in inputs {
+        let strict = SliceLexer::new(s).parse_one(value::parse_unbounded);
+        assert_eq!(relaxed(s), strict.map(|v| v.to_string()));
+    }
+
+    assert_eq!(relaxed(b"'a"), Err(str::Error::Eof.into()));
+    assert_eq!(
+        relaxed(br"'\''"),
+        Err(str::Error::Escape(escape::Error::UnknownKind).into())
+    );
+    let low = escape::Error::ExpectedLowSurrogate;
+    assert_eq!(relaxed(br"'\uD800'"), Err(str::Error::Escape(low).into()));
+    let strict = SliceLexer::new(b"'a'").exactly_one(value::parse_unbounded);
+    assert_eq!(strict, Err(Expect::Value.into()));
+}
+
+#[test]
+fn str_bytes_utf8() {
+    use str::LexWrite;
+    let pieces: [&[u8]; 12] = [
+        b"a",
+        b"\xc3\xa4",
+        b"\xc3",
+        b"\xa4",
+        b"\xe2\x82\xac",
+        b"\xe0\x80\x80",
+        b"\xed\xa0\x80",
+        b"\xf0\x9f\x98\x80",
+        b"\xf4\x90\x80\x80",
+        b"\xc0\xaf",
+        b"\xff",
+        br"\n",
+    ];
+    for p1 in pieces {
+        for p2 in pieces {
+            for p3 in pieces {
+                let contents = [p1, p2, p3].concat();
+                let valid = std::str::from_utf8(&contents).is_ok();
+                let input = [&contents[..], b"\""].concat();
+
+                let mut bytes = Default::default();
+                let slice = SliceLexer::new(&input).str_bytes_utf8(&mut bytes);
+                assert_eq!(slice.is_ok(), valid);
+                assert!(slice.is_ok() || matches!(slice, Err(str::Error::Utf8(_))));
+
+                let quoted = [b"\"", &input[..]].concat();
+                let mut lexer = IterLexer::new(iter_of_slice(&quoted));
+                assert_eq!(lexer.ws_token(), Some(Token::Quote));
+                let mut iter_bytes = Vec::new();
+                let iter = lexer.str_bytes_utf8(&mut iter_bytes);
+                assert_eq!(iter.is_ok(), valid);
+            }
+        }
+    }
+
+    // reading stops at the offending byte
+    let mut lexer = IterLexer::new(iter_of_slice(b"\"a\xffbcd\""));
+    let mut bytes = Vec::new();
+    let token = lexer.ws_token();
+    assert_eq!(token, Some(Token::Quote));
+    assert!(lexer.str_bytes_utf8(&mut bytes).is_err());
+    assert_eq!(bytes, b"a");
+    assert!(!lexer.is_exhausted());
+}
+
+#[test]
+fn capture() {
+    let input = br#"[1.5e3, "a\"b", {"c": [null]}, true ]"#;
+    let mut lexer = SliceLexer::new(input);
+    let mut items = Vec::new();
+    let token = lexer.ws_token();
+    assert_eq!(token, Some(Token::LSquare));
+    loop {
+        lexer.eat_whitespace();
+        let (raw, ()) = lexer.capture(|l| l.parse_one(ignore::parse)).unwrap();
+        items.push(raw);
+        if lexer.ws_token() != Some(Token::Comma) {
+            break;
+        }
+    }
+    let expected: [&[u8]; 4] = [b"1.5e3", br#""a\"b""#, br#"{"c": [null]}"#, b"true"];
+    assert_eq!(items, expected);
+    assert!(lexer.is_empty());
+
+    // on failure, nothing is returned
+    let mut lexer = SliceLexer::new(b"[1, ]");
+    let captured = lexer.capture(|l| l.parse_one(ignore::parse));
+    assert_eq!(captured, Err(Expect::Value.into()));
+}
+
+#[test]
+fn hex_partial() {
+    use escape::Lex;
+    type Hex = Result<(u16, u8), escape::Error>;
+    let inputs: [(&[u8], Hex); 6] = [
+        (b"", Ok((0, 0))),
+        (b"a", Ok((0xa, 1))),
+        (b"00", Ok((0, 2))),
+        (b"FfF", Ok((0xfff, 3))),
+        (b"D83Dx", Ok((0xd83d, 4))),
+        (b"00g", Err(escape::Error::InvalidHex)),
+    ];
+    for (s, expected) in inputs {
+        assert_eq!(SliceLexer::new(s).hex_partial(), expected);
+        assert_eq!(IterLexer::new(iter_of_slice(s)).hex_partial(), expected);
+    }
+    // truncated Unicode escape sequences are still reported as such
+    assert_eq!(
+        SliceLexer::new(b"u00").escape().err(),
+        Some(escape::Error::Eof)
+    );
+}
+
+#[test]
+fn budget() -> Result<(), Error> {
+    use hifijson::budget::BudgetLexer;
+    let mut input = b"[".to_vec();
+    while input.len() < 1 << 20 {
+        input.extend_from_slice(br#"{"key": "value", "n": 12345},"#);
+    }
+    input.extend_from_slice(b"null]");
+
+    let budget = 64 << 10;
+    let mut lexer = BudgetLexer::new(SliceLexer::new(&input), budget);
+    let v = lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded));
+    assert_eq!(v.unwrap_err(), Error::Budget);
+    assert!(lexer.is_exhausted());
+
+    let lexer = IterLexer::new(iter_of_slice(&input));
+    let mut lexer = BudgetLexer::new(lexer, budget);
+    let v = lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded));
+    assert_eq!(v.unwrap_err(), Error::Budget);
+
+    // a single long string is cut off at the budget
+    let mut lexer = BudgetLexer::new(SliceLexer::new(br#""abcdef""#), 3);
+    let v = lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded));
+    assert_eq!(v.unwrap_err(), Error::Budget);
+
+    let mut lexer = BudgetLexer::new(SliceLexer::new(br#"["abc", 12]"#), 10);
+    lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded))?;
+    assert_eq!(lexer.remaining(), 5);
+
+    // long strings and numbers are not read beyond the budget
+    let long_str = [&b"\""[..], &[b'a'; 1000], b"\""].concat();
+    let long_num = [b'1'; 1000];
+    for input in [&long_str[..], &long_num] {
+        let mut read = 0;
+        let iter = iter_of_slice(input).inspect(|_| read += 1);
+        let mut lexer = BudgetLexer::new(IterLexer::new(iter), 8);
+        let v = lexer.run(|lexer| lexer.exactly_one(value::parse_unbounded));
+        assert_eq!(v.unwrap_err(), Error::Budget);
+        assert!(read < 16);
+    }
+
+    let mut lexer = BudgetLexer::new(IterLexer::new(iter_of_slice(&long_str)), 8);
+    let s = lexer.run(|lexer| {
+        lexer.ws_token();
+        Ok(hifijson::str::LexAlloc::str_string_lenient(lexer)?)
+    });
+    assert_eq!(s.unwrap_err(), Error::Budget);
+    Ok(())
+}
+
+#[test]
+fn object_select() -> Result<(), Error> {
+    let json = br#"{"a": 1, "ab": [2], "b": {"a": 3}, "ab": 4, "abc": 5, "": 6}"#;
+    let select = |keys: &[&str]| -> Result<Vec<(usize, String)>, Error> {
+        let mut found = Vec::new();
+        let mut lexer = SliceLexer::new(json);
+        lexer.exactly_one(|token, lexer| {
+            token.equals_or(Token::LCurly, Expect::Value)?;
+            lexer.object_select(keys, |i, token, lexer| {
+                found.push((i, value::parse_unbounded(token, lexer)?.to_string()));
+                Ok::<_, Error>(())
+            })
+        })?;
+
+        let mut iter_found = Vec::new();
+        let mut lexer = IterLexer::new(iter_of_slice(json));
+        lexer.exactly_one(|token, lexer| {
+            token.equals_or(Token::LCurly, Expect::Value)?;
+            lexer.object_select(keys, |i, token, lexer| {
+                iter_found.push((i, value::parse_unbounded(token, lexer)?.to_string()));
+                Ok::<_, Error>(())
+            })
+        })?;
+        assert_eq!(found, iter_found);
+        Ok(found)
+    };
+    let s = |i, v: &str| (i, v.to_string());
+    assert_eq!(select(&[])?, []);
+    assert_eq!(select(&["a"])?, [s(0, "1")]);
+    assert_eq!(select(&["abc", "ab"])?, [s(1, "[2]"), s(1, "4"), s(0, "5")]);
+    assert_eq!(select(&["b", "", "x"])?, [s(0, r#"{"a":3}"#), s(1, "6")]);
+
+    let mut lexer = SliceLexer::new(br#"{"a": 1 "b": 2}"#);
+    lexer.ws_token();
+    let v = lexer.object_select(&["b"], |_, token, lexer| ignore::parse(token, lexer));
+    assert_eq!(v, Err(Expect::CommaOrEnd.into()));
+    Ok(())
+}
+
+#[test]
+fn detect_encoding() {
+    use hifijson::{detect_encoding, Encoding::*};
+    let utf16le = |s: &str| {
+        s.encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect::<Vec<_>>()
+    };
+    let utf16be = |s: &str| {
+        s.encode_utf16()
+            .flat_map(|c| c.to_be_bytes())
+            .collect::<Vec<_>>()
+    };
+    let utf32le = |s: &str| {
+        s.chars()
+            .flat_map(|c| (c as u32).to_le_bytes())
+            .collect::<Vec<_>>()
+    };
+    let utf32be = |s: &str| {
+        s.chars()
+            .flat_map(|c| (c as u32).to_be_bytes())
+            .collect::<Vec<_>>()
+    };
+
+    for s in ["1", "[]", "\"ä\"", "\u{feff}{}", " true"] {
+        assert_eq!(detect_encoding(s.as_bytes()), Utf8);
+        assert_eq!(detect_encoding(&utf16le(s)), Utf16Le);
+        assert_eq!(detect_encoding(&utf16be(s)), Utf16Be);
+        assert_eq!(detect_encoding(&utf32le(s)), Utf32Le);
+        assert_eq!(detect_encoding(&utf32be(s)), Utf32Be);
+    }
+    assert_eq!(detect_encoding(b""), Utf8);
+    assert_eq!(detect_encoding(b"\xff\xfe"), Utf16Le);
+    assert_eq!(detect_encoding(b"\xfe\xff"), Utf16Be);
+}
+
+#[test]
+fn empty_input() {
+    for s in [&b""[..], b"  ", b"\n\t\r "] {
+        let v = SliceLexer::new(s).exactly_one(ignore::parse);
+        assert_eq!(v, Err(Expect::EmptyInput.into()));
+        let v = IterLexer::new(iter_of_slice(s)).exactly_one(ignore::parse);
+        assert_eq!(v, Err(Expect::EmptyInput.into()));
+    }
+    let err = SliceLexer::new(b" ")
+        .exactly_one(ignore::parse)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "empty input");
+
+    // invalid input is still reported differently
+    let v = SliceLexer::new(b" ]").exactly_one(ignore::parse);
+    assert_eq!(v, Err(Expect::Value.into()));
+}
+
+#[test]
+fn str_eq() {
+    use str::Lex;
+    let eq = |s: &[u8], expected: &str| {
+        let mut lexer = SliceLexer::new(s);
+        assert_eq!(lexer.ws_token(), Some(Token::Quote));
+        let slice = lexer.str_eq(expected);
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert_eq!(lexer.ws_token(), Some(Token::Quote));
+        assert_eq!(slice, lexer.str_eq(expected));
+        slice
+    };
+    assert_eq!(eq(br#""foo""#, "foo"), Ok(true));
+    assert_eq!(eq(br#""foo""#, "bar"), Ok(false));
+    assert_eq!(eq(br#""foo""#, "fo"), Ok(false));
+    assert_eq!(eq(br#""foo""#, "fooo"), Ok(false));
+    assert_eq!(eq(br#""""#, ""), Ok(true));
+    assert_eq!(eq(br#""\u00e4\"""#, "ä\""), Ok(true));
+    assert_eq!(
+        eq(br#""x\q""#, "y"),
+        Err(str::Error::Escape(escape::Error::UnknownKind))
+    );
+    assert_eq!(eq(br#""x"#, "y"), Err(str::Error::Eof));
+
+    // the whole string is consumed even on a mismatch
+    let mut lexer = SliceLexer::new(br#""bar", 1"#);
+    lexer.ws_token();
+    assert_eq!(lexer.str_eq("foo"), Ok(false));
+    assert_eq!(lexer.as_slice(), b", 1");
+}
+
+#[test]
+fn whitespace_with() {
+    fn tokens<L: hifijson::Lex>(lexer: &mut L) -> Vec<Token> {
+        let is_space = |c| matches!(c, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c');
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.ws_token_with(is_space) {
+            if token == Token::DigitOrMinus {
+                lexer.num_ignore().unwrap();
+            }
+            tokens.push(token)
+        }
+        tokens
+    }
+
+    let s = b"\x0c [ \x0c1,\x0c \x0c2 ]\x0c";
+    use Token::*;
+    let expected = [LSquare, DigitOrMinus, Comma, DigitOrMinus, RSquare];
+    assert_eq!(tokens(&mut SliceLexer::new(s)), expected);
+    assert_eq!(tokens(&mut IterLexer::new(iter_of_slice(s))), expected);
+
+    // the default whitespace does not include form feed
+    assert_eq!(SliceLexer::new(b"\x0c1").ws_token(), Some(Token::Error));
+}
+
+#[test]
+fn peek_token_then_parse() -> Result<(), Error> {
+    for s in [
+        &b" null"[..],
+        b"true",
+        b"\nfalse",
+        b"-0",
+        br#""a""#,
+        b"[1]",
+        b"{}",
+    ] {
+        let mut lexer = SliceLexer::new(s);
+        let kind = lexer.peek_token();
+        let v = lexer.exactly_one(value::parse_unbounded)?;
+        assert_eq!(kind, SliceLexer::new(s).ws_token().and_then(|t| t.kind()));
+        assert!(kind.is_some());
+
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        assert_eq!(lexer.peek_token(), kind);
+        assert_eq!(lexer.exactly_one(value::parse_unbounded)?, v);
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_recovering() -> Result<(), Error> {
+    fn recover(s: &[u8]) -> (String, Vec<Error>) {
+        let mut lexer = SliceLexer::new(s);
+        let token = lexer.ws_token().unwrap();
+        let (v, errors) = value::parse_recovering(token, &mut lexer);
+        let errors: Vec<_> = errors.into_iter().map(|(_, e)| e).collect();
+
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let token = lexer.ws_token().unwrap();
+        let (iter_v, iter_errors) = value::parse_recovering(token, &mut lexer);
+        assert_eq!(v, iter_v);
+        assert_eq!(
+            errors,
+            iter_errors.into_iter().map(|(_, e)| e).collect::<Vec<_>>()
+        );
+        (v.to_string(), errors)
+    }
+    use Expect as E;
+    let recovered = |v: &str, errors: Vec<Expect>| {
+        let errors = errors.into_iter().map(Error::Token).collect();
+        (v.to_string(), errors)
+    };
+
+    // valid documents are parsed like with `parse_unbounded`
+    let valid = br#"{"a": [1, null, {"b": "c"}], "d": {}, "e": []}"#;
+    let v = SliceLexer::new(valid).exactly_one(value::parse_unbounded)?;
+    assert_eq!(recover(valid), (v.to_string(), Vec::new()));
+
+    assert_eq!(recover(b"[1,,2]"), recovered("[1,null,2]", vec![E::Value]));
+    assert_eq!(recover(b"[1,]"), recovered("[1]", vec![E::Value]));
+    assert_eq!(recover(b"[1 2]"), recovered("[1,2]", vec![E::CommaOrEnd]));
+    assert_eq!(recover(b"[1 : 2]"), recovered("[1]", vec![E::CommaOrEnd]));
+    assert_eq!(
+        recover(b"[[1, 2], 3}"),
+        recovered("[[1,2],3]", vec![E::CommaOrEnd])
+    );
+    assert_eq!(recover(b"[[1"), recovered("[[1]]", vec![E::CommaOrEnd]));
+    assert_eq!(recover(b"["), recovered("[]", vec![E::ValueOrEnd]));
+    assert_eq!(
+        recover(br#"[{"a": [1, {"x": }]}, 2]"#),
+        recovered(r#"[{"a":[1,{"x":null}]},2]"#, vec![E::Value])
+    );
+    assert_eq!(
+        recover(br#"{"a": 1, 2: 3, "b" 4, "c":, , "d": "e, ]"}"#),
+        recovered(
+            r#"{"a":1,"b":4,"c":null,"d":"e, ]"}"#,
+            vec![E::String, E::Colon, E::Value, E::String]
+        )
+    );
+
+    let (v, errors) = recover(b"[-x, \"a\\qb, c\", \"d\te\", 1]");
+    assert_eq!(v, "[null,null,null,1]");
+    assert_eq!(
+        errors,
+        [
+            Error::Num(num::Error::ExpectedDigit),
+            Error::Str(str::Error::Escape(escape::Error::UnknownKind)),
+            Error::Str(str::Error::Control)
+        ]
+    );
+
+    // errors are reported with their offset
+    let mut lexer = SliceLexer::new(b"[1, 2 3]");
+    let token = lexer.ws_token().unwrap();
+    let (_, errors) = value::parse_recovering(token, &mut lexer);
+    assert_eq!(errors, [(5, Error::Token(E::CommaOrEnd))]);
+    Ok(())
+}
+
+#[test]
+fn string_end() {
+    let ends: Vec<u8> = (0..=u8::MAX).filter(|c| str::string_end(*c)).collect();
+    let expected: Vec<u8> = (0..0x20).chain([b'"', b'\\']).collect();
+    assert_eq!(ends, expected);
+
+    fn until_sentinel<L: hifijson::LexWrite>(lexer: &mut L) -> Vec<u8> {
+        let mut bytes = Default::default();
+        lexer.write_until(&mut bytes, |c| str::string_end(c) || c == b'|');
+        bytes.to_vec()
+    }
+    for s in [&b"ab|c"[..], b"ab\"c", b"ab\\c", b"ab\nc", b"ab"] {
+        assert_eq!(until_sentinel(&mut SliceLexer::new(s)), b"ab");
+        assert_eq!(until_sentinel(&mut IterLexer::new(iter_of_slice(s))), b"ab");
+    }
+}
+
+#[test]
+fn keyword() {
+    // parse `null`, `true`, `false`, and `undefined`
+    fn literal<L: hifijson::Lex>(lexer: &mut L) -> Option<Option<bool>> {
+        lexer.eat_whitespace();
+        match lexer.peek_next()? {
+            b'u' => lexer.keyword(b"undefined").then(|| None),
+            _ => match lexer.ws_token()? {
+                Token::Null => Some(None),
+                Token::True => Some(Some(true)),
+                Token::False => Some(Some(false)),
+                _ => None,
+            },
+        }
+    }
+    let s = b"null true undefined false undefined";
+    let expected = [None, Some(true), None, Some(false), None].map(Some);
+    let mut lexer = SliceLexer::new(s);
+    assert_eq!([(); 5].map(|_| literal(&mut lexer)), expected);
+    assert_eq!(literal(&mut lexer), None);
+    let mut lexer = IterLexer::new(iter_of_slice(s));
+    assert_eq!([(); 5].map(|_| literal(&mut lexer)), expected);
+    assert_eq!(literal(&mut lexer), None);
+
+    // mismatches
+    let mut lexer = SliceLexer::new(b"undef");
+    assert!(!lexer.keyword(b"null"));
+    assert_eq!(lexer.as_slice(), b"undef");
+    assert!(!lexer.keyword(b"undefined"));
+    assert!(lexer.keyword(b""));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn read_error() {
+    use std::io::{self, Read};
+    struct Broken;
+    impl Read for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    for prefix in [&br#"["abc"#[..], b"[1, 2", b"{", b""] {
+        let mut lexer = IterLexer::from_read(prefix.chain(Broken));
+        let v = lexer.exactly_one_read(value::parse_unbounded);
+        assert_eq!(v, Err(Error::Read));
+        let e = lexer.take_error().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+        assert!(lexer.take_error().is_none());
+    }
+
+    // without read error, errors are reported as usual
+    let mut lexer = IterLexer::new(iter_of_slice(b"[1, 2"));
+    let v = lexer.exactly_one_read(value::parse_unbounded);
+    assert_eq!(v, Err(Expect::CommaOrEnd.into()));
+    let mut lexer = IterLexer::new(iter_of_slice(b"[1, 2]"));
+    assert!(lexer.exactly_one_read(value::parse_unbounded).is_ok());
+}
+
+#[test]
+fn parse_pointer() {
+    fn get(s: &[u8], ptr: &str) -> Result<Option<String>, Error> {
+        let v = SliceLexer::new(s).exactly_one(|t, l| value::parse_pointer(ptr, t, l));
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let w = lexer.exactly_one(|t, l| value::parse_pointer(ptr, t, l));
+        let v = v.map(|v| v.map(|v| v.to_string()));
+        assert_eq!(v, w.map(|w| w.map(|w| w.to_string())));
+        v
+    }
+
+    let s = br#"{"a": [1, {"b": true}, [2]], "a/b": null, "~": 3, "": 4, "a": 5}"#;
+    assert_eq!(get(s, "/a/1/b").unwrap().as_deref(), Some("true"));
+    assert_eq!(get(s, "/a/2/0").unwrap().as_deref(), Some("2"));
+    assert_eq!(get(s, "/a~1b").unwrap().as_deref(), Some("null"));
+    assert_eq!(get(s, "/~0").unwrap().as_deref(), Some("3"));
+    assert_eq!(get(s, "/").unwrap().as_deref(), Some("4"));
+    assert_eq!(get(b"[1, 2]", "").unwrap().as_deref(), Some("[1,2]"));
+
+    // first occurrence of a key wins
+    assert_eq!(get(s, "/a/0").unwrap().as_deref(), Some("1"));
+
+    for ptr in [
+        "/b", "/a/3", "/a/-", "/a/x", "/a/01", "/a/+1", "/a/0/0", "a",
+    ] {
+        assert_eq!(get(s, ptr), Ok(None));
+    }
+
+    // errors after the target are still reported
+    assert_eq!(get(b"[1, 2", "/0"), Err(Expect::CommaOrEnd.into()));
+}
+
+#[test]
+fn str_colon_eq() {
+    fn test(s: &[u8], key: &str) -> Result<bool, Error> {
+        let mut lexer = SliceLexer::new(s);
+        let token = lexer.ws_token().unwrap();
+        let eq = lexer.str_colon_eq(token, key);
+        if eq.is_ok() {
+            assert_eq!(lexer.as_slice(), b" [1]");
+        }
+
+        let mut lexer = IterLexer::new(iter_of_slice(s));
+        let token = lexer.ws_token().unwrap();
+        assert_eq!(lexer.str_colon_eq(token, key), eq);
+        eq
+    }
+
+    assert_eq!(test(br#""a\nb" : [1]"#, "a\nb"), Ok(true));
+    assert_eq!(test(br#""a\nb" : [1]"#, "a"), Ok(false));
+    assert_eq!(test(br#""a\nb" : [1]"#, "a\nbc"), Ok(false));
+    assert_eq!(test(br#""" : [1]"#, ""), Ok(true));
+    assert_eq!(test(br#""a" [1]"#, "a"), Err(Expect::Colon.into()));
+    assert_eq!(test(b"1: [1]", "1"), Err(Expect::String.into()));
+}
+
+#[test]
+fn parse_in() -> Result<(), Error> {
+    use value::{GenericValue, Maps, Vecs};
+    let s = br#"{"b": [{"d": 1, "c": 2}], "a": null, "b": true}"#;
+
+    let v: GenericValue<_, _, Vecs> = SliceLexer::new(s).exactly_one(value::parse_in)?;
+    let w = SliceLexer::new(s).exactly_one(value::parse_unbounded)?;
+    assert_eq!(v.clone(), v);
+    assert_eq!(format!("{:?}", v), format!("{:?}", w));
+
+    let m: GenericValue<_, _, Maps> = SliceLexer::new(s).exactly_one(value::parse_in)?;
+    let n: GenericValue<_, String, Maps> =
+        IterLexer::new(iter_of_slice(s)).exactly_one(value::parse_in)?;
+    assert_eq!(m.clone(), m);
+    assert_eq!(format!("{:?}", m), format!("{:?}", n));
+    let obj = match m {
+        GenericValue::Object(obj) => obj,
+        _ => panic!("object expected"),
+    };
+    assert_eq!(obj.keys().map(|k| &**k).collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(obj["b"], GenericValue::Bool(true));
+    assert_eq!(obj["a"], GenericValue::Null);
+    Ok(())
+}
+
+#[test]
+fn value_inference() {
+    let o = Value::Object(vec![("a".to_string(), Value::<String, String>::Bool(true))]);
+    let a = Value::Array(vec![o.clone(), Value::Null]);
+    assert_eq!(
+        format!("{:?}", a),
+        r#"Array([Object([("a", Bool(true))]), Null])"#
+    );
+}
+
+#[test]
+fn dyn_iter() {
+    use hifijson::DynIterLexer;
+    fn parse(lexer: &mut DynIterLexer<()>) -> Result<String, Error> {
+        let v = lexer.exactly_one(value::parse_unbounded)?;
+        Ok(v.to_string())
+    }
+
+    let inputs: [&[u8]; 4] = [br#"{"a": [1, "b\n"]}"#, b"  null ", b"[1, 2", b"1 2"];
+    for s in inputs {
+        let mut iter = iter_of_slice(s);
+        let v = parse(&mut IterLexer::from_dyn(&mut iter));
+        let w = SliceLexer::new(s).exactly_one(value::parse_unbounded);
+        assert_eq!(v, w.map(|w| w.to_string()));
+    }
+
+    let mut bytes = [Ok(b'['), Err(())].into_iter();
+    let mut lexer = IterLexer::from_dyn(&mut bytes);
+    assert_eq!(lexer.exactly_one_read(ignore::parse), Err(Error::Read));
+    assert_eq!(lexer.take_error(), Some(()));
+}
+
+#[test]
+fn owned_slice_lexer() -> Result<(), Error> {
+    use hifijson::OwnedSliceLexer;
+    fn open(s: &str) -> OwnedSliceLexer {
+        OwnedSliceLexer::new(s.as_bytes().to_vec())
+    }
+
+    let mut lexer = open(r#"{"a": []} [1] x"#);
+    let v = lexer.with(|lexer| lexer.parse_one(value::parse_unbounded))?;
+    assert_eq!(v.to_string(), r#"{"a":[]}"#);
+    let v = lexer.with(|lexer| lexer.parse_one(value::parse_unbounded))?;
+    assert_eq!(v.to_string(), "[1]");
+    assert_eq!(lexer.as_slice(), b" x");
+    let v = lexer.with(|lexer| lexer.parse_one(value::parse_unbounded));
+    assert_eq!(v.unwrap_err(), Expect::Value.into());
+
+    // resetting to other input discards the original input
+    let mut reset = open("[1] [2]");
+    let v = reset.with(|lexer| {
+        lexer.reset(b"[3, 4, 5, 6, 7, 8, 9] [10]");
+        lexer.parse_one(value::parse_unbounded)
+    })?;
+    assert_eq!(v.to_string(), "[3,4,5,6,7,8,9]");
+    assert_eq!(reset.as_slice(), b"");
+
+    assert_eq!(lexer.into_inner(), br#"{"a": []} [1] x"#);
+    Ok(())
+}
+
+#[test]
+fn find() -> Result<(), Error> {
+    fn find(s: &[u8], n: &str) -> Result<(Option<String>, usize), Error> {
+        let mut lexer = SliceLexer::new(s);
+        let token = lexer.ws_token().ok_or(Expect::Value)?;
+        let v = value::find(token, &mut lexer, |v| v.to_string() == n)?;
+        let v = v.map(|v| v.to_string());
+
+        let mut iter = iter_of_slice(s);
+        let mut ilexer = IterLexer::new(&mut iter);
+        let token = ilexer.ws_token().ok_or(Expect::Value)?;
+        let w = value::find(token, &mut ilexer, |w| w.to_string() == n)?;
+        let w = w.map(|w| w.to_string());
+        assert_eq!(v, w);
+        Ok((v, lexer.as_slice().len()))
+    }
+
+    let s = br#"[1, [2], {"a": 3}, 4]"#;
+    assert_eq!(find(s, "[2]")?, (Some("[2]".into()), 14));
+    assert_eq!(find(s, "4")?, (Some("4".into()), 1));
+    assert_eq!(find(s, "5")?, (None, 0));
+    assert_eq!(find(b"[]", "[]")?, (None, 0));
+    assert_eq!(find(b"[]  ", "1")?, (None, 2));
+    assert_eq!(find(b"{}", "{}")?, (Some("{}".into()), 0));
+    assert_eq!(find(b"3", "4")?, (None, 0));
+
+    // errors before a match are reported, errors after it are not reached
+    assert_eq!(find(b"[1, 2 3]", "3"), Err(Expect::CommaOrEnd.into()));
+    assert_eq!(find(b"[1, 2 3]", "1")?, (Some("1".into()), 6));
     Ok(())
 }